@@ -0,0 +1,118 @@
+//! Uniform "instruction executed" audit trail, emitted through `EmitEvent`.
+
+use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+#[cfg(feature = "access-log")]
+use pinocchio::{program_error::ProgramError, ProgramResult};
+
+use crate::{event, INSTRUCTION_EXECUTED_EVENT};
+#[cfg(feature = "access-log")]
+use crate::{
+    event_authority_signer, get_clock, set_panic_instruction, EmitEvent, Validation,
+    EMIT_EVENT_DISCRIMINATOR,
+};
+
+/// Emitted once per instruction by [`dispatch_logged`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstructionExecuted {
+    /// Always [`INSTRUCTION_EXECUTED_EVENT`].
+    pub event: u8,
+    /// Instruction discriminator.
+    pub instruction: u8,
+    pub _padding: [u8; 6],
+    /// First signer of the instruction, zeroed if there is none.
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+event!(InstructionExecuted);
+
+impl InstructionExecuted {
+    pub fn new(instruction: u8, accounts: &[AccountInfo], slot: u64) -> Self {
+        let signer = accounts
+            .iter()
+            .find(|ai| ai.is_signer())
+            .map_or([0; 32], |ai| *ai.key());
+        Self {
+            event: INSTRUCTION_EXECUTED_EVENT,
+            instruction,
+            _padding: [0; 6],
+            signer,
+            slot,
+        }
+    }
+}
+
+/// Splits off the instruction discriminator, runs `handler` and emits an
+/// [`InstructionExecuted`] event once it succeeds.
+///
+/// The event authority and program accounts are expected as the last two
+/// accounts of every instruction and are not passed to `handler`.
+///
+/// The `EmitEvent` self-CPI comes back through the same entrypoint with only
+/// the event authority, it is accepted here without reaching `handler`.
+///
+/// ```ignore
+/// pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+///     dispatch_logged(program_id, EVENT_AUTHORITY_BUMP, accounts, data, |ix, accounts, args| {
+///         match MyInstruction::try_from(ix) { ... }
+///     })
+/// }
+/// ```
+#[cfg(feature = "access-log")]
+pub fn dispatch_logged<F>(
+    program_id: &Pubkey,
+    event_authority_bump: u8,
+    accounts: &[AccountInfo],
+    data: &[u8],
+    handler: F,
+) -> ProgramResult
+where
+    F: FnOnce(u8, &[AccountInfo], &[u8]) -> ProgramResult,
+{
+    let (&instruction, args) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if instruction == EMIT_EVENT_DISCRIMINATOR {
+        let [event_authority, ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        return Validation::default()
+            .is_event_authority(program_id, event_authority_bump)
+            .run(event_authority);
+    }
+    let [accounts @ .., event_authority, program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    set_panic_instruction(instruction);
+    handler(instruction, accounts, args)?;
+
+    let event = InstructionExecuted::new(instruction, accounts, get_clock()?.slot);
+    event_authority_signer(event_authority_bump).with_signer(|signers| {
+        EmitEvent {
+            program_id,
+            program,
+            event_authority,
+            data: event.to_bytes(),
+        }
+        .invoke_signed(signers)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_executed_layout() {
+        assert_eq!(core::mem::size_of::<InstructionExecuted>(), 48);
+
+        let event = InstructionExecuted::new(3, &[], 42);
+        let bytes = event.to_bytes();
+        assert_eq!(bytes[0], INSTRUCTION_EXECUTED_EVENT);
+        assert_eq!(bytes[1], 3);
+        assert_eq!(&bytes[8..40], &[0; 32]);
+        assert_eq!(&bytes[40..], &42u64.to_le_bytes());
+    }
+}
@@ -0,0 +1,138 @@
+//! Read-only access to accounts of other programs.
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{trace, AccountDeserialize, Discriminator};
+
+/// Account type owned by another program, see [`external_account!`](crate::external_account).
+pub trait ExternalAccount: AccountDeserialize + Discriminator {
+    /// Program that owns accounts of this type.
+    const PROGRAM_ID: Pubkey;
+}
+
+/// Read-only deserialization of [`ExternalAccount`]s.
+pub trait AsExternalAccount {
+    /// Checks that the account is owned by `T::PROGRAM_ID` and holds a `T`,
+    /// then borrows it. There is no mutable counterpart, as only the owning
+    /// program can write the account.
+    fn as_external<T: ExternalAccount>(&self) -> Result<Ref<T>, ProgramError>;
+}
+
+impl AsExternalAccount for AccountInfo {
+    #[track_caller]
+    fn as_external<T: ExternalAccount>(&self) -> Result<Ref<T>, ProgramError> {
+        if !self.is_owned_by(&T::PROGRAM_ID) {
+            return Err(trace(
+                "External account has wrong owner",
+                ProgramError::InvalidAccountOwner,
+            ));
+        }
+        let data = self.try_borrow_data()?;
+        let account: *const T = T::try_from_bytes(&data)?;
+        // SAFETY: `account` points into `data`, which the mapped `Ref` keeps borrowed
+        Ok(Ref::map(data, |_| unsafe { &*account }))
+    }
+}
+
+/// Declare the layout of an account owned by another program, to read it with
+/// [`AsExternalAccount::as_external`].
+///
+/// The struct must be `#[repr(C)]` and mirror the foreign layout, starting with
+/// its discriminator. Both single byte and multi-byte discriminators are supported:
+///
+/// ```ignore
+/// external_account!(VoteRecord, program = GOVERNANCE_PROGRAM_ID, discriminator = 12);
+/// external_account!(StakePool, program = STAKING_PROGRAM_ID, discriminator = [0xf1, 0x9a, 0x6d, 0x04, 0x11, 0xb1, 0x6d, 0xbc]);
+///
+/// let record = vote_record_info.as_external::<VoteRecord>()?;
+/// ```
+#[macro_export]
+macro_rules! external_account {
+    ($struct_name:ident, program = $program_id:expr, discriminator = [$($byte:expr),+ $(,)?]) => {
+        $crate::account!($struct_name, discriminator = [$($byte),+]);
+        $crate::external_account!(@impl $struct_name, $program_id);
+    };
+    ($struct_name:ident, program = $program_id:expr, discriminator = $discriminator:expr) => {
+        $crate::account!(@impl $struct_name, $discriminator, None, None, []);
+        $crate::external_account!(@impl $struct_name, $program_id);
+    };
+    (@impl $struct_name:ident, $program_id:expr) => {
+        impl $crate::ExternalAccount for $struct_name {
+            const PROGRAM_ID: pinocchio::pubkey::Pubkey = $program_id;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockAccount, Validation};
+
+    const GOVERNANCE_PROGRAM_ID: Pubkey = [5; 32];
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct VoteRecord {
+        discriminator: u8,
+        weight: u8,
+    }
+
+    external_account!(
+        VoteRecord,
+        program = GOVERNANCE_PROGRAM_ID,
+        discriminator = 12
+    );
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct StakePool {
+        discriminator: [u8; 8],
+        total: u64,
+    }
+
+    external_account!(
+        StakePool,
+        program = GOVERNANCE_PROGRAM_ID,
+        discriminator = [0xf1, 0x9a, 0x6d, 0x04, 0x11, 0xb1, 0x6d, 0xbc]
+    );
+
+    #[test]
+    fn test_as_external() {
+        let mut record = MockAccount::new([1; 32], GOVERNANCE_PROGRAM_ID, 100, &[12, 7]);
+        let mut foreign = MockAccount::new([2; 32], [6; 32], 100, &[12, 7]);
+        let mut pool_data = [0u8; 16];
+        pool_data[..8].copy_from_slice(&[0xf1, 0x9a, 0x6d, 0x04, 0x11, 0xb1, 0x6d, 0xbc]);
+        pool_data[8..].copy_from_slice(&42u64.to_le_bytes());
+        let mut pool = MockAccount::new([3; 32], GOVERNANCE_PROGRAM_ID, 100, &pool_data);
+
+        assert_eq!(record.info().as_external::<VoteRecord>().unwrap().weight, 7);
+        assert_eq!(pool.info().as_external::<StakePool>().unwrap().total, 42);
+        assert!(matches!(
+            foreign.info().as_external::<VoteRecord>(),
+            Err(ProgramError::InvalidAccountOwner)
+        ));
+        assert!(matches!(
+            record.info().as_external::<StakePool>(),
+            Err(ProgramError::InvalidAccountData)
+        ));
+
+        // `is_type_of` compares all 8 bytes, unlike the first byte checked by `is_type`
+        let mut fake_pool_data = pool_data;
+        fake_pool_data[7] ^= 1;
+        let mut fake_pool = MockAccount::new([4; 32], GOVERNANCE_PROGRAM_ID, 100, &fake_pool_data);
+        let validation = Validation::default().is_type_of::<StakePool>(&GOVERNANCE_PROGRAM_ID);
+        assert!(validation.run(&pool.info()).is_ok());
+        assert_eq!(
+            validation.run(&fake_pool.info()),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert!(Validation::default()
+            .is_type_of::<VoteRecord>(&GOVERNANCE_PROGRAM_ID)
+            .run(&record.info())
+            .is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::trace;
+
+/// Checked direct lamport math, for accounts the program owns or closes.
+pub trait LamportsExt {
+    /// Adds `amount`, failing with `ArithmeticOverflow` instead of wrapping.
+    fn lamports_checked_add(&self, amount: u64) -> ProgramResult;
+
+    /// Subtracts `amount`, failing with `InsufficientFunds` instead of wrapping.
+    fn lamports_checked_sub(&self, amount: u64) -> ProgramResult;
+
+    /// Moves `amount` to `to` with both checks.
+    #[inline(always)]
+    fn transfer_lamports_to(&self, to: &AccountInfo, amount: u64) -> ProgramResult {
+        self.lamports_checked_sub(amount)?;
+        to.lamports_checked_add(amount)
+    }
+}
+
+impl LamportsExt for AccountInfo {
+    #[track_caller]
+    #[inline(always)]
+    fn lamports_checked_add(&self, amount: u64) -> ProgramResult {
+        let mut lamports = self.try_borrow_mut_lamports()?;
+        *lamports = lamports
+            .checked_add(amount)
+            .ok_or_else(|| trace("Lamports overflow", ProgramError::ArithmeticOverflow))?;
+        Ok(())
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn lamports_checked_sub(&self, amount: u64) -> ProgramResult {
+        let mut lamports = self.try_borrow_mut_lamports()?;
+        *lamports = lamports
+            .checked_sub(amount)
+            .ok_or_else(|| trace("Insufficient lamports", ProgramError::InsufficientFunds))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use core::slice::ChunksExact;
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+use pinocchio_log::log;
+
+use crate::{trace, Validation};
+
+/// Returns the instruction accounts as a fixed-size array, failing unless
+/// there are exactly `N` of them.
+///
+/// ```ignore
+/// let [payer, config, vault] = expect_accounts(accounts)?;
+/// ```
+#[track_caller]
+#[inline(always)]
+pub fn expect_accounts<const N: usize>(
+    accounts: &[AccountInfo],
+) -> Result<&[AccountInfo; N], ProgramError> {
+    if accounts.len() > N {
+        return Err(trace("Too many accounts", ProgramError::InvalidArgument));
+    }
+    let (fixed, _) = split_fixed_rest::<N>(accounts)?;
+    Ok(fixed)
+}
+
+/// Splits the instruction accounts into a fixed-size prefix of `N` accounts and
+/// the remaining accounts.
+///
+/// ```ignore
+/// let ([payer, config], remaining) = split_fixed_rest(accounts)?;
+/// ```
+#[track_caller]
+#[inline(always)]
+pub fn split_fixed_rest<const N: usize>(
+    accounts: &[AccountInfo],
+) -> Result<(&[AccountInfo; N], &[AccountInfo]), ProgramError> {
+    if accounts.len() < N {
+        return Err(trace(
+            "Not enough accounts",
+            ProgramError::NotEnoughAccountKeys,
+        ));
+    }
+    let (fixed, rest) = accounts.split_at(N);
+    // SAFETY: `fixed` is exactly N accounts long
+    Ok((fixed.try_into().unwrap(), rest))
+}
+
+/// Iterates over remaining accounts in groups of `N`, validating the account
+/// at each position of a group with the rule at the same position.
+///
+/// ```ignore
+/// let rules = [
+///     Validation::default().is_writable(true).has_owner(&TOKEN_PROGRAM_ID),
+///     Validation::default().has_owner(&TOKEN_PROGRAM_ID),
+/// ];
+/// for item in account_chunks(remaining, rules)? {
+///     let [token_account, mint] = item?;
+/// }
+/// ```
+#[track_caller]
+#[inline(always)]
+pub fn account_chunks<'a, 'v, const N: usize>(
+    accounts: &'a [AccountInfo],
+    rules: [Validation<'v>; N],
+) -> Result<AccountChunks<'a, 'v, N>, ProgramError> {
+    if N == 0 || accounts.len() % N != 0 {
+        return Err(trace(
+            "Remaining accounts are not a multiple of the group size",
+            ProgramError::NotEnoughAccountKeys,
+        ));
+    }
+    Ok(AccountChunks {
+        chunks: accounts.chunks_exact(N),
+        rules,
+        index: 0,
+    })
+}
+
+/// Iterator returned by [`account_chunks`].
+pub struct AccountChunks<'a, 'v, const N: usize> {
+    chunks: ChunksExact<'a, AccountInfo>,
+    rules: [Validation<'v>; N],
+    index: usize,
+}
+
+impl<'a, const N: usize> Iterator for AccountChunks<'a, '_, N> {
+    type Item = Result<&'a [AccountInfo; N], ProgramError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index;
+        let chunk = self.chunks.next()?;
+        self.index += 1;
+        for (position, (ai, validation)) in chunk.iter().zip(self.rules).enumerate() {
+            let mut rule = "";
+            if let Err(e) = validation.run_reporting(ai, &mut rule) {
+                log!("account {} of group {} failed {}", position, index, rule);
+                return Some(Err(e));
+            }
+        }
+        // SAFETY: `chunks_exact` yields exactly N accounts
+        Some(Ok(chunk.try_into().unwrap()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for AccountChunks<'_, '_, N> {}
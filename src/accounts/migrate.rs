@@ -0,0 +1,120 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey::Pubkey,
+    ProgramResult,
+};
+
+use crate::{
+    trace, AccountDeserialize, CloseProgramAccount, CreateProgramAccount, Discriminator,
+    ResizeProgramAccount,
+};
+
+/// Account type changes.
+pub trait MigrateAccount {
+    /// Performs an in-place account layout upgrade:
+    /// 1. Program owner check and copy of the account data as `TOld`
+    /// 2. Resize to `size_of::<TNew>()` via [`ResizeProgramAccount`], funded by `payer`
+    /// 3. Zero-fill of the account data and write of the `TNew` discriminator and namespace
+    /// 4. User supplied field mapping from the old copy into `TNew`
+    fn realloc_and_migrate<TOld, TNew, F>(
+        &self,
+        payer: &AccountInfo,
+        program_id: &Pubkey,
+        migrate: F,
+    ) -> ProgramResult
+    where
+        TOld: AccountDeserialize + Discriminator + Copy,
+        TNew: AccountDeserialize + Discriminator,
+        F: FnOnce(&TOld, &mut TNew);
+
+    /// Closes the account, refunding all its lamports to `payer`, and recreates
+    /// it as a zeroed `TNew` with only the discriminator and namespace set.
+    ///
+    /// Unlike `realloc_and_migrate`, nothing of the old state is carried over.
+    fn close_and_reopen<TNew>(
+        &self,
+        payer: &AccountInfo,
+        program_id: &Pubkey,
+        signers: &[Signer],
+    ) -> ProgramResult
+    where
+        TNew: AccountDeserialize + Discriminator;
+}
+
+impl MigrateAccount for AccountInfo {
+    fn realloc_and_migrate<TOld, TNew, F>(
+        &self,
+        payer: &AccountInfo,
+        program_id: &Pubkey,
+        migrate: F,
+    ) -> ProgramResult
+    where
+        TOld: AccountDeserialize + Discriminator + Copy,
+        TNew: AccountDeserialize + Discriminator,
+        F: FnOnce(&TOld, &mut TNew),
+    {
+        // Validate account owner.
+        if !self.is_owned_by(program_id) {
+            return Err(trace(
+                "Account has wrong owner",
+                ProgramError::InvalidAccountOwner,
+            ));
+        }
+
+        // Copy the old state out, the data is overwritten below.
+        let old = *TOld::try_from_bytes(&self.try_borrow_data()?)?;
+
+        ResizeProgramAccount {
+            payer,
+            pda: self,
+            space: core::mem::size_of::<TNew>(),
+            program: program_id,
+        }
+        .invoke()?;
+
+        let mut data = self.try_borrow_mut_data()?;
+        data.fill(0);
+        TNew::write_discriminator(&mut data);
+
+        let new = TNew::try_from_bytes_mut(&mut data)?;
+        migrate(&old, new);
+
+        Ok(())
+    }
+
+    fn close_and_reopen<TNew>(
+        &self,
+        payer: &AccountInfo,
+        program_id: &Pubkey,
+        signers: &[Signer],
+    ) -> ProgramResult
+    where
+        TNew: AccountDeserialize + Discriminator,
+    {
+        // Validate account owner.
+        if !self.is_owned_by(program_id) {
+            return Err(trace(
+                "Account has wrong owner",
+                ProgramError::InvalidAccountOwner,
+            ));
+        }
+
+        CloseProgramAccount {
+            account: self,
+            destination: payer,
+        }
+        .invoke()?;
+
+        CreateProgramAccount {
+            payer,
+            pda: self,
+            space: core::mem::size_of::<TNew>(),
+            owner: program_id,
+        }
+        .invoke_signed(signers)?;
+
+        let mut data = self.try_borrow_mut_data()?;
+        TNew::write_discriminator(&mut data);
+
+        Ok(())
+    }
+}
@@ -1,3 +1,17 @@
+mod external;
+mod lamports;
+mod list;
+mod migrate;
+mod order;
+mod spec;
+mod sweep;
 mod validation;
 
+pub use external::*;
+pub use lamports::*;
+pub use list::*;
+pub use migrate::*;
+pub use order::*;
+pub use spec::*;
+pub use sweep::*;
 pub use validation::*;
@@ -0,0 +1,111 @@
+use core::cmp::Ordering;
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::trace;
+
+pub const ERROR_ACCOUNTS_NOT_SORTED: u32 = 19;
+pub const ERROR_DUPLICATE_ACCOUNT: u32 = 20;
+
+/// Fails unless `accounts` are sorted by pubkey without duplicates, so they can
+/// be binary searched.
+#[track_caller]
+#[inline(always)]
+pub fn check_sorted_by_key(accounts: &[AccountInfo]) -> ProgramResult {
+    check_strictly_increasing(accounts.iter().map(|ai| Ok(ai.key())))
+}
+
+/// Fails unless `accounts` are sorted by the key `f` extracts, e.g. from account
+/// data, without duplicate keys.
+///
+/// ```ignore
+/// check_sorted_by(remaining, |ai| Ok(Position::try_from_bytes(&ai.try_borrow_data()?)?.health))?;
+/// ```
+#[track_caller]
+#[inline(always)]
+pub fn check_sorted_by<K, F>(accounts: &[AccountInfo], f: F) -> ProgramResult
+where
+    K: Ord,
+    F: FnMut(&AccountInfo) -> Result<K, ProgramError>,
+{
+    check_strictly_increasing(accounts.iter().map(f))
+}
+
+/// Fails if any two of `accounts` share a pubkey, for positions that must be
+/// distinct such as `from != to` or `authority != recipient`.
+///
+/// ```ignore
+/// assert_unique(&[from, to, fee_recipient])?;
+/// ```
+#[track_caller]
+pub fn assert_unique(accounts: &[&AccountInfo]) -> ProgramResult {
+    for (i, a) in accounts.iter().enumerate() {
+        for (j, b) in accounts.iter().enumerate().skip(i + 1) {
+            if a.key().eq(b.key()) {
+                log!("Accounts {} and {} are the same", i, j);
+                return Err(trace(
+                    "Duplicate account",
+                    ProgramError::Custom(ERROR_DUPLICATE_ACCOUNT),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Logs the offending index and fails on the first key not greater than its predecessor.
+#[track_caller]
+fn check_strictly_increasing<K: Ord>(
+    mut keys: impl Iterator<Item = Result<K, ProgramError>>,
+) -> ProgramResult {
+    let Some(mut prev) = keys.next().transpose()? else {
+        return Ok(());
+    };
+    for (index, key) in keys.enumerate() {
+        let key = key?;
+        match prev.cmp(&key) {
+            Ordering::Less => {}
+            Ordering::Equal => {
+                log!("Account {} duplicates the previous one", index + 1);
+                return Err(trace(
+                    "Duplicate account",
+                    ProgramError::Custom(ERROR_DUPLICATE_ACCOUNT),
+                ));
+            }
+            Ordering::Greater => {
+                log!("Account {} is out of order", index + 1);
+                return Err(trace(
+                    "Accounts are not sorted",
+                    ProgramError::Custom(ERROR_ACCOUNTS_NOT_SORTED),
+                ));
+            }
+        }
+        prev = key;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(keys: &[u64]) -> ProgramResult {
+        check_strictly_increasing(keys.iter().map(|k| Ok(*k)))
+    }
+
+    #[test]
+    fn test_check_strictly_increasing() {
+        assert!(check(&[]).is_ok());
+        assert!(check(&[1]).is_ok());
+        assert!(check(&[1, 2, 5]).is_ok());
+        assert_eq!(
+            check(&[1, 2, 2]),
+            Err(ProgramError::Custom(ERROR_DUPLICATE_ACCOUNT))
+        );
+        assert_eq!(
+            check(&[1, 3, 2]),
+            Err(ProgramError::Custom(ERROR_ACCOUNTS_NOT_SORTED))
+        );
+    }
+}
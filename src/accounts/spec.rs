@@ -0,0 +1,48 @@
+/// Expected account of an instruction, in the order the program reads them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountSpec {
+    pub name: &'static str,
+    pub writable: bool,
+    pub signer: bool,
+}
+
+impl AccountSpec {
+    pub const fn readonly(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: false,
+            signer: false,
+        }
+    }
+
+    pub const fn writable(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: true,
+            signer: false,
+        }
+    }
+
+    pub const fn readonly_signer(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: false,
+            signer: true,
+        }
+    }
+
+    pub const fn writable_signer(name: &'static str) -> Self {
+        Self {
+            name,
+            writable: true,
+            signer: true,
+        }
+    }
+}
+
+/// Account list of an instruction, declared with the `accounts` block of
+/// [`instruction!`](crate::instruction) so clients and code generators share
+/// the program's account order.
+pub trait InstructionAccounts {
+    const ACCOUNTS: &'static [AccountSpec];
+}
@@ -0,0 +1,142 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use pinocchio_log::log;
+
+use crate::{fast_memset, trace, CloseProgramAccount, Validation, TOMBSTONE_DISCRIMINATOR};
+
+/// Retires `account` without closing it: data is zeroed and the discriminator
+/// set to `tombstone`, so it can't be read as a live account and a later
+/// [`SweepClosedAccounts`] can reclaim its rent.
+#[inline(always)]
+pub fn tombstone_account(account: &AccountInfo, tombstone: u8) -> Result<(), ProgramError> {
+    let mut data = account.try_borrow_mut_data()?;
+    fast_memset(&mut data, 0);
+    if let Some(first) = data.first_mut() {
+        *first = tombstone;
+    }
+    Ok(())
+}
+
+/// Janitorial instruction handler closing tombstoned accounts and returning
+/// their lamports to a treasury.
+///
+/// ```ignore
+/// // Accounts: [treasury, ...tombstoned accounts]
+/// let (treasury, rest) = accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+/// Validation::default().has_address(&TREASURY).is_writable(true).run(treasury)?;
+/// let reclaimed = SweepClosedAccounts::new(&crate::ID, treasury).run(rest)?;
+/// ```
+pub struct SweepClosedAccounts<'a> {
+    program_id: &'a Pubkey,
+    treasury: &'a AccountInfo,
+    tombstone: u8,
+}
+
+impl<'a> SweepClosedAccounts<'a> {
+    /// Sweeps accounts of `program_id` marked with [`TOMBSTONE_DISCRIMINATOR`] into `treasury`.
+    pub const fn new(program_id: &'a Pubkey, treasury: &'a AccountInfo) -> Self {
+        Self {
+            program_id,
+            treasury,
+            tombstone: TOMBSTONE_DISCRIMINATOR,
+        }
+    }
+
+    /// Sweeps accounts marked with `tombstone` instead.
+    pub const fn tombstone(mut self, tombstone: u8) -> Self {
+        self.tombstone = tombstone;
+        self
+    }
+
+    /// Closes every account of `accounts` into the treasury, returning the
+    /// lamports reclaimed. Fails on the first account that is not a writable
+    /// tombstoned account of the program with otherwise zeroed data.
+    #[track_caller]
+    pub fn run(&self, accounts: &[AccountInfo]) -> Result<u64, ProgramError> {
+        let validation = Validation::default()
+            .is_writable(true)
+            .is_type(self.program_id, self.tombstone)
+            .is_distinct_from(self.treasury.key());
+
+        let mut reclaimed = 0u64;
+        for (index, account) in accounts.iter().enumerate() {
+            let mut rule = "";
+            if let Err(e) = validation.run_reporting(account, &mut rule) {
+                log!("account {} failed {}", index, rule);
+                return Err(e);
+            }
+            if account.try_borrow_data()?[1..].iter().any(|b| *b != 0) {
+                log!("account {} failed zeroed data", index);
+                return Err(trace(
+                    "Tombstoned account has data",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+
+            reclaimed = reclaimed.saturating_add(account.lamports());
+            CloseProgramAccount {
+                account,
+                destination: self.treasury,
+            }
+            .invoke()?;
+        }
+        Ok(reclaimed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{as_account_infos, MockAccount};
+
+    #[test]
+    fn test_sweep_rejects_live_accounts() {
+        let program_id = [9; 32];
+        let mut treasury = MockAccount::new([1; 32], [0; 32], 0, &[]).writable(true);
+        let mut retired = MockAccount::new([2; 32], program_id, 100, &[3, 4, 5]).writable(true);
+        let mut live = MockAccount::new([3; 32], program_id, 100, &[3, 4, 5]).writable(true);
+
+        tombstone_account(&retired.info(), TOMBSTONE_DISCRIMINATOR).unwrap();
+        assert_eq!(retired.data(), &[TOMBSTONE_DISCRIMINATOR, 0, 0]);
+
+        let treasury = treasury.info();
+        let sweep = SweepClosedAccounts::new(&program_id, &treasury);
+        assert_eq!(
+            sweep.run(as_account_infos(&[live.info()])),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // Tombstoned with a leftover byte
+        let mut dirty =
+            MockAccount::new([4; 32], program_id, 100, &[TOMBSTONE_DISCRIMINATOR, 0, 1])
+                .writable(true);
+        assert_eq!(
+            sweep.run(as_account_infos(&[dirty.info()])),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(sweep.run(&[]), Ok(0));
+    }
+
+    #[test]
+    fn test_sweep_closes_tombstoned_accounts() {
+        let program_id = [9; 32];
+        let mut treasury = MockAccount::new([1; 32], [0; 32], 50, &[]).writable(true);
+        let mut first = MockAccount::new([2; 32], program_id, 100, &[3, 4, 5]).writable(true);
+        let mut second = MockAccount::new([3; 32], program_id, 200, &[6, 7]).writable(true);
+        tombstone_account(&first.info(), TOMBSTONE_DISCRIMINATOR).unwrap();
+        tombstone_account(&second.info(), TOMBSTONE_DISCRIMINATOR).unwrap();
+
+        let treasury_info = treasury.info();
+        let sweep = SweepClosedAccounts::new(&program_id, &treasury_info);
+        assert_eq!(
+            sweep.run(as_account_infos(&[first.info(), second.info()])),
+            Ok(300)
+        );
+        drop(treasury_info);
+
+        assert_eq!(treasury.lamports(), 350);
+        for retired in [&first, &second] {
+            assert_eq!(retired.lamports(), 0);
+            assert!(retired.data().is_empty());
+        }
+    }
+}
@@ -7,24 +7,62 @@ use pinocchio::{
 
 #[cfg(target_os = "solana")]
 use pinocchio::syscalls::sol_sha256;
-
-use crate::{trace, AccountDeserialize, Discriminator, SYSVAR_PROGRAM_ID};
+use pinocchio_log::log;
+
+#[cfg(any(target_os = "solana", feature = "curve25519"))]
+use crate::is_on_curve;
+use crate::{
+    get_rent, is_initialized_data, pubkey_eq, set_panic_account, trace, AccountDeserialize,
+    Discriminator, SavedBump, TokenAccountView, ASSOCIATED_TOKEN_PROGRAM_ID,
+    ERROR_DUPLICATE_ACCOUNT, ERROR_FEE_PAYER_UNKNOWN, EVENT_AUTHORITY_SEED, SYSTEM_PROGRAM_ID,
+    SYSVAR_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID, TOKEN_PROGRAM_IDS,
+};
 
 /// Build dynamic validation rules for AccountInfo
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct Validation<'a> {
     is_signer: bool,
     is_writable: bool,
     is_executable: bool,
     is_empty: bool,
-    is_type: Option<(u8, &'a Pubkey)>,
+    is_type: Option<(TypeDiscriminator, &'a Pubkey)>,
+    is_account_of: Option<(fn(&[u8]) -> bool, usize, &'a Pubkey)>,
+    has_discriminator_in: Option<(&'a [u8], Option<u8>, &'a Pubkey)>,
     is_program: Option<&'a Pubkey>,
+    is_program_in: Option<&'a [Pubkey]>,
     is_sysvar: Option<&'a Pubkey>,
     has_address: Option<&'a Pubkey>,
     has_owner: Option<&'a Pubkey>,
     has_seeds: Option<(&'a [&'a [u8]], &'a Pubkey)>,
     has_seeds_with_bump: Option<(&'a [&'a [u8]], &'a Pubkey, u8)>,
+    is_pda: Option<(&'a [&'a [u8]], &'a Pubkey, u8)>,
     has_seeds_with_saved_bump: Option<(&'a [&'a [u8]], &'a Pubkey)>,
+    has_seeds_with_saved_bump_of: Option<(&'a [&'a [u8]], &'a Pubkey, SavedBumpReader)>,
+    is_token_account: bool,
+    has_no_delegate: bool,
+    has_no_close_authority: bool,
+    #[cfg(any(target_os = "solana", feature = "curve25519"))]
+    is_pda_like: bool,
+    is_distinct_from: Option<&'a Pubkey>,
+    has_rent_epoch_exempt: bool,
+    is_initialized: Option<&'a Pubkey>,
+    is_uninitialized: bool,
+    is_fee_payer: bool,
+}
+
+/// Reads the saved bump from raw account data.
+type SavedBumpReader = fn(&[u8]) -> Result<u8, ProgramError>;
+
+/// Discriminator checked by `is_type`: a single byte (and namespace), or the
+/// discriminator of an account type at its own width.
+#[derive(Clone, Copy)]
+enum TypeDiscriminator {
+    Byte(u8, Option<u8>),
+    Of(fn(&[u8]) -> bool),
+}
+
+fn read_saved_bump<T: AccountDeserialize + SavedBump>(data: &[u8]) -> Result<u8, ProgramError> {
+    Ok(T::try_from_bytes(data)?.bump())
 }
 
 impl<'a> Validation<'a> {
@@ -35,16 +73,44 @@ impl<'a> Validation<'a> {
             is_executable: false,
             is_empty: false,
             is_type: None,
+            is_account_of: None,
+            has_discriminator_in: None,
             is_program: None,
+            is_program_in: None,
             is_sysvar: None,
             has_address: None,
             has_owner: None,
             has_seeds: None,
             has_seeds_with_bump: None,
+            is_pda: None,
             has_seeds_with_saved_bump: None,
+            has_seeds_with_saved_bump_of: None,
+            is_token_account: false,
+            has_no_delegate: false,
+            has_no_close_authority: false,
+            #[cfg(any(target_os = "solana", feature = "curve25519"))]
+            is_pda_like: false,
+            is_distinct_from: None,
+            has_rent_epoch_exempt: false,
+            is_initialized: None,
+            is_uninitialized: false,
+            is_fee_payer: false,
         }
     }
 
+    /// Preset for SPL Token and Token-2022 accounts, refusing rent-paying ones.
+    pub fn token_account() -> Self {
+        Self::default().is_token_account().has_rent_epoch_exempt()
+    }
+
+    /// Preset for the program's state accounts: initialized by `program_id`
+    /// and not rent-paying.
+    pub fn state_account(program_id: &'a Pubkey) -> Self {
+        Self::default()
+            .is_initialized(program_id)
+            .has_rent_epoch_exempt()
+    }
+
     pub const fn is_signer(mut self, must: bool) -> Self {
         self.is_signer = must;
         self
@@ -62,13 +128,87 @@ impl<'a> Validation<'a> {
         self
     }
     pub const fn is_type(mut self, program_id: &'a Pubkey, discriminator: u8) -> Self {
-        self.is_type = Some((discriminator, program_id));
+        self.is_type = Some((TypeDiscriminator::Byte(discriminator, None), program_id));
+        self
+    }
+    /// Same as `is_type`, but also checks the namespace byte of namespaced accounts.
+    pub const fn is_type_with_namespace(
+        mut self,
+        program_id: &'a Pubkey,
+        discriminator: u8,
+        namespace: u8,
+    ) -> Self {
+        self.is_type = Some((
+            TypeDiscriminator::Byte(discriminator, Some(namespace)),
+            program_id,
+        ));
+        self
+    }
+    /// Same as `is_type`, but matches `T`'s discriminator at its own width, e.g.
+    /// the 8 byte `DISCRIMINATOR_BYTES` of foreign account types.
+    pub const fn is_type_of<T: Discriminator>(mut self, program_id: &'a Pubkey) -> Self {
+        self.is_type = Some((
+            TypeDiscriminator::Of(T::matches_discriminator as fn(&[u8]) -> bool),
+            program_id,
+        ));
+        self
+    }
+    /// Account must be owned by `program_id`, be exactly `size_of::<T>()` long and
+    /// start with `T`'s discriminator (and namespace, if any).
+    pub const fn is_account_of<T: Discriminator>(mut self, program_id: &'a Pubkey) -> Self {
+        self.is_account_of = Some((
+            T::matches_discriminator as fn(&[u8]) -> bool,
+            core::mem::size_of::<T>(),
+            program_id,
+        ));
+        self
+    }
+    /// Account must be owned by `program_id` and have any of `discriminators`.
+    /// Use [`Self::run_matched`] to learn which one matched.
+    pub const fn has_discriminator_in(
+        mut self,
+        program_id: &'a Pubkey,
+        discriminators: &'a [u8],
+    ) -> Self {
+        self.has_discriminator_in = Some((discriminators, None, program_id));
+        self
+    }
+    /// Same as `has_discriminator_in`, but also checks the namespace byte of
+    /// namespaced accounts.
+    pub const fn has_discriminator_in_with_namespace(
+        mut self,
+        program_id: &'a Pubkey,
+        discriminators: &'a [u8],
+        namespace: u8,
+    ) -> Self {
+        self.has_discriminator_in = Some((discriminators, Some(namespace), program_id));
         self
     }
     pub const fn is_program(mut self, program_id: &'a Pubkey) -> Self {
         self.is_program = Some(program_id);
         self
     }
+    /// Account must be any of the `program_ids` executables.
+    pub const fn is_program_in(mut self, program_ids: &'a [Pubkey]) -> Self {
+        self.is_program_in = Some(program_ids);
+        self
+    }
+    pub const fn is_system_program(self) -> Self {
+        self.is_program(&SYSTEM_PROGRAM_ID)
+    }
+    pub const fn is_token_program(self) -> Self {
+        self.is_program(&TOKEN_PROGRAM_ID)
+    }
+    pub const fn is_token_2022_program(self) -> Self {
+        self.is_program(&TOKEN_2022_PROGRAM_ID)
+    }
+    /// Account must be either SPL Token or Token-2022 program.
+    pub const fn is_token_program_any(self) -> Self {
+        self.is_program_in(TOKEN_PROGRAM_IDS)
+    }
+    pub const fn is_associated_token_program(self) -> Self {
+        self.is_program(&ASSOCIATED_TOKEN_PROGRAM_ID)
+    }
     pub const fn is_sysvar(mut self, sysvar_id: &'a Pubkey) -> Self {
         self.is_sysvar = Some(sysvar_id);
         self
@@ -77,6 +217,11 @@ impl<'a> Validation<'a> {
         self.has_address = Some(address);
         self
     }
+    /// Account must not be `other`, e.g. a recipient that must differ from the source.
+    pub const fn is_distinct_from(mut self, other: &'a Pubkey) -> Self {
+        self.is_distinct_from = Some(other);
+        self
+    }
     pub const fn has_owner(mut self, program_id: &'a Pubkey) -> Self {
         self.has_owner = Some(program_id);
         self
@@ -85,6 +230,18 @@ impl<'a> Validation<'a> {
         self.has_seeds = Some((seeds, program_id));
         self
     }
+    /// Account must be the signing event authority PDA of `program_id`, with
+    /// its canonical `bump`, see [`event_authority_pda`](crate::event_authority_pda).
+    pub const fn is_event_authority(self, program_id: &'a Pubkey, bump: u8) -> Self {
+        const SEEDS: &[&[u8]] = &[EVENT_AUTHORITY_SEED];
+        self.is_signer(true).is_pda(SEEDS, program_id, bump)
+    }
+    /// Account key must be the PDA of `seeds` and `bump`. Unlike
+    /// `has_seeds_with_bump`, the account may hold no data, e.g. signer-only PDAs.
+    pub const fn is_pda(mut self, seeds: &'a [&'a [u8]], program_id: &'a Pubkey, bump: u8) -> Self {
+        self.is_pda = Some((seeds, program_id, bump));
+        self
+    }
     pub const fn has_seeds_with_bump(
         mut self,
         seeds: &'a [&'a [u8]],
@@ -102,49 +259,264 @@ impl<'a> Validation<'a> {
         self.has_seeds_with_saved_bump = Some((seeds, program_id));
         self
     }
+    /// Same as `has_seeds_with_saved_bump`, but reads the bump through
+    /// `T`'s [`SavedBump`] impl instead of assuming it is the second byte.
+    pub const fn has_seeds_with_saved_bump_of<T: AccountDeserialize + SavedBump>(
+        mut self,
+        seeds: &'a [&'a [u8]],
+        program_id: &'a Pubkey,
+    ) -> Self {
+        self.has_seeds_with_saved_bump_of = Some((seeds, program_id, read_saved_bump::<T>));
+        self
+    }
+    /// Account key must be off the ed25519 curve, rejecting wallet keys passed
+    /// where a PDA is expected. Cheaper than re-deriving when seeds are unknown.
+    ///
+    /// Off-chain, e.g. in tests, requires the `curve25519` feature.
+    #[cfg(any(target_os = "solana", feature = "curve25519"))]
+    pub const fn is_pda_like(mut self) -> Self {
+        self.is_pda_like = true;
+        self
+    }
+    /// Account must be a writable signer that can pay the transaction fee. See
+    /// [`fee_payer`](crate::fee_payer) for why this can't prove it did.
+    pub const fn is_fee_payer(mut self) -> Self {
+        self.is_fee_payer = true;
+        self
+    }
+    /// Account must hold at least the rent-exempt minimum for its current data
+    /// length, refusing rent-paying accounts that may be reaped.
+    pub const fn has_rent_epoch_exempt(mut self) -> Self {
+        self.has_rent_epoch_exempt = true;
+        self
+    }
+    /// Account must be owned by `program_id` and have a discriminator written,
+    /// see [`is_initialized_data`].
+    pub const fn is_initialized(mut self, program_id: &'a Pubkey) -> Self {
+        self.is_initialized = Some(program_id);
+        self
+    }
+    /// Account data must be empty or have no discriminator written, e.g. when
+    /// the account was allocated but not initialized yet.
+    pub const fn is_uninitialized(mut self) -> Self {
+        self.is_uninitialized = true;
+        self
+    }
+    /// Account must be owned by a token program and hold a token account.
+    pub const fn is_token_account(mut self) -> Self {
+        self.is_token_account = true;
+        self
+    }
+    /// Account must be a token account without an active delegate.
+    pub const fn has_no_delegate(mut self) -> Self {
+        self.has_no_delegate = true;
+        self
+    }
+    /// Account must be a token account without a close authority.
+    pub const fn has_no_close_authority(mut self) -> Self {
+        self.has_no_close_authority = true;
+        self
+    }
 
     #[must_use]
     #[inline(never)]
     pub fn run(self, ai: &AccountInfo) -> ProgramResult {
+        self.run_matched(ai).map(|_| ())
+    }
+
+    /// Same as [`Self::run`], but returns the discriminator matched by
+    /// `has_discriminator_in`, if that rule is set.
+    #[inline(never)]
+    pub fn run_matched(self, ai: &AccountInfo) -> Result<Option<u8>, ProgramError> {
+        self.run_reporting(ai, &mut "")
+    }
+
+    /// Same as [`Self::run`], but logs `account '<name>' failed <rule>` on error
+    /// so the failing account can be told from explorer logs.
+    #[inline(never)]
+    pub fn run_named(self, ai: &AccountInfo, name: &str) -> ProgramResult {
+        let mut rule = "";
+        let result = self.run_reporting(ai, &mut rule);
+        if result.is_err() {
+            log!("account '{}' failed {}", name, rule);
+        }
+        result.map(|_| ())
+    }
+
+    /// Runs all rules, leaving the name of the failing one in `rule` on error.
+    #[inline(always)]
+    pub(crate) fn run_reporting(
+        self,
+        ai: &AccountInfo,
+        rule: &mut &'static str,
+    ) -> Result<Option<u8>, ProgramError> {
+        // Borrow account data once, and only if a rule needs it.
+        let result = if self.needs_data() {
+            let data = ai.try_borrow_data()?;
+            self.check_rules(ai, &data, rule)
+        } else {
+            self.check_rules(ai, &[], rule)
+        };
+        if result.is_ok() {
+            set_panic_account(ai.key());
+        }
+        result
+    }
+
+    /// Same as [`Self::run`], but returns the data borrow taken for the checks,
+    /// so the account can be deserialized without borrowing it again.
+    ///
+    /// The borrow must be dropped before the account data is borrowed mutably.
+    #[inline(never)]
+    pub fn run_borrowed<'b>(self, ai: &'b AccountInfo) -> Result<Ref<'b, [u8]>, ProgramError> {
+        let data = ai.try_borrow_data()?;
+        self.check(ai, &data)?;
+        Ok(data)
+    }
+
+    /// Returns `true` if any rule reads account data.
+    #[inline(always)]
+    const fn needs_data(&self) -> bool {
+        self.is_type.is_some()
+            || self.is_account_of.is_some()
+            || self.has_discriminator_in.is_some()
+            || self.has_seeds_with_saved_bump.is_some()
+            || self.has_seeds_with_saved_bump_of.is_some()
+            || self.is_token_account
+            || self.has_no_delegate
+            || self.has_no_close_authority
+            || self.is_initialized.is_some()
+            || self.is_uninitialized
+    }
+
+    /// Runs all rules. `data` must be the account data if [`Self::needs_data`].
+    #[inline(always)]
+    fn check(self, ai: &AccountInfo, data: &[u8]) -> Result<Option<u8>, ProgramError> {
+        self.check_rules(ai, data, &mut "")
+    }
+
+    /// Same as [`Self::check`], but leaves the name of the last rule checked in
+    /// `rule`, i.e. the failing one on error.
+    #[inline(always)]
+    fn check_rules(
+        self,
+        ai: &AccountInfo,
+        data: &[u8],
+        rule: &mut &'static str,
+    ) -> Result<Option<u8>, ProgramError> {
+        let mut matched = None;
+
         // --------------- is_signer -------------------------------
+        *rule = "is_signer";
         if self.is_signer && !ai.is_signer() {
             // return Err(trace("Account is not a signer", ProgramError::MissingRequiredSignature));
             return Err(ProgramError::MissingRequiredSignature);
         }
 
         // --------------- is_writable -------------------------------
+        *rule = "is_writable";
         if self.is_writable && !ai.is_writable() {
             return Err(ProgramError::InvalidAccountData);
         }
 
         // --------------- is_executable -------------------------------
+        *rule = "is_executable";
         if self.is_executable && !ai.executable() {
             return Err(ProgramError::InvalidAccountData);
         }
 
         // --------------- is_empty -------------------------------
+        *rule = "is_empty";
         if self.is_empty && !ai.data_is_empty() {
             return Err(ProgramError::AccountAlreadyInitialized);
         }
 
         // --------------- is_type -------------------------------
+        *rule = "is_type";
         if let Some((discriminator, program_id)) = self.is_type {
             if !ai.is_owned_by(program_id) {
                 return Err(ProgramError::InvalidAccountOwner);
             }
 
             // We only check discriminator, because we own account.
-            if ai.data_len() == 0 {
+            let matches = match discriminator {
+                TypeDiscriminator::Byte(discriminator, None) => {
+                    data.first() == Some(&discriminator)
+                }
+                TypeDiscriminator::Byte(discriminator, Some(namespace)) => {
+                    data.get(..2) == Some(&[discriminator, namespace][..])
+                }
+                TypeDiscriminator::Of(matches_discriminator) => matches_discriminator(data),
+            };
+            if !matches {
                 return Err(ProgramError::InvalidAccountData);
             }
-            if ai.try_borrow_data()?[0].ne(&discriminator) {
+        }
+
+        // --------------- is_account_of -------------------------------
+        *rule = "is_account_of";
+        if let Some((matches_discriminator, len, program_id)) = self.is_account_of {
+            if !ai.is_owned_by(program_id) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if data.len() != len || !matches_discriminator(data) {
                 return Err(ProgramError::InvalidAccountData);
             }
         }
 
+        // --------------- has_discriminator_in -------------------------------
+        *rule = "has_discriminator_in";
+        if let Some((discriminators, namespace, program_id)) = self.has_discriminator_in {
+            if !ai.is_owned_by(program_id) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if data.is_empty() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let discriminator = data[0];
+            if !discriminators.contains(&discriminator) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if let Some(namespace) = namespace {
+                if data.get(1) != Some(&namespace) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+            matched = Some(discriminator);
+        }
+
+        // --------------- is_initialized -------------------------------
+        *rule = "is_initialized";
+        if let Some(program_id) = self.is_initialized {
+            if !ai.is_owned_by(program_id) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if !is_initialized_data(data) {
+                return Err(ProgramError::UninitializedAccount);
+            }
+        }
+
+        // --------------- is_uninitialized -------------------------------
+        *rule = "is_uninitialized";
+        if self.is_uninitialized && is_initialized_data(data) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
         // // --------------- is_program -------------------------------
+        *rule = "is_program";
         if let Some(program_id) = self.is_program {
-            if ai.key().ne(program_id) {
+            if !pubkey_eq(ai.key(), program_id) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            if !ai.executable() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        // --------------- is_program_in -------------------------------
+        *rule = "is_program_in";
+        if let Some(program_ids) = self.is_program_in {
+            if !program_ids.iter().any(|id| pubkey_eq(id, ai.key())) {
                 return Err(ProgramError::InvalidAccountOwner);
             }
             if !ai.executable() {
@@ -153,23 +525,26 @@ impl<'a> Validation<'a> {
         }
 
         // --------------- is_sysvar -------------------------------
+        *rule = "is_sysvar";
         if let Some(sysvar_id) = self.is_sysvar {
             if !ai.is_owned_by(&SYSVAR_PROGRAM_ID) {
                 return Err(ProgramError::InvalidAccountOwner);
             }
-            if ai.key().ne(sysvar_id) {
+            if !pubkey_eq(ai.key(), sysvar_id) {
                 return Err(ProgramError::InvalidAccountData);
             }
         }
 
         // --------------- has_address -------------------------------
+        *rule = "has_address";
         if let Some(address) = self.has_address {
-            if ai.key().ne(address) {
+            if !pubkey_eq(ai.key(), address) {
                 return Err(ProgramError::InvalidAccountData);
             }
         }
 
         // // --------------- has_owner -------------------------------
+        *rule = "has_owner";
         if let Some(owner) = self.has_owner {
             if !ai.is_owned_by(owner) {
                 return Err(ProgramError::InvalidAccountOwner);
@@ -177,16 +552,18 @@ impl<'a> Validation<'a> {
         }
 
         // --------------- has_seeds -------------------------------
+        *rule = "has_seeds";
         // NOTE: Calling `find_program_address` is expensive.
         // Consider using `has_seeds_with_bump` instead for program owned accounts.
         if let Some((seeds, pid)) = self.has_seeds {
             let (pda, _bump) = find_program_address(seeds, pid);
-            if ai.key().ne(&pda) {
+            if !pubkey_eq(ai.key(), &pda) {
                 return Err(ProgramError::InvalidSeeds);
             }
         }
 
         // --------------- has_seeds_with_bump -------------------------------
+        *rule = "has_seeds_with_bump";
         if let Some((seeds, pid, bump)) = self.has_seeds_with_bump {
             // Account must be initialized
             if ai.data_is_empty() || ai.data_len() < 2 {
@@ -197,37 +574,127 @@ impl<'a> Validation<'a> {
             let derived_pubkey = derive_pda(seeds, pid, bump_seed)?;
 
             // Check if the account key matches the derived PDA
-            if ai.key().ne(&derived_pubkey) {
+            if !pubkey_eq(ai.key(), &derived_pubkey) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+        }
+
+        // --------------- is_pda -------------------------------
+        *rule = "is_pda";
+        if let Some((seeds, pid, bump)) = self.is_pda {
+            if !pubkey_eq(ai.key(), &derive_pda(seeds, pid, [bump])?) {
                 return Err(ProgramError::InvalidSeeds);
             }
         }
 
         // --------------- has_seeds_with_saved_bump -------------------------------
+        *rule = "has_seeds_with_saved_bump";
         if let Some((seeds, pid)) = self.has_seeds_with_saved_bump {
             // Account must be owned by the program
             if !ai.is_owned_by(pid) {
                 return Err(ProgramError::InvalidAccountOwner);
             }
             // Account must be initialized
-            if ai.data_is_empty() || ai.data_len() < 2 {
+            if data.len() < 2 {
                 return Err(ProgramError::InvalidAccountData);
             }
 
             // SAFETY: bump should always be the second byte of account data
-            let bump_seed = [ai.try_borrow_data()?[1]];
+            let bump_seed = [data[1]];
             let derived_pubkey = derive_pda(seeds, pid, bump_seed)?;
 
             // Check if the account key matches the derived PDA
-            if ai.key().ne(&derived_pubkey) {
+            if !pubkey_eq(ai.key(), &derived_pubkey) {
                 return Err(ProgramError::InvalidSeeds);
             }
         }
 
-        Ok(())
+        // --------------- has_seeds_with_saved_bump_of -------------------------------
+        *rule = "has_seeds_with_saved_bump_of";
+        if let Some((seeds, pid, read_bump)) = self.has_seeds_with_saved_bump_of {
+            // Account must be owned by the program
+            if !ai.is_owned_by(pid) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+
+            let bump_seed = [read_bump(data)?];
+            let derived_pubkey = derive_pda(seeds, pid, bump_seed)?;
+
+            // Check if the account key matches the derived PDA
+            if !pubkey_eq(ai.key(), &derived_pubkey) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+        }
+
+        // --------------- is_distinct_from -------------------------------
+        *rule = "is_distinct_from";
+        if let Some(other) = self.is_distinct_from {
+            if pubkey_eq(ai.key(), other) {
+                return Err(trace(
+                    "Duplicate account",
+                    ProgramError::Custom(ERROR_DUPLICATE_ACCOUNT),
+                ));
+            }
+        }
+
+        // --------------- is_fee_payer -------------------------------
+        *rule = "is_fee_payer";
+        if self.is_fee_payer && (!ai.is_signer() || !ai.is_writable()) {
+            return Err(trace(
+                "Fee payer must be a writable signer",
+                ProgramError::Custom(ERROR_FEE_PAYER_UNKNOWN),
+            ));
+        }
+
+        // --------------- has_rent_epoch_exempt -------------------------------
+        *rule = "has_rent_epoch_exempt";
+        if self.has_rent_epoch_exempt && ai.lamports() < get_rent()?.minimum_balance(ai.data_len())
+        {
+            return Err(trace(
+                "Account is not rent exempt",
+                ProgramError::AccountNotRentExempt,
+            ));
+        }
+
+        // --------------- is_pda_like -------------------------------
+        #[cfg(any(target_os = "solana", feature = "curve25519"))]
+        {
+            *rule = "is_pda_like";
+            if self.is_pda_like && is_on_curve(ai.key()) {
+                return Err(trace("Account is not a PDA", ProgramError::InvalidSeeds));
+            }
+        }
+
+        // --------------- is_token_account / has_no_delegate / has_no_close_authority
+        if self.is_token_account || self.has_no_delegate || self.has_no_close_authority {
+            *rule = "is_token_account";
+            if !TOKEN_PROGRAM_IDS.iter().any(|id| ai.is_owned_by(id)) {
+                return Err(ProgramError::InvalidAccountOwner);
+            }
+            let token_account = TokenAccountView::try_from_bytes(data)?;
+            *rule = "has_no_delegate";
+            if self.has_no_delegate && token_account.delegate().is_some() {
+                return Err(trace(
+                    "Token account has a delegate",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+            *rule = "has_no_close_authority";
+            if self.has_no_close_authority && token_account.close_authority().is_some() {
+                return Err(trace(
+                    "Token account has a close authority",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+        }
+
+        Ok(matched)
     }
 }
 
-fn derive_pda(
+/// Address of the PDA of `seeds` and `bump_seed`, without the off-curve check
+/// of `create_program_address`: a known bump is trusted to be valid.
+pub(crate) fn derive_pda(
     seeds: &[&[u8]],
     program_id: &Pubkey,
     bump_seed: [u8; 1],
@@ -267,11 +734,18 @@ fn derive_pda(
             result
         }
 
+        // Host builds hash in software, so PDA checks can be unit tested
         #[cfg(not(target_os = "solana"))]
         {
-            unreachable!("deriving a pda is only available on target `solana`");
-            #[allow(unreachable_code)]
-            [0u8; 32] // Never executed, just for type satisfaction
+            let mut buf = [0u8; MAX_SEEDS * pinocchio::pubkey::MAX_SEED_LEN];
+            let mut len = 0;
+            for seed in data_slice {
+                buf.get_mut(len..len + seed.len())
+                    .ok_or(ProgramError::InvalidSeeds)?
+                    .copy_from_slice(seed);
+                len += seed.len();
+            }
+            crate::sha256(&buf[..len])
         }
     };
 
@@ -304,6 +778,7 @@ impl AsAccount for AccountInfo {
             ));
         }
 
+        set_panic_account(self.key());
         Ok(Ref::map(self.try_borrow_data()?, |data| {
             T::try_from_bytes(data).unwrap()
         }))
@@ -320,6 +795,7 @@ impl AsAccount for AccountInfo {
                 ProgramError::InvalidAccountOwner,
             ));
         }
+        set_panic_account(self.key());
         Ok(RefMut::map(self.try_borrow_mut_data()?, |data| {
             T::try_from_bytes_mut(data).unwrap()
         }))
@@ -0,0 +1,159 @@
+//! Bump allocator with high-water mark tracking, to tune heap usage and catch
+//! accidental allocations in hot paths.
+//!
+//! Install it with [`tracking_allocator!`](crate::tracking_allocator) in place of
+//! pinocchio's default allocator (i.e. with `program_entrypoint!` and
+//! `no_allocator!` instead of `entrypoint!`).
+
+use core::{
+    alloc::{GlobalAlloc, Layout},
+    mem::size_of,
+    ptr::null_mut,
+};
+
+/// Start address of the program heap.
+pub const HEAP_START_ADDRESS: usize = 0x300000000;
+/// Default program heap length.
+pub const HEAP_LENGTH: usize = 32 * 1024;
+/// Bytes at the heap start never handed out by [`TrackingBumpAllocator`]:
+/// its own state, followed by the panic context of [`log_panic_context`](crate::log_panic_context).
+pub const HEAP_RESERVED_LEN: usize = 64;
+
+/// Allocator state, kept at the start of the (zero-initialized) heap.
+#[repr(C)]
+struct State {
+    /// Lowest allocated address, allocations grow downwards. `0` until first use.
+    pos: usize,
+    /// Peak number of bytes in use.
+    peak: usize,
+}
+
+// The panic context follows the state at offset 16
+const _: () = assert!(size_of::<State>() <= 16);
+
+/// Bump allocator over `[start, start + len)` recording its peak usage.
+///
+/// Only the most recent allocation is reclaimed on `dealloc`.
+pub struct TrackingBumpAllocator {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl TrackingBumpAllocator {
+    #[inline(always)]
+    fn state(&self) -> *mut State {
+        self.start as *mut State
+    }
+
+    #[inline(always)]
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    /// Bytes currently allocated, alignment padding included.
+    pub fn used(&self) -> usize {
+        // SAFETY: the heap start is reserved for the state
+        let pos = unsafe { (*self.state()).pos };
+        if pos == 0 {
+            0
+        } else {
+            self.end() - pos
+        }
+    }
+
+    /// Highest [`Self::used`] seen so far.
+    pub fn peak(&self) -> usize {
+        // SAFETY: the heap start is reserved for the state
+        unsafe { (*self.state()).peak }
+    }
+
+    /// Logs current and peak heap usage.
+    #[cfg(feature = "heap-stats")]
+    pub fn log(&self) {
+        pinocchio_log::log!(
+            "heap used: {}, peak: {}, of: {}",
+            self.used(),
+            self.peak(),
+            self.len - HEAP_RESERVED_LEN
+        );
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingBumpAllocator {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let state = self.state();
+        let pos = match (*state).pos {
+            0 => self.end(),
+            pos => pos,
+        };
+        let Some(pos) = pos.checked_sub(layout.size()) else {
+            return null_mut();
+        };
+        let pos = pos & !(layout.align() - 1);
+        if pos < self.start + HEAP_RESERVED_LEN {
+            return null_mut();
+        }
+
+        (*state).pos = pos;
+        let used = self.end() - pos;
+        if used > (*state).peak {
+            (*state).peak = used;
+        }
+        pos as *mut u8
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // Reclaim the most recent allocation, alignment padding above it stays used
+        let state = self.state();
+        if ptr as usize == (*state).pos {
+            (*state).pos = ptr as usize + layout.size();
+        }
+    }
+}
+
+/// Installs a [`TrackingBumpAllocator`] over the program heap as the global allocator.
+#[macro_export]
+macro_rules! tracking_allocator {
+    () => {
+        #[cfg(target_os = "solana")]
+        #[global_allocator]
+        static ALLOCATOR: $crate::TrackingBumpAllocator = $crate::TrackingBumpAllocator {
+            start: $crate::HEAP_START_ADDRESS,
+            len: $crate::HEAP_LENGTH,
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracking_bump_allocator() {
+        let mut heap = [0u64; 16];
+        let allocator = TrackingBumpAllocator {
+            start: heap.as_mut_ptr() as usize,
+            len: core::mem::size_of_val(&heap),
+        };
+        assert_eq!(allocator.used(), 0);
+
+        unsafe {
+            let a = allocator.alloc(Layout::new::<u64>());
+            let b = allocator.alloc(Layout::new::<[u8; 24]>());
+            assert!(!a.is_null() && !b.is_null());
+            assert_eq!(allocator.used(), 32);
+
+            // Only the latest allocation is reclaimed
+            allocator.dealloc(b, Layout::new::<[u8; 24]>());
+            assert_eq!(allocator.used(), 8);
+            assert_eq!(allocator.peak(), 32);
+
+            // The reserved prefix takes 64 of the 128 bytes
+            assert!(allocator.alloc(Layout::new::<[u8; 57]>()).is_null());
+            assert!(!allocator.alloc(Layout::new::<[u8; 56]>()).is_null());
+            assert_eq!(allocator.peak(), 64);
+        }
+    }
+}
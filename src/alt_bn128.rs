@@ -0,0 +1,317 @@
+//! alt_bn128 syscall wrappers and a minimal Groth16 verifier.
+//!
+//! Points use the big-endian, uncompressed encoding of the syscalls:
+//! 64 bytes for G1 and 128 bytes for G2.
+
+use pinocchio::{program_error::ProgramError, ProgramResult};
+
+use crate::trace;
+
+pub const ERROR_GROTH16_INVALID_PROOF: u32 = 18;
+
+pub const ALT_BN128_G1_LEN: usize = 64;
+pub const ALT_BN128_G2_LEN: usize = 128;
+pub const ALT_BN128_G1_COMPRESSED_LEN: usize = 32;
+pub const ALT_BN128_G2_COMPRESSED_LEN: usize = 64;
+/// Length of one `(G1, G2)` pairing element.
+pub const ALT_BN128_PAIRING_ELEMENT_LEN: usize = ALT_BN128_G1_LEN + ALT_BN128_G2_LEN;
+
+/// Base field modulus, big-endian.
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Scalar field modulus, big-endian.
+const SCALAR_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+const OP_ADD: u64 = 0;
+const OP_MUL: u64 = 2;
+const OP_PAIRING: u64 = 3;
+
+const OP_G1_COMPRESS: u64 = 0;
+const OP_G1_DECOMPRESS: u64 = 1;
+const OP_G2_COMPRESS: u64 = 2;
+const OP_G2_DECOMPRESS: u64 = 3;
+
+#[cfg(target_os = "solana")]
+extern "C" {
+    fn sol_alt_bn128_group_op(op: u64, input: *const u8, input_size: u64, result: *mut u8) -> u64;
+    fn sol_alt_bn128_compression(
+        op: u64,
+        input: *const u8,
+        input_size: u64,
+        result: *mut u8,
+    ) -> u64;
+}
+
+#[inline(always)]
+fn group_op(op: u64, input: &[u8], result: &mut [u8]) -> ProgramResult {
+    #[cfg(target_os = "solana")]
+    {
+        let status = unsafe {
+            sol_alt_bn128_group_op(op, input.as_ptr(), input.len() as u64, result.as_mut_ptr())
+        };
+        if status != 0 {
+            return Err(trace(
+                "alt_bn128 group operation failed",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        let _ = (op, input, result);
+        Err(trace(
+            "alt_bn128 is only available on target `solana`",
+            ProgramError::InvalidArgument,
+        ))
+    }
+}
+
+#[inline(always)]
+fn compression(op: u64, input: &[u8], result: &mut [u8]) -> ProgramResult {
+    #[cfg(target_os = "solana")]
+    {
+        let status = unsafe {
+            sol_alt_bn128_compression(op, input.as_ptr(), input.len() as u64, result.as_mut_ptr())
+        };
+        if status != 0 {
+            return Err(trace(
+                "alt_bn128 compression failed",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        let _ = (op, input, result);
+        Err(trace(
+            "alt_bn128 is only available on target `solana`",
+            ProgramError::InvalidArgument,
+        ))
+    }
+}
+
+/// G1 point addition.
+pub fn alt_bn128_add(
+    left: &[u8; ALT_BN128_G1_LEN],
+    right: &[u8; ALT_BN128_G1_LEN],
+) -> Result<[u8; ALT_BN128_G1_LEN], ProgramError> {
+    let mut input = [0u8; 2 * ALT_BN128_G1_LEN];
+    input[..ALT_BN128_G1_LEN].copy_from_slice(left);
+    input[ALT_BN128_G1_LEN..].copy_from_slice(right);
+    let mut result = [0u8; ALT_BN128_G1_LEN];
+    group_op(OP_ADD, &input, &mut result)?;
+    Ok(result)
+}
+
+/// G1 scalar multiplication.
+pub fn alt_bn128_mul(
+    point: &[u8; ALT_BN128_G1_LEN],
+    scalar: &[u8; 32],
+) -> Result<[u8; ALT_BN128_G1_LEN], ProgramError> {
+    let mut input = [0u8; ALT_BN128_G1_LEN + 32];
+    input[..ALT_BN128_G1_LEN].copy_from_slice(point);
+    input[ALT_BN128_G1_LEN..].copy_from_slice(scalar);
+    let mut result = [0u8; ALT_BN128_G1_LEN];
+    group_op(OP_MUL, &input, &mut result)?;
+    Ok(result)
+}
+
+/// Returns `true` if the product of the pairings of `input`, a concatenation of
+/// [`ALT_BN128_PAIRING_ELEMENT_LEN`] byte `(G1, G2)` elements, is one.
+pub fn alt_bn128_pairing(input: &[u8]) -> Result<bool, ProgramError> {
+    if input.len() % ALT_BN128_PAIRING_ELEMENT_LEN != 0 {
+        return Err(trace(
+            "Invalid pairing input length",
+            ProgramError::InvalidArgument,
+        ));
+    }
+    let mut result = [0u8; 32];
+    group_op(OP_PAIRING, input, &mut result)?;
+    Ok(result[31] == 1)
+}
+
+pub fn alt_bn128_g1_compress(
+    point: &[u8; ALT_BN128_G1_LEN],
+) -> Result<[u8; ALT_BN128_G1_COMPRESSED_LEN], ProgramError> {
+    let mut result = [0u8; ALT_BN128_G1_COMPRESSED_LEN];
+    compression(OP_G1_COMPRESS, point, &mut result)?;
+    Ok(result)
+}
+
+pub fn alt_bn128_g1_decompress(
+    point: &[u8; ALT_BN128_G1_COMPRESSED_LEN],
+) -> Result<[u8; ALT_BN128_G1_LEN], ProgramError> {
+    let mut result = [0u8; ALT_BN128_G1_LEN];
+    compression(OP_G1_DECOMPRESS, point, &mut result)?;
+    Ok(result)
+}
+
+pub fn alt_bn128_g2_compress(
+    point: &[u8; ALT_BN128_G2_LEN],
+) -> Result<[u8; ALT_BN128_G2_COMPRESSED_LEN], ProgramError> {
+    let mut result = [0u8; ALT_BN128_G2_COMPRESSED_LEN];
+    compression(OP_G2_COMPRESS, point, &mut result)?;
+    Ok(result)
+}
+
+pub fn alt_bn128_g2_decompress(
+    point: &[u8; ALT_BN128_G2_COMPRESSED_LEN],
+) -> Result<[u8; ALT_BN128_G2_LEN], ProgramError> {
+    let mut result = [0u8; ALT_BN128_G2_LEN];
+    compression(OP_G2_DECOMPRESS, point, &mut result)?;
+    Ok(result)
+}
+
+/// Negates a G1 point by replacing `y` with `p - y`.
+pub fn alt_bn128_g1_negate(point: &[u8; ALT_BN128_G1_LEN]) -> [u8; ALT_BN128_G1_LEN] {
+    let mut result = *point;
+    let y = &point[32..];
+    if y.iter().all(|b| *b == 0) {
+        return result;
+    }
+
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut diff = FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = (diff < 0) as i16;
+        if diff < 0 {
+            diff += 256;
+        }
+        result[32 + i] = diff as u8;
+    }
+    result
+}
+
+/// Returns `true` if `value` is a canonical scalar field element.
+#[inline(always)]
+pub fn is_alt_bn128_scalar(value: &[u8; 32]) -> bool {
+    value < &SCALAR_MODULUS
+}
+
+/// Groth16 verifying key, usually generated into a `const` by the circuit tooling.
+pub struct Groth16VerifyingKey<'a> {
+    pub alpha_g1: [u8; ALT_BN128_G1_LEN],
+    pub beta_g2: [u8; ALT_BN128_G2_LEN],
+    pub gamma_g2: [u8; ALT_BN128_G2_LEN],
+    pub delta_g2: [u8; ALT_BN128_G2_LEN],
+    /// One point per public input, plus one.
+    pub ic: &'a [[u8; ALT_BN128_G1_LEN]],
+}
+
+/// Groth16 proof as passed in instruction data, `a` not negated.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: [u8; ALT_BN128_G1_LEN],
+    pub b: [u8; ALT_BN128_G2_LEN],
+    pub c: [u8; ALT_BN128_G1_LEN],
+}
+
+impl Groth16Proof {
+    pub const LEN: usize = core::mem::size_of::<Self>();
+
+    pub fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // SAFETY: all fields are byte arrays, alignment is 1
+        Ok(unsafe { &*(data.as_ptr() as *const Self) })
+    }
+}
+
+impl Groth16VerifyingKey<'_> {
+    /// Verifies `proof` for `public_inputs`, big-endian scalar field elements.
+    #[track_caller]
+    pub fn verify(&self, proof: &Groth16Proof, public_inputs: &[[u8; 32]]) -> ProgramResult {
+        if public_inputs.len() + 1 != self.ic.len() {
+            return Err(trace(
+                "Wrong number of public inputs",
+                ProgramError::InvalidArgument,
+            ));
+        }
+
+        // vk_x = ic[0] + sum(input[i] * ic[i + 1])
+        let mut prepared = self.ic[0];
+        for (input, ic) in public_inputs.iter().zip(&self.ic[1..]) {
+            if !is_alt_bn128_scalar(input) {
+                return Err(trace(
+                    "Public input is not a field element",
+                    ProgramError::InvalidArgument,
+                ));
+            }
+            prepared = alt_bn128_add(&prepared, &alt_bn128_mul(ic, input)?)?;
+        }
+
+        // e(-a, b) * e(vk_x, gamma) * e(c, delta) * e(alpha, beta) == 1
+        let mut pairing = [0u8; 4 * ALT_BN128_PAIRING_ELEMENT_LEN];
+        let elements: [(&[u8], &[u8]); 4] = [
+            (&alt_bn128_g1_negate(&proof.a), &proof.b),
+            (&prepared, &self.gamma_g2),
+            (&proof.c, &self.delta_g2),
+            (&self.alpha_g1, &self.beta_g2),
+        ];
+        for ((g1, g2), chunk) in elements
+            .iter()
+            .zip(pairing.chunks_exact_mut(ALT_BN128_PAIRING_ELEMENT_LEN))
+        {
+            chunk[..ALT_BN128_G1_LEN].copy_from_slice(g1);
+            chunk[ALT_BN128_G1_LEN..].copy_from_slice(g2);
+        }
+
+        if !alt_bn128_pairing(&pairing)? {
+            return Err(trace(
+                "Invalid Groth16 proof",
+                ProgramError::Custom(ERROR_GROTH16_INVALID_PROOF),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_g1_negate() {
+        // Generator (1, 2) negates to (1, p - 2)
+        let mut point = [0u8; 64];
+        point[31] = 1;
+        point[63] = 2;
+        let negated = alt_bn128_g1_negate(&point);
+        assert_eq!(&negated[..32], &point[..32]);
+        let mut expected = FIELD_MODULUS;
+        expected[31] -= 2;
+        assert_eq!(&negated[32..], &expected);
+        assert_eq!(alt_bn128_g1_negate(&negated), point);
+
+        // Point at infinity is its own negation
+        assert_eq!(alt_bn128_g1_negate(&[0; 64]), [0; 64]);
+    }
+
+    #[test]
+    fn test_is_scalar() {
+        assert!(is_alt_bn128_scalar(&[0; 32]));
+        let mut max = SCALAR_MODULUS;
+        assert!(!is_alt_bn128_scalar(&max));
+        max[31] -= 1;
+        assert!(is_alt_bn128_scalar(&max));
+    }
+
+    #[test]
+    fn test_proof_from_bytes() {
+        let data = [7u8; Groth16Proof::LEN];
+        assert_eq!(Groth16Proof::try_from_bytes(&data).unwrap().c, [7; 64]);
+        assert!(Groth16Proof::try_from_bytes(&data[1..]).is_err());
+    }
+}
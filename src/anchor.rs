@@ -0,0 +1,364 @@
+//! Compatibility shims for calling Anchor programs without their crates.
+
+use crate::{PodU128LE, PodU16LE, PodU32LE, PodU64LE};
+
+/// Length of Anchor instruction and account discriminators.
+pub const ANCHOR_DISCRIMINATOR_LEN: usize = 8;
+
+/// Anchor discriminator of `preimage`, i.e. the first 8 bytes of its sha256:
+/// `global:<instruction_name>` for instructions, `account:<TypeName>` for accounts.
+///
+/// Computed at compile time when used in a `const`.
+pub const fn anchor_discriminator(preimage: &[u8]) -> [u8; ANCHOR_DISCRIMINATOR_LEN] {
+    let hash = sha256(preimage);
+    let mut out = [0u8; ANCHOR_DISCRIMINATOR_LEN];
+    let mut i = 0;
+    while i < ANCHOR_DISCRIMINATOR_LEN {
+        out[i] = hash[i];
+        i += 1;
+    }
+    out
+}
+
+/// Fixed-size borsh serialization of instruction arguments.
+pub trait BorshArg {
+    /// Maximum serialized length.
+    const MAX_LEN: usize;
+
+    /// Writes the borsh encoding to the start of `buf`, returns its length.
+    fn write_borsh(&self, buf: &mut [u8]) -> usize;
+}
+
+macro_rules! borsh_arg_int {
+    ($($ty:ty),*) => {
+        $(impl BorshArg for $ty {
+            const MAX_LEN: usize = core::mem::size_of::<$ty>();
+
+            #[inline(always)]
+            fn write_borsh(&self, buf: &mut [u8]) -> usize {
+                buf[..Self::MAX_LEN].copy_from_slice(&self.to_le_bytes());
+                Self::MAX_LEN
+            }
+        })*
+    };
+}
+
+borsh_arg_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+macro_rules! borsh_arg_pod {
+    ($($pod:ty => $ty:ty),*) => {
+        $(impl BorshArg for $pod {
+            const MAX_LEN: usize = <$ty as BorshArg>::MAX_LEN;
+
+            #[inline(always)]
+            fn write_borsh(&self, buf: &mut [u8]) -> usize {
+                self.get().write_borsh(buf)
+            }
+        })*
+    };
+}
+
+borsh_arg_pod!(PodU16LE => u16, PodU32LE => u32, PodU64LE => u64, PodU128LE => u128);
+
+impl BorshArg for bool {
+    const MAX_LEN: usize = 1;
+
+    #[inline(always)]
+    fn write_borsh(&self, buf: &mut [u8]) -> usize {
+        buf[0] = *self as u8;
+        1
+    }
+}
+
+/// Fixed-size byte arrays, including pubkeys, are written as is.
+impl<const N: usize> BorshArg for [u8; N] {
+    const MAX_LEN: usize = N;
+
+    #[inline(always)]
+    fn write_borsh(&self, buf: &mut [u8]) -> usize {
+        buf[..N].copy_from_slice(self);
+        N
+    }
+}
+
+impl<T: BorshArg> BorshArg for Option<T> {
+    const MAX_LEN: usize = 1 + T::MAX_LEN;
+
+    #[inline(always)]
+    fn write_borsh(&self, buf: &mut [u8]) -> usize {
+        match self {
+            None => {
+                buf[0] = 0;
+                1
+            }
+            Some(value) => {
+                buf[0] = 1;
+                1 + value.write_borsh(&mut buf[1..])
+            }
+        }
+    }
+}
+
+/// Declare a CPI to an instruction of an Anchor program, generating its 8 byte
+/// discriminator, the borsh encoding of its arguments (`data`), its account metas
+/// (`metas`) and `invoke`/`invoke_signed`.
+///
+/// Accounts use the modes of [`cpi_interface!`](crate::cpi_interface), in the
+/// order of the Anchor `Accounts` struct. Arguments implement [`BorshArg`].
+///
+/// ```ignore
+/// anchor_cpi!(pub struct Swap => swap {
+///     accounts { pool: writable, user_source: writable, user_destination: writable, user: readonly_signer },
+///     args { amount_in: u64, minimum_amount_out: u64 },
+/// });
+///
+/// Swap { program_id: &DEX_PROGRAM_ID, pool, user_source, user_destination, user, amount_in, minimum_amount_out }
+///     .invoke()?;
+/// ```
+#[macro_export]
+macro_rules! anchor_cpi {
+    ($vis:vis struct $name:ident => $ix:ident {
+        accounts { $($account:ident : $mode:ident),* $(,)? },
+        args { $($arg:ident : $ty:ty),* $(,)? } $(,)?
+    }) => {
+        $vis struct $name<'a> {
+            /// Callee program ID.
+            pub program_id: &'a pinocchio::pubkey::Pubkey,
+            $(pub $account: &'a pinocchio::account_info::AccountInfo,)*
+            $(pub $arg: $ty,)*
+        }
+
+        impl $name<'_> {
+            /// Anchor discriminator of the instruction, `sha256("global:<name>")[..8]`.
+            pub const DISCRIMINATOR: [u8; $crate::ANCHOR_DISCRIMINATOR_LEN] =
+                $crate::anchor_discriminator(concat!("global:", stringify!($ix)).as_bytes());
+
+            /// Maximum length of the instruction data.
+            pub const MAX_LEN: usize =
+                $crate::ANCHOR_DISCRIMINATOR_LEN $(+ <$ty as $crate::BorshArg>::MAX_LEN)*;
+
+            #[inline(always)]
+            pub fn invoke(&self) -> pinocchio::ProgramResult {
+                self.invoke_signed(&[])
+            }
+
+            pub fn invoke_signed(
+                &self,
+                signers: &[pinocchio::instruction::Signer],
+            ) -> pinocchio::ProgramResult {
+                let (data, len) = self.data();
+                let accounts = self.metas();
+                let instruction = pinocchio::instruction::Instruction {
+                    program_id: self.program_id,
+                    accounts: &accounts,
+                    data: &data[..len],
+                };
+
+                pinocchio::cpi::invoke_signed(&instruction, &[$(self.$account),*], signers)
+            }
+
+            /// Instruction data, the discriminator followed by the borsh encoded
+            /// arguments, and its length.
+            pub fn data(
+                &self,
+            ) -> ([u8; $crate::ANCHOR_DISCRIMINATOR_LEN $(+ <$ty as $crate::BorshArg>::MAX_LEN)*], usize) {
+                let mut data =
+                    [0u8; $crate::ANCHOR_DISCRIMINATOR_LEN $(+ <$ty as $crate::BorshArg>::MAX_LEN)*];
+                data[..$crate::ANCHOR_DISCRIMINATOR_LEN].copy_from_slice(&Self::DISCRIMINATOR);
+                #[allow(unused_mut)]
+                let mut len = $crate::ANCHOR_DISCRIMINATOR_LEN;
+                $(len += $crate::BorshArg::write_borsh(&self.$arg, &mut data[len..]);)*
+                (data, len)
+            }
+
+            /// Account metas, in the order of the Anchor `Accounts` struct.
+            pub fn metas(
+                &self,
+            ) -> [pinocchio::instruction::AccountMeta<'_>; 0 $(+ $crate::account!(@one $account))*] {
+                [$($crate::cpi_interface!(@meta self.$account, $mode)),*]
+            }
+        }
+    };
+}
+
+/// Round constants of sha256.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Compile-time sha256, for discriminators and host-side PDA derivation. Use the
+/// syscall based hashes on-chain.
+pub(crate) const fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let bit_len = (data.len() as u64) * 8;
+    // Message, 0x80 terminator and 64 bit length, padded to whole blocks
+    let total = (data.len() + 9).div_ceil(64) * 64;
+
+    let mut block = 0;
+    while block < total {
+        let mut w = [0u32; 64];
+        let mut i = 0;
+        while i < 16 {
+            let mut word = 0u32;
+            let mut j = 0;
+            while j < 4 {
+                let idx = block + i * 4 + j;
+                let byte = if idx < data.len() {
+                    data[idx]
+                } else if idx == data.len() {
+                    0x80
+                } else if idx >= total - 8 {
+                    (bit_len >> (8 * (total - 1 - idx))) as u8
+                } else {
+                    0
+                };
+                word = (word << 8) | byte as u32;
+                j += 1;
+            }
+            w[i] = word;
+            i += 1;
+        }
+        while i < 64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+            i += 1;
+        }
+
+        let (mut a, mut b, mut c, mut d) = (h[0], h[1], h[2], h[3]);
+        let (mut e, mut f, mut g, mut hh) = (h[4], h[5], h[6], h[7]);
+        i = 0;
+        while i < 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+            i += 1;
+        }
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+        h[5] = h[5].wrapping_add(f);
+        h[6] = h[6].wrapping_add(g);
+        h[7] = h[7].wrapping_add(hh);
+        block += 64;
+    }
+
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 8 {
+        let bytes = h[i].to_be_bytes();
+        out[i * 4] = bytes[0];
+        out[i * 4 + 1] = bytes[1];
+        out[i * 4 + 2] = bytes[2];
+        out[i * 4 + 3] = bytes[3];
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use pinocchio::pubkey::Pubkey;
+
+    use super::*;
+    use crate::MockAccount;
+
+    anchor_cpi!(struct Swap => swap {
+        accounts { pool: writable, user: readonly_signer },
+        args { amount_in: u64, minimum_amount_out: Option<u64> },
+    });
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256(b"abc"),
+            [
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+                0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+                0xf2, 0x00, 0x15, 0xad
+            ]
+        );
+        // Spans two blocks
+        assert_eq!(
+            sha256(&[b'a'; 100])[..8],
+            [40, 22, 89, 120, 136, 228, 160, 211]
+        );
+    }
+
+    #[test]
+    fn test_anchor_discriminator() {
+        const INITIALIZE: [u8; 8] = anchor_discriminator(b"global:initialize");
+        assert_eq!(INITIALIZE, [175, 175, 109, 31, 13, 152, 155, 237]);
+    }
+
+    #[test]
+    fn test_borsh_arg() {
+        let mut buf = [0u8; 16];
+        assert_eq!(7u32.write_borsh(&mut buf), 4);
+        assert_eq!(buf[..4], [7, 0, 0, 0]);
+        assert_eq!(Some(true).write_borsh(&mut buf), 2);
+        assert_eq!(buf[..2], [1, 1]);
+        assert_eq!(None::<u64>.write_borsh(&mut buf), 1);
+        assert_eq!(buf[0], 0);
+        assert_eq!(<Option<Pubkey> as BorshArg>::MAX_LEN, 33);
+    }
+
+    #[test]
+    fn test_anchor_cpi() {
+        let program_id = [9; 32];
+        let mut pool = MockAccount::new([1; 32], program_id, 100, &[]).writable(true);
+        let mut user = MockAccount::new([2; 32], [0; 32], 100, &[]).signer(true);
+        let (pool, user) = (pool.info(), user.info());
+        let swap = Swap {
+            program_id: &program_id,
+            pool: &pool,
+            user: &user,
+            amount_in: 5,
+            minimum_amount_out: None,
+        };
+        assert_eq!(Swap::MAX_LEN, 8 + 8 + 9);
+
+        let (data, len) = swap.data();
+        assert_eq!(len, 17);
+        assert_eq!(data[..8], anchor_discriminator(b"global:swap"));
+        assert_eq!(data[8..len], [5, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let metas = swap.metas();
+        assert_eq!(metas[0].pubkey, &[1; 32]);
+        assert!(metas[0].is_writable && !metas[0].is_signer);
+        assert_eq!(metas[1].pubkey, &[2; 32]);
+        assert!(!metas[1].is_writable && metas[1].is_signer);
+
+        assert_eq!(swap.invoke(), Ok(()));
+    }
+}
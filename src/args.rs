@@ -0,0 +1,133 @@
+//! Instruction argument checks used by the `validate` arm of `instruction!`.
+
+use core::ops::RangeBounds;
+
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey, ProgramResult};
+use pinocchio_log::log;
+
+#[track_caller]
+#[inline(always)]
+fn invalid_arg(name: &str) -> ProgramError {
+    log!("Invalid instruction argument: {}", name);
+    ProgramError::InvalidInstructionData
+}
+
+/// Fails if `value` equals its type's default, e.g. `0`.
+#[track_caller]
+#[inline(always)]
+pub fn check_arg_non_zero<T: PartialEq + Default>(value: &T, name: &str) -> ProgramResult {
+    if value.eq(&T::default()) {
+        return Err(invalid_arg(name));
+    }
+    Ok(())
+}
+
+/// Fails if `value` is outside of `range`.
+#[track_caller]
+#[inline(always)]
+pub fn check_arg_range<T: PartialOrd, R: RangeBounds<T>>(
+    value: &T,
+    range: R,
+    name: &str,
+) -> ProgramResult {
+    if !range.contains(value) {
+        return Err(invalid_arg(name));
+    }
+    Ok(())
+}
+
+/// Fails if `value` is the default (all zero) pubkey.
+#[track_caller]
+#[inline(always)]
+pub fn check_arg_pubkey(value: &Pubkey, name: &str) -> ProgramResult {
+    if value.eq(&Pubkey::default()) {
+        return Err(invalid_arg(name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct Swap {
+        amount: u64,
+        fee_bps: u16,
+        authority: Pubkey,
+    }
+
+    #[repr(u8)]
+    pub enum Ix {
+        Swap = 0,
+    }
+
+    crate::instruction!(
+        Ix,
+        Swap,
+        validate {
+            amount: non_zero,
+            fee_bps: range(1..=10_000),
+            authority: valid_pubkey,
+        },
+        accounts SwapAccounts {
+            user: writable_signer,
+            pool: writable,
+            token_program: readonly,
+        }
+    );
+
+    use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+    #[test]
+    fn test_validate() {
+        let ok = Swap {
+            amount: 1,
+            fee_bps: 30,
+            authority: [1; 32],
+        };
+        assert!(ok.validate().is_ok());
+
+        let cases = [
+            Swap { amount: 0, ..ok },
+            Swap { fee_bps: 0, ..ok },
+            Swap {
+                fee_bps: 10_001,
+                ..ok
+            },
+            Swap {
+                authority: [0; 32],
+                ..ok
+            },
+        ];
+        for case in cases {
+            assert_eq!(case.validate(), Err(ProgramError::InvalidInstructionData));
+        }
+    }
+
+    #[test]
+    fn test_accounts() {
+        assert_eq!(
+            Swap::ACCOUNTS,
+            &[
+                AccountSpec::writable_signer("user"),
+                AccountSpec::writable("pool"),
+                AccountSpec::readonly("token_program"),
+            ]
+        );
+
+        let (user, pool, token_program) = ([1; 32], [2; 32], [3; 32]);
+        let metas = SwapAccounts {
+            user: &user,
+            pool: &pool,
+            token_program: &token_program,
+        }
+        .metas();
+        assert_eq!(SwapAccounts::LEN, 3);
+        assert_eq!(metas[0].pubkey, &user);
+        assert!(metas[0].is_writable && metas[0].is_signer);
+        assert!(metas[1].is_writable && !metas[1].is_signer);
+        assert!(!metas[2].is_writable && !metas[2].is_signer);
+    }
+}
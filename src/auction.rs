@@ -0,0 +1,129 @@
+//! Price curves for Dutch auctions, e.g. NFT mints and liquidations.
+//!
+//! Prices are clamped to the auction window and rounded explicitly, so a
+//! buyer can be quoted the exact price the program will charge.
+
+use crate::{Rounding, WAD};
+
+/// ln(2), `WAD` scaled.
+const LN_2_WAD: u128 = 693_147_180_559_945_309;
+
+/// Price moving linearly from `start_price` at `start_ts` to `end_price` at `end_ts`.
+///
+/// Before the window the price is `start_price`, after it `end_price`, also if
+/// `end_ts <= start_ts`. Works for decreasing and increasing prices alike.
+pub fn price_at(
+    now: u64,
+    start_price: u64,
+    end_price: u64,
+    start_ts: u64,
+    end_ts: u64,
+    rounding: Rounding,
+) -> u64 {
+    if now <= start_ts {
+        return start_price;
+    }
+    if now >= end_ts {
+        return end_price;
+    }
+    let duration = (end_ts - start_ts) as u128;
+    let elapsed = (now - start_ts) as u128;
+    // Weighted average of both prices, can't exceed the larger one
+    let weighted = start_price as u128 * (duration - elapsed) + end_price as u128 * elapsed;
+    div_round(weighted, duration, rounding) as u64
+}
+
+/// Price halving every `half_life_secs` from `start_price` at `start_ts`, but
+/// never below `floor_price`.
+///
+/// The decay is continuous, `start_price * 2^(-elapsed / half_life_secs)`, with
+/// a relative error below 1e-12. A zero half-life drops to the floor at once.
+pub fn exponential_price_at(
+    now: u64,
+    start_price: u64,
+    floor_price: u64,
+    start_ts: u64,
+    half_life_secs: u64,
+    rounding: Rounding,
+) -> u64 {
+    if now <= start_ts {
+        return start_price.max(floor_price);
+    }
+    if half_life_secs == 0 {
+        return floor_price;
+    }
+    let elapsed = now - start_ts;
+    let halvings = elapsed / half_life_secs;
+    // 2^(-fraction) for the part of a half-life left over
+    let fraction = (elapsed % half_life_secs) as u128 * WAD / half_life_secs as u128;
+    let factor = exp_neg_wad(fraction * LN_2_WAD / WAD);
+
+    let scaled = start_price as u128 * factor;
+    let price = if halvings <= 68 {
+        // WAD * 2^68 still fits in u128
+        div_round(scaled, WAD << halvings, rounding) as u64
+    } else {
+        // Less than a quarter is left
+        match rounding {
+            Rounding::Up => (scaled > 0) as u64,
+            Rounding::Down | Rounding::Nearest => 0,
+        }
+    };
+    price.max(floor_price)
+}
+
+/// `e^(-x)` for `0 <= x < 1`, `WAD` scaled, from its Taylor series.
+fn exp_neg_wad(x: u128) -> u128 {
+    let mut sum = WAD as i128;
+    let mut term = WAD as i128;
+    for n in 1..=14 {
+        term = -term * x as i128 / WAD as i128 / n;
+        sum += term;
+    }
+    sum as u128
+}
+
+fn div_round(numerator: u128, denominator: u128, rounding: Rounding) -> u128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => remainder > 0,
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+    quotient + round_up as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_price() {
+        assert_eq!(price_at(50, 1_000, 100, 100, 200, Rounding::Down), 1_000);
+        assert_eq!(price_at(150, 1_000, 100, 100, 200, Rounding::Down), 550);
+        assert_eq!(price_at(250, 1_000, 100, 100, 200, Rounding::Down), 100);
+        // Increasing price
+        assert_eq!(price_at(125, 100, 1_000, 100, 200, Rounding::Down), 325);
+
+        // (2 * 1000 + 99) / 3 = 699.67
+        assert_eq!(price_at(1, 1_000, 99, 0, 3, Rounding::Down), 699);
+        assert_eq!(price_at(1, 1_000, 99, 0, 3, Rounding::Up), 700);
+        assert_eq!(price_at(1, 1_000, 99, 0, 3, Rounding::Nearest), 700);
+    }
+
+    #[test]
+    fn test_exponential_price() {
+        let price = |now, rounding| exponential_price_at(now, 1_000_000, 1_000, 0, 100, rounding);
+        assert_eq!(price(0, Rounding::Down), 1_000_000);
+        assert_eq!(price(100, Rounding::Down), 500_000);
+        assert_eq!(price(200, Rounding::Down), 250_000);
+        // 1e6 / sqrt(2) = 707106.78
+        assert_eq!(price(50, Rounding::Down), 707_106);
+        assert_eq!(price(50, Rounding::Up), 707_107);
+        assert_eq!(price(50, Rounding::Nearest), 707_107);
+        // Clamped to the floor
+        assert_eq!(price(1_000, Rounding::Down), 1_000);
+        assert_eq!(price(100_000, Rounding::Up), 1_000);
+    }
+}
@@ -0,0 +1,111 @@
+//! Compute unit budget tests, running instruction handlers under Mollusk's
+//! compute meter.
+//!
+//! ```ignore
+//! cu_budget_test!(increment_fits_budget, 1_200, || {
+//!     let mollusk = Mollusk::new(&PROGRAM_ID, "target/deploy/counter");
+//!     mollusk.process_instruction(&increment(payer, counter), &accounts)
+//! });
+//! ```
+//!
+//! Running them with [`CU_REGISTRY_ENV`] set to a file path appends each
+//! measurement to that file, which clients load as a `CuRegistry` to size
+//! their compute unit limits.
+
+use std::{format, fs::OpenOptions, io::Write as _, path::Path, string::String};
+
+use mollusk_svm::result::InstructionResult;
+use pinocchio::pubkey::Pubkey;
+
+use crate::AccountStore;
+
+/// Environment variable naming the file [`assert_cu_budget`] records its
+/// measurements to.
+pub const CU_REGISTRY_ENV: &str = "PINSTEEL_CU_REGISTRY";
+
+/// Appends the measurement of `name` to the registry file at `path`, as one
+/// `<name> <units>` line.
+pub fn record_cu(path: impl AsRef<Path>, name: &str, units: u64) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{} {}", name, units)
+}
+
+/// Fails if `consumed` exceeds `max_cu`, otherwise returns the headroom left.
+pub fn check_cu_budget(name: &str, consumed: u64, max_cu: u64) -> Result<u64, String> {
+    max_cu.checked_sub(consumed).ok_or_else(|| {
+        format!(
+            "{} consumed {} CU, over its budget of {} CU by {}",
+            name,
+            consumed,
+            max_cu,
+            consumed - max_cu
+        )
+    })
+}
+
+/// Panics unless the instruction succeeded within `max_cu` compute units,
+/// otherwise returns the headroom left.
+///
+/// Records the consumption with [`record_cu`] if [`CU_REGISTRY_ENV`] is set.
+#[track_caller]
+pub fn assert_cu_budget(name: &str, result: &InstructionResult, max_cu: u64) -> u64 {
+    if result.raw_result.is_err() {
+        panic!("{} failed: {:?}", name, result.program_result);
+    }
+    if let Ok(path) = std::env::var(CU_REGISTRY_ENV) {
+        record_cu(&path, name, result.compute_units_consumed)
+            .unwrap_or_else(|e| panic!("failed to record {} CU to {}: {}", name, path, e));
+    }
+    check_cu_budget(name, result.compute_units_consumed, max_cu)
+        .unwrap_or_else(|message| panic!("{}", message))
+}
+
+/// Resulting accounts of the instruction, for [`assert_account_eq`](crate::assert_account_eq).
+impl AccountStore for InstructionResult {
+    fn account_data(&self, key: &Pubkey) -> Option<&[u8]> {
+        self.resulting_accounts
+            .iter()
+            .find(|(account_key, _)| account_key.to_bytes() == *key)
+            .map(|(_, account)| account.data.as_slice())
+    }
+}
+
+/// Declare a test that fails when the instruction returned by the closure
+/// fails or consumes more than `max_cu` compute units.
+#[macro_export]
+macro_rules! cu_budget_test {
+    ($name:ident, $max_cu:expr, || $body:block) => {
+        #[test]
+        fn $name() {
+            let result: mollusk_svm::result::InstructionResult = (|| $body)();
+            $crate::assert_cu_budget(stringify!($name), &result, $max_cu);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_cu_budget() {
+        assert_eq!(check_cu_budget("ix", 900, 1_000), Ok(100));
+        assert_eq!(check_cu_budget("ix", 1_000, 1_000), Ok(0));
+        assert_eq!(
+            check_cu_budget("ix", 1_250, 1_000),
+            Err("ix consumed 1250 CU, over its budget of 1000 CU by 250".into())
+        );
+    }
+
+    #[test]
+    fn test_record_cu() {
+        let path = std::env::temp_dir().join(format!("pinsteel-cu-{}", std::process::id()));
+        record_cu(&path, "deposit", 9_000).unwrap();
+        record_cu(&path, "withdraw", 12_000).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "deposit 9000\nwithdraw 12000\n"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
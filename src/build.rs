@@ -0,0 +1,87 @@
+//! Build script helpers.
+//!
+//! PDAs with fully static seeds (e.g. `b"config"`) can be derived once in the
+//! program's `build.rs` and included as constants, so neither the ~1500 CU
+//! `find_program_address` call nor a bump in instruction data is needed:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     pinsteel::PdaConsts::new(&PROGRAM_ID)
+//!         .add("CONFIG", &[b"config"])
+//!         .write("pdas.rs")
+//!         .unwrap();
+//! }
+//!
+//! // lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/pdas.rs"));
+//! ```
+
+use std::{fmt::Write as _, string::String};
+
+use pinocchio::pubkey::Pubkey;
+
+use crate::find_program_address_offchain;
+
+/// Generates a Rust source file of precomputed PDA constants.
+pub struct PdaConsts<'a> {
+    program_id: &'a Pubkey,
+    out: String,
+}
+
+impl<'a> PdaConsts<'a> {
+    pub fn new(program_id: &'a Pubkey) -> Self {
+        Self {
+            program_id,
+            out: String::new(),
+        }
+    }
+
+    /// Emits `pub const {name}: Pubkey` and `pub const {name}_BUMP: u8`.
+    ///
+    /// # Panics
+    /// If no valid PDA exists for `seeds`.
+    pub fn add(&mut self, name: &str, seeds: &[&[u8]]) -> &mut Self {
+        let (pda, bump) = find_program_address_offchain(seeds, self.program_id)
+            .unwrap_or_else(|| panic!("no valid PDA for `{name}`"));
+
+        writeln!(
+            self.out,
+            "pub const {name}: pinocchio::pubkey::Pubkey = {pda:?};\npub const {name}_BUMP: u8 = {bump};"
+        )
+        .unwrap();
+        self
+    }
+
+    /// Generated source.
+    pub fn source(&self) -> &str {
+        &self.out
+    }
+
+    /// Writes the generated source to `$OUT_DIR/{file_name}`.
+    pub fn write(&self, file_name: &str) -> std::io::Result<()> {
+        let out_dir = std::env::var("OUT_DIR")
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
+        std::fs::write(std::path::Path::new(&out_dir).join(file_name), &self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_program_address_offchain;
+
+    #[test]
+    fn test_find_program_address_offchain() {
+        let program_id = [3u8; 32];
+        let (pda, bump) = find_program_address_offchain(&[b"config"], &program_id).unwrap();
+        assert_eq!(
+            create_program_address_offchain(&[b"config", &[bump]], &program_id),
+            Some(pda)
+        );
+
+        let mut consts = PdaConsts::new(&program_id);
+        consts.add("CONFIG", &[b"config"]);
+        assert!(consts.source().contains("pub const CONFIG_BUMP: u8"));
+    }
+}
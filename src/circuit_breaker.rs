@@ -0,0 +1,139 @@
+//! Pause switch tripped when an oracle price deviates too far from a
+//! reference price such as a TWAP, emitting [`CircuitBreakerTripped`].
+//!
+//! ```ignore
+//! let price = PythPriceUpdate::load(oracle)?.get_price_no_older_than(&clock, 60, &FEED_ID)?;
+//! if state.breaker.check_and_trip(price.price as u64, state.twap.get(), 500)? {
+//!     // Return Ok so the pause is persisted, an error would revert it
+//!     return Ok(());
+//! }
+//! ```
+
+use pinocchio::{program_error::ProgramError, ProgramResult};
+
+use crate::{
+    event, to_u64_checked, trace, widen, Loggable, PodU64LE, CIRCUIT_BREAKER_TRIPPED_EVENT,
+};
+
+pub const ERROR_CIRCUIT_BREAKER_TRIPPED: u32 = 28;
+
+/// Emitted when a [`CircuitBreaker`] trips.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerTripped {
+    /// Always [`CIRCUIT_BREAKER_TRIPPED_EVENT`].
+    pub event: u8,
+    pub _padding: [u8; 7],
+    pub price: u64,
+    pub reference: u64,
+    /// Deviation of `price` from `reference`, saturated to `u64::MAX`.
+    pub deviation_bps: u64,
+}
+
+event!(CircuitBreakerTripped);
+
+/// Pause flag set once a price deviates too far from its reference, until
+/// [`CircuitBreaker::reset`] by an admin.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Non-zero once tripped.
+    pub paused: u8,
+    /// Price and reference of the last trip.
+    pub trip_price: PodU64LE,
+    pub trip_reference: PodU64LE,
+}
+
+impl CircuitBreaker {
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    /// Fails with [`ERROR_CIRCUIT_BREAKER_TRIPPED`] while paused.
+    #[track_caller]
+    #[inline(always)]
+    pub fn assert_not_paused(&self) -> ProgramResult {
+        if self.is_paused() {
+            return Err(trace(
+                "Circuit breaker is tripped",
+                ProgramError::Custom(ERROR_CIRCUIT_BREAKER_TRIPPED),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Trips the breaker and logs a [`CircuitBreakerTripped`] event if `price`
+    /// deviates more than `max_deviation_bps` from `reference`, returning `true`
+    /// if it did. Fails while already paused or if `reference` is zero.
+    ///
+    /// The caller must return `Ok` after a trip, as an error would roll the
+    /// pause back with the rest of the transaction.
+    #[track_caller]
+    pub fn check_and_trip(
+        &mut self,
+        price: u64,
+        reference: u64,
+        max_deviation_bps: u16,
+    ) -> Result<bool, ProgramError> {
+        self.assert_not_paused()?;
+        if reference == 0 {
+            return Err(trace(
+                "Reference price is zero",
+                ProgramError::InvalidArgument,
+            ));
+        }
+
+        let diff = widen(price.abs_diff(reference));
+        if diff * 10_000 <= widen(reference) * max_deviation_bps as u128 {
+            return Ok(false);
+        }
+
+        self.paused = 1;
+        self.trip_price.set(price);
+        self.trip_reference.set(reference);
+        CircuitBreakerTripped {
+            event: CIRCUIT_BREAKER_TRIPPED_EVENT,
+            _padding: [0; 7],
+            price,
+            reference,
+            deviation_bps: to_u64_checked(diff * 10_000 / widen(reference)).unwrap_or(u64::MAX),
+        }
+        .log();
+        Ok(true)
+    }
+
+    /// Clears the pause, the caller is responsible for checking the admin.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.paused = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let mut breaker = CircuitBreaker::default();
+        assert_eq!(breaker.check_and_trip(105, 100, 500), Ok(false));
+        assert_eq!(breaker.check_and_trip(95, 100, 500), Ok(false));
+        assert!(!breaker.is_paused());
+
+        assert_eq!(breaker.check_and_trip(94, 100, 500), Ok(true));
+        assert!(breaker.is_paused());
+        assert_eq!(breaker.trip_price.get(), 94);
+        assert_eq!(
+            breaker.check_and_trip(100, 100, 500),
+            Err(ProgramError::Custom(ERROR_CIRCUIT_BREAKER_TRIPPED))
+        );
+
+        breaker.reset();
+        assert_eq!(breaker.assert_not_paused(), Ok(()));
+        assert_eq!(
+            breaker.check_and_trip(100, 0, 500),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
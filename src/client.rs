@@ -0,0 +1,194 @@
+//! Off-chain helpers for indexers and clients.
+
+use std::vec::Vec;
+
+use pinocchio::pubkey::Pubkey;
+use solana_transaction_status::{
+    EncodedTransaction, EncodedTransactionWithStatusMeta, UiInstruction, UiLoadedAddresses,
+    UiMessage, UiParsedInstruction,
+};
+
+use crate::{decode_event_frame, is_event_frame, EMIT_EVENT_DISCRIMINATOR};
+
+/// Decodes the events of a program from their raw bytes.
+///
+/// Usually implemented on an enum with one variant per `event!` struct,
+/// dispatching on the event's own discriminator.
+pub trait EventRegistry: Sized {
+    fn decode(data: &[u8]) -> Option<Self>;
+}
+
+/// Returns the event bytes if `data` is a self-CPI `EmitEvent` of `program_id`.
+#[inline]
+pub fn parse_cpi_event<'a>(
+    program_id: &Pubkey,
+    invoked_program: &Pubkey,
+    data: &'a [u8],
+) -> Option<&'a [u8]> {
+    if invoked_program.ne(program_id) {
+        return None;
+    }
+    match data.split_first() {
+        Some((&EMIT_EVENT_DISCRIMINATOR, frame)) if is_event_frame(frame) => {
+            decode_event_frame(frame).ok()
+        }
+        // Emitted with `EmitEvent::invoke_signed_legacy`
+        Some((&EMIT_EVENT_DISCRIMINATOR, event)) if !event.is_empty() => Some(event),
+        _ => None,
+    }
+}
+
+/// Decodes all self-CPI events of `program_id` from `(invoked_program, data)` pairs.
+pub fn decode_cpi_events<'a, E: EventRegistry>(
+    program_id: &Pubkey,
+    instructions: impl IntoIterator<Item = (&'a Pubkey, &'a [u8])>,
+) -> Vec<E> {
+    instructions
+        .into_iter()
+        .filter_map(|(invoked, data)| parse_cpi_event(program_id, invoked, data))
+        .filter_map(E::decode)
+        .collect()
+}
+
+/// Decodes all self-CPI events of `program_id` from a transaction's inner instructions.
+///
+/// Compiled instructions are resolved against the transaction's static account keys
+/// followed by its loaded addresses. Transactions that can't be decoded yield no events.
+pub fn decode_transaction_events<E: EventRegistry>(
+    program_id: &Pubkey,
+    tx: &EncodedTransactionWithStatusMeta,
+) -> Vec<E> {
+    let Some(meta) = &tx.meta else {
+        return Vec::new();
+    };
+    let inner: Option<Vec<_>> = meta.inner_instructions.clone().into();
+    let Some(inner) = inner else {
+        return Vec::new();
+    };
+
+    // Resolve account keys used by compiled instructions.
+    let mut keys: Vec<Pubkey> = match &tx.transaction {
+        EncodedTransaction::Json(ui) => match &ui.message {
+            UiMessage::Raw(raw) => raw
+                .account_keys
+                .iter()
+                .filter_map(|k| decode_pubkey(k))
+                .collect(),
+            UiMessage::Parsed(parsed) => parsed
+                .account_keys
+                .iter()
+                .filter_map(|k| decode_pubkey(&k.pubkey))
+                .collect(),
+        },
+        encoded => encoded
+            .decode()
+            .map(|vtx| {
+                vtx.message
+                    .static_account_keys()
+                    .iter()
+                    .map(|k| k.to_bytes())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    let loaded: Option<UiLoadedAddresses> = meta.loaded_addresses.clone().into();
+    if let Some(loaded) = loaded {
+        keys.extend(loaded.writable.iter().filter_map(|k| decode_pubkey(k)));
+        keys.extend(loaded.readonly.iter().filter_map(|k| decode_pubkey(k)));
+    }
+
+    let mut instructions: Vec<(Pubkey, Vec<u8>)> = Vec::new();
+    for ix in inner.iter().flat_map(|i| i.instructions.iter()) {
+        let (invoked, data) = match ix {
+            UiInstruction::Compiled(c) => match keys.get(c.program_id_index as usize) {
+                Some(k) => (*k, &c.data),
+                None => continue,
+            },
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(p)) => {
+                match decode_pubkey(&p.program_id) {
+                    Some(k) => (k, &p.data),
+                    None => continue,
+                }
+            }
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => continue,
+        };
+        if let Ok(data) = bs58::decode(data).into_vec() {
+            instructions.push((invoked, data));
+        }
+    }
+
+    decode_cpi_events(
+        program_id,
+        instructions.iter().map(|(k, d)| (k, d.as_slice())),
+    )
+}
+
+/// Decodes an `abort_with` payload from a failed simulation's return data.
+///
+/// Returns `None` unless the return data was set by `program_id` and is exactly
+/// `size_of::<T>()` long.
+pub fn decode_error_payload<T: Copy>(
+    program_id: &Pubkey,
+    return_program_id: &Pubkey,
+    data: &[u8],
+) -> Option<T> {
+    if return_program_id.ne(program_id) || data.len() != core::mem::size_of::<T>() {
+        return None;
+    }
+    // SAFETY: length is exactly `size_of::<T>()`, `read_unaligned` has no alignment requirement
+    Some(unsafe { core::ptr::read_unaligned(data.as_ptr() as *const T) })
+}
+
+fn decode_pubkey(s: &str) -> Option<Pubkey> {
+    bs58::decode(s).into_vec().ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Ev(u8);
+
+    impl EventRegistry for Ev {
+        fn decode(data: &[u8]) -> Option<Self> {
+            data.first().map(|&b| Ev(b))
+        }
+    }
+
+    #[test]
+    fn test_decode_cpi_events() {
+        let program = [1u8; 32];
+        let other = [2u8; 32];
+        let frame = |event: &[u8]| {
+            let mut buf = [0u8; 16];
+            buf[0] = EMIT_EVENT_DISCRIMINATOR;
+            let len = crate::encode_event_frame(event, true, &mut buf[1..]).unwrap();
+            buf[..1 + len].to_vec()
+        };
+        let (seven, eight) = (frame(&[7]), frame(&[8]));
+        let truncated = &seven[..seven.len() - 1];
+        let ixs: [(&Pubkey, &[u8]); 6] = [
+            (&program, &seven),
+            (&other, &eight),
+            (&program, &[0, 9]),
+            (&program, &[EMIT_EVENT_DISCRIMINATOR]),
+            (&program, truncated),
+            (&program, &[EMIT_EVENT_DISCRIMINATOR, 6]),
+        ];
+        let events: Vec<Ev> = decode_cpi_events(&program, ixs);
+        assert_eq!(events, [Ev(7), Ev(6)]);
+    }
+
+    #[test]
+    fn test_decode_error_payload() {
+        let program = [1u8; 32];
+        let data = [5u8, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            decode_error_payload::<u64>(&program, &program, &data),
+            Some(5)
+        );
+        assert_eq!(decode_error_payload::<u64>(&program, &[2; 32], &data), None);
+        assert_eq!(decode_error_payload::<u32>(&program, &program, &data), None);
+    }
+}
@@ -0,0 +1,121 @@
+//! Cluster selection at build time, for addresses that differ per cluster.
+//!
+//! The cluster is chosen by the `devnet` or `localnet` cargo feature, else by the
+//! `PINSTEEL_CLUSTER` environment variable (`mainnet`, `devnet`, `localnet`) at
+//! build time, defaulting to mainnet.
+//!
+//! ```ignore
+//! pub const TREASURY: Pubkey = ClusterConst::new(
+//!     pubkey!("Treasury1111111111111111111111111111111111"),
+//!     pubkey!("DevTreasury11111111111111111111111111111111"),
+//!     pubkey!("LocalTreasury111111111111111111111111111111"),
+//! )
+//! .get();
+//!
+//! log!("running on {}", cluster_name());
+//! ```
+
+use crate::str_eq;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Localnet,
+}
+
+impl Cluster {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "mainnet",
+            Cluster::Devnet => "devnet",
+            Cluster::Localnet => "localnet",
+        }
+    }
+
+    /// Parses a cluster name, `None` if unknown.
+    pub const fn from_name(name: &str) -> Option<Self> {
+        if str_eq(name, "mainnet") || str_eq(name, "mainnet-beta") {
+            Some(Cluster::Mainnet)
+        } else if str_eq(name, "devnet") {
+            Some(Cluster::Devnet)
+        } else if str_eq(name, "localnet") {
+            Some(Cluster::Localnet)
+        } else {
+            None
+        }
+    }
+}
+
+/// Cluster the program is built for.
+pub const CLUSTER: Cluster = select_cluster();
+
+const fn select_cluster() -> Cluster {
+    if cfg!(feature = "localnet") {
+        return Cluster::Localnet;
+    }
+    if cfg!(feature = "devnet") {
+        return Cluster::Devnet;
+    }
+    match option_env!("PINSTEEL_CLUSTER") {
+        Some(name) => match Cluster::from_name(name) {
+            Some(cluster) => cluster,
+            None => panic!("PINSTEEL_CLUSTER must be mainnet, devnet or localnet"),
+        },
+        None => Cluster::Mainnet,
+    }
+}
+
+/// Name of [`CLUSTER`], for logging.
+#[inline(always)]
+pub const fn cluster_name() -> &'static str {
+    CLUSTER.name()
+}
+
+/// A value with one variant per cluster, resolved against [`CLUSTER`].
+#[derive(Clone, Copy, Debug)]
+pub struct ClusterConst<T> {
+    pub mainnet: T,
+    pub devnet: T,
+    pub localnet: T,
+}
+
+impl<T: Copy> ClusterConst<T> {
+    pub const fn new(mainnet: T, devnet: T, localnet: T) -> Self {
+        Self {
+            mainnet,
+            devnet,
+            localnet,
+        }
+    }
+
+    /// Value on the selected cluster.
+    #[inline(always)]
+    pub const fn get(&self) -> T {
+        self.on(CLUSTER)
+    }
+
+    #[inline(always)]
+    pub const fn on(&self, cluster: Cluster) -> T {
+        match cluster {
+            Cluster::Mainnet => self.mainnet,
+            Cluster::Devnet => self.devnet,
+            Cluster::Localnet => self.localnet,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_const() {
+        const VALUE: ClusterConst<u8> = ClusterConst::new(1, 2, 3);
+        assert_eq!(VALUE.on(Cluster::Devnet), 2);
+        assert_eq!(VALUE.get(), VALUE.on(CLUSTER));
+        assert_eq!(Cluster::from_name("localnet"), Some(Cluster::Localnet));
+        assert_eq!(Cluster::from_name("testnet"), None);
+        assert_eq!(cluster_name(), CLUSTER.name());
+    }
+}
@@ -0,0 +1,395 @@
+//! Client code generation from [`AccountLayout`] metadata, keeping web clients
+//! in lockstep with the Rust layouts.
+//!
+//! Typically run from a test or a small host binary:
+//!
+//! ```ignore
+//! let mut ts = TsCodegen::new();
+//! ts.account::<Counter>().instruction::<Increment>().event::<Incremented>();
+//! ts.write("clients/ts/src/generated.ts")?;
+//! ```
+
+use std::{fmt::Write as _, format, string::String};
+
+use crate::{AccountLayout, Discriminator, FieldKind, FieldLayout, InstructionAccounts};
+
+/// Generates TypeScript interfaces with `decode`/`encode` functions over
+/// `Uint8Array`s, using `PublicKey` from `@solana/web3.js` for pubkeys.
+pub struct TsCodegen {
+    out: String,
+}
+
+impl Default for TsCodegen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TsCodegen {
+    pub fn new() -> Self {
+        Self {
+            out: String::from(
+                "// Generated by pinsteel, do not edit.\n\nimport { PublicKey } from \"@solana/web3.js\";\n",
+            ),
+        }
+    }
+
+    /// Account type, encoded as its raw bytes.
+    pub fn account<T: AccountLayout>(&mut self) -> &mut Self {
+        self.add::<T>(0, None)
+    }
+
+    /// Event type, encoded as its raw bytes.
+    pub fn event<T: AccountLayout>(&mut self) -> &mut Self {
+        self.add::<T>(0, None)
+    }
+
+    /// Instruction type, encoded with its discriminator byte first like `instruction!`.
+    pub fn instruction<T: AccountLayout + Discriminator>(&mut self) -> &mut Self {
+        self.add::<T>(1, Some(T::discriminator()))
+    }
+
+    /// Expected accounts of an instruction, as a `NAME_ACCOUNTS` array in program order.
+    pub fn instruction_accounts<T: AccountLayout + InstructionAccounts>(&mut self) -> &mut Self {
+        let out = &mut self.out;
+        let _ = writeln!(
+            out,
+            "\nexport const {}_ACCOUNTS = [",
+            T::NAME.to_uppercase()
+        );
+        for account in T::ACCOUNTS {
+            let _ = writeln!(
+                out,
+                "  {{ name: \"{}\", writable: {}, signer: {} }},",
+                account.name, account.writable, account.signer
+            );
+        }
+        let _ = writeln!(out, "] as const;");
+        self
+    }
+
+    pub fn source(&self) -> &str {
+        &self.out
+    }
+
+    pub fn write(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, &self.out)
+    }
+
+    fn add<T: AccountLayout>(&mut self, header: usize, discriminator: Option<u8>) -> &mut Self {
+        let name = T::NAME;
+        let fields = T::fields();
+        let len = header + core::mem::size_of::<T>();
+        let out = &mut self.out;
+
+        let _ = writeln!(out, "\nexport const {}_LEN = {};", name.to_uppercase(), len);
+        if let Some(discriminator) = discriminator {
+            let _ = writeln!(
+                out,
+                "export const {}_DISCRIMINATOR = {};",
+                name.to_uppercase(),
+                discriminator
+            );
+        }
+
+        let _ = writeln!(out, "\nexport interface {} {{", name);
+        for field in &fields {
+            let _ = writeln!(out, "  {}: {};", field.name, ts_type(field.kind));
+        }
+        let _ = writeln!(out, "}}");
+
+        let _ = writeln!(
+            out,
+            "\nexport function decode{name}(data: Uint8Array): {name} {{\n  \
+             if (data.length !== {len}) throw new Error(\"{name}: expected {len} bytes\");\n  \
+             const view = new DataView(data.buffer, data.byteOffset, data.byteLength);\n  \
+             return {{"
+        );
+        for field in &fields {
+            let _ = writeln!(
+                out,
+                "    {}: {},",
+                field.name,
+                ts_read(field, header + field.offset)
+            );
+        }
+        let _ = writeln!(out, "  }};\n}}");
+
+        let _ = writeln!(
+            out,
+            "\nexport function encode{name}(value: {name}): Uint8Array {{\n  \
+             const data = new Uint8Array({len});\n  \
+             const view = new DataView(data.buffer);"
+        );
+        if let Some(discriminator) = discriminator {
+            let _ = writeln!(out, "  view.setUint8(0, {});", discriminator);
+        }
+        for field in &fields {
+            let _ = writeln!(out, "  {};", ts_write(field, header + field.offset));
+        }
+        let _ = writeln!(out, "  return data;\n}}");
+        self
+    }
+}
+
+/// Generates Python layout descriptions: a `struct` format string, the total
+/// size and `(name, offset, size, format)` field tuples per type.
+///
+/// ```text
+/// COUNTER_FORMAT = "<B32sQ"
+/// COUNTER_SIZE = 41
+/// COUNTER_FIELDS = [
+///     ("discriminator", 0, 1, "B"),
+///     ...
+/// ]
+/// ```
+///
+/// All formats are little-endian; 128 bit integers are left as `16s` bytes,
+/// which `struct` can't decode.
+pub struct PyLayoutCodegen {
+    out: String,
+}
+
+impl Default for PyLayoutCodegen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PyLayoutCodegen {
+    pub fn new() -> Self {
+        Self {
+            out: String::from("# Generated by pinsteel, do not edit.\n"),
+        }
+    }
+
+    pub fn account<T: AccountLayout>(&mut self) -> &mut Self {
+        self.add::<T>(0)
+    }
+
+    pub fn event<T: AccountLayout>(&mut self) -> &mut Self {
+        self.add::<T>(0)
+    }
+
+    /// Instruction type, laid out after its discriminator byte like `instruction!`.
+    pub fn instruction<T: AccountLayout + Discriminator>(&mut self) -> &mut Self {
+        self.add::<T>(1)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.out
+    }
+
+    pub fn write(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, &self.out)
+    }
+
+    fn add<T: AccountLayout>(&mut self, header: usize) -> &mut Self {
+        let prefix = T::NAME.to_uppercase();
+        let fields = T::fields();
+        let size = header + core::mem::size_of::<T>();
+        let out = &mut self.out;
+
+        let _ = writeln!(
+            out,
+            "\n{prefix}_FORMAT = \"{}\"",
+            struct_format(&fields, header, size)
+        );
+        let _ = writeln!(out, "{prefix}_SIZE = {size}");
+        let _ = writeln!(out, "{prefix}_FIELDS = [");
+        if header > 0 {
+            let _ = writeln!(out, "    (\"discriminator\", 0, 1, \"B\"),");
+        }
+        for field in &fields {
+            let _ = writeln!(
+                out,
+                "    (\"{}\", {}, {}, \"{}\"),",
+                field.name,
+                header + field.offset,
+                field.size,
+                py_format(field.kind)
+            );
+        }
+        let _ = writeln!(out, "]");
+        self
+    }
+}
+
+/// Python `struct` format of `fields`, padding gaps with `x`. A `header` byte,
+/// the instruction discriminator, is described as `B`.
+pub fn struct_format(fields: &[FieldLayout], header: usize, size: usize) -> String {
+    let mut format = String::from("<");
+    if header > 0 {
+        format.push('B');
+    }
+    let mut offset = header;
+    let mut sorted: std::vec::Vec<&FieldLayout> = fields.iter().collect();
+    sorted.sort_by_key(|field| field.offset);
+    for field in sorted {
+        let start = header + field.offset;
+        if start > offset {
+            let _ = write!(format, "{}x", start - offset);
+        }
+        format.push_str(&py_format(field.kind));
+        offset = start + field.size;
+    }
+    if size > offset {
+        let _ = write!(format, "{}x", size - offset);
+    }
+    format
+}
+
+fn py_format(kind: FieldKind) -> String {
+    match kind {
+        FieldKind::U8 => "B".into(),
+        FieldKind::U16 => "H".into(),
+        FieldKind::U32 => "I".into(),
+        FieldKind::U64 => "Q".into(),
+        FieldKind::I8 => "b".into(),
+        FieldKind::I16 => "h".into(),
+        FieldKind::I32 => "i".into(),
+        FieldKind::I64 => "q".into(),
+        FieldKind::Bool => "?".into(),
+        FieldKind::U128 | FieldKind::I128 => "16s".into(),
+        FieldKind::Pubkey => "32s".into(),
+        FieldKind::Bytes(len) => format!("{len}s"),
+    }
+}
+
+fn ts_type(kind: FieldKind) -> &'static str {
+    match kind {
+        FieldKind::U8 | FieldKind::U16 | FieldKind::U32 => "number",
+        FieldKind::I8 | FieldKind::I16 | FieldKind::I32 => "number",
+        FieldKind::U64 | FieldKind::U128 | FieldKind::I64 | FieldKind::I128 => "bigint",
+        FieldKind::Bool => "boolean",
+        FieldKind::Pubkey => "PublicKey",
+        FieldKind::Bytes(_) => "Uint8Array",
+    }
+}
+
+fn ts_read(field: &FieldLayout, offset: usize) -> String {
+    let end = offset + field.size;
+    match field.kind {
+        FieldKind::U8 => format!("view.getUint8({offset})"),
+        FieldKind::U16 => format!("view.getUint16({offset}, true)"),
+        FieldKind::U32 => format!("view.getUint32({offset}, true)"),
+        FieldKind::U64 => format!("view.getBigUint64({offset}, true)"),
+        FieldKind::I8 => format!("view.getInt8({offset})"),
+        FieldKind::I16 => format!("view.getInt16({offset}, true)"),
+        FieldKind::I32 => format!("view.getInt32({offset}, true)"),
+        FieldKind::I64 => format!("view.getBigInt64({offset}, true)"),
+        FieldKind::U128 => format!(
+            "(view.getBigUint64({}, true) << 64n) | view.getBigUint64({offset}, true)",
+            offset + 8
+        ),
+        FieldKind::I128 => format!(
+            "(view.getBigInt64({}, true) << 64n) | view.getBigUint64({offset}, true)",
+            offset + 8
+        ),
+        FieldKind::Bool => format!("view.getUint8({offset}) !== 0"),
+        FieldKind::Pubkey => format!("new PublicKey(data.slice({offset}, {end}))"),
+        FieldKind::Bytes(_) => format!("data.slice({offset}, {end})"),
+    }
+}
+
+fn ts_write(field: &FieldLayout, offset: usize) -> String {
+    let name = field.name;
+    match field.kind {
+        FieldKind::U8 => format!("view.setUint8({offset}, value.{name})"),
+        FieldKind::U16 => format!("view.setUint16({offset}, value.{name}, true)"),
+        FieldKind::U32 => format!("view.setUint32({offset}, value.{name}, true)"),
+        FieldKind::U64 => format!("view.setBigUint64({offset}, value.{name}, true)"),
+        FieldKind::I8 => format!("view.setInt8({offset}, value.{name})"),
+        FieldKind::I16 => format!("view.setInt16({offset}, value.{name}, true)"),
+        FieldKind::I32 => format!("view.setInt32({offset}, value.{name}, true)"),
+        FieldKind::I64 => format!("view.setBigInt64({offset}, value.{name}, true)"),
+        FieldKind::U128 | FieldKind::I128 => format!(
+            "view.setBigUint64({offset}, BigInt.asUintN(64, value.{name}), true); \
+             view.setBigUint64({}, BigInt.asUintN(64, value.{name} >> 64n), true)",
+            offset + 8
+        ),
+        FieldKind::Bool => format!("view.setUint8({offset}, value.{name} ? 1 : 0)"),
+        FieldKind::Pubkey => format!("data.set(value.{name}.toBytes(), {offset})"),
+        FieldKind::Bytes(_) => format!("data.set(value.{name}, {offset})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PodU64LE;
+
+    #[repr(C)]
+    struct Deposit {
+        owner: [u8; 32],
+        amount: PodU64LE,
+        locked: bool,
+    }
+
+    crate::account_layout!(Deposit {
+        owner as Pubkey,
+        amount,
+        locked
+    });
+
+    impl Discriminator for Deposit {
+        fn discriminator() -> u8 {
+            3
+        }
+    }
+
+    impl InstructionAccounts for Deposit {
+        const ACCOUNTS: &'static [crate::AccountSpec] = &[
+            crate::AccountSpec::writable_signer("owner"),
+            crate::AccountSpec::readonly("mint"),
+        ];
+    }
+
+    #[test]
+    fn test_ts_codegen() {
+        let mut ts = TsCodegen::new();
+        ts.instruction::<Deposit>();
+        let source = ts.source();
+
+        assert!(source.contains("export const DEPOSIT_LEN = 42;"));
+        assert!(source.contains("export const DEPOSIT_DISCRIMINATOR = 3;"));
+        assert!(source.contains("  owner: PublicKey;\n  amount: bigint;\n  locked: boolean;\n"));
+        assert!(source.contains("amount: view.getBigUint64(33, true),"));
+        assert!(source.contains("  view.setUint8(0, 3);\n"));
+        assert!(source.contains("data.set(value.owner.toBytes(), 1);"));
+        assert!(source.contains("view.setUint8(41, value.locked ? 1 : 0);"));
+
+        ts.instruction_accounts::<Deposit>();
+        assert!(ts.source().ends_with(
+            "export const DEPOSIT_ACCOUNTS = [\n  \
+             { name: \"owner\", writable: true, signer: true },\n  \
+             { name: \"mint\", writable: false, signer: false },\n] as const;\n"
+        ));
+    }
+
+    #[repr(C)]
+    struct Position {
+        kind: u8,
+        value: u64,
+        hash: [u8; 32],
+    }
+
+    crate::account_layout!(Position { kind, value, hash });
+
+    #[test]
+    fn test_py_layout_codegen() {
+        let mut py = PyLayoutCodegen::new();
+        py.instruction::<Deposit>().account::<Position>();
+        let source = py.source();
+
+        assert!(source.contains("DEPOSIT_FORMAT = \"<B32sQ?\"\nDEPOSIT_SIZE = 42\n"));
+        assert!(source
+            .contains("    (\"discriminator\", 0, 1, \"B\"),\n    (\"owner\", 1, 32, \"32s\"),"));
+        assert!(source.contains("POSITION_FORMAT = \"<B7xQ32s\"\nPOSITION_SIZE = 48\n"));
+        assert!(source.contains("(\"value\", 8, 8, \"Q\"),"));
+
+        let mut ts = TsCodegen::new();
+        ts.account::<Position>();
+        assert!(ts.source().contains("  hash: Uint8Array;\n"));
+    }
+}
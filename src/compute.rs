@@ -0,0 +1,115 @@
+//! Compute budget helpers for batch and crank instructions.
+//!
+//! ```ignore
+//! let mut state = crank.as_account_mut::<Crank>(&crate::ID)?;
+//! let done = for_each_until_cu_limit(rest, &mut state.cursor, 20_000, |account| {
+//!     settle(account)
+//! })?;
+//! if done {
+//!     state.cursor.set(0);
+//! }
+//! ```
+
+use pinocchio::{program_error::ProgramError, ProgramResult};
+
+use crate::PodU64LE;
+
+/// Compute units left in the current transaction, `u64::MAX` off-chain.
+#[inline(always)]
+pub fn remaining_compute_units() -> u64 {
+    #[cfg(target_os = "solana")]
+    // SAFETY: the syscall has no arguments
+    return unsafe { pinocchio::syscalls::sol_remaining_compute_units() };
+
+    #[cfg(not(target_os = "solana"))]
+    u64::MAX
+}
+
+/// Calls `f` on `items` from `cursor` onwards, stopping before an item once
+/// fewer than `min_remaining` compute units are left. `cursor` is left at the
+/// first unprocessed item, so the next crank resumes from there.
+///
+/// Returns `true` once every item was processed. An error from `f` is returned
+/// as-is, leaving `cursor` at the failing item.
+#[inline(always)]
+pub fn for_each_until_cu_limit<T, F>(
+    items: &[T],
+    cursor: &mut PodU64LE,
+    min_remaining: u64,
+    f: F,
+) -> Result<bool, ProgramError>
+where
+    F: FnMut(&T) -> ProgramResult,
+{
+    for_each_until(items, cursor, min_remaining, remaining_compute_units, f)
+}
+
+fn for_each_until<T, F>(
+    items: &[T],
+    cursor: &mut PodU64LE,
+    min_remaining: u64,
+    mut remaining: impl FnMut() -> u64,
+    mut f: F,
+) -> Result<bool, ProgramError>
+where
+    F: FnMut(&T) -> ProgramResult,
+{
+    let start = usize::try_from(cursor.get()).map_err(|_| ProgramError::InvalidAccountData)?;
+    for (index, item) in items.iter().enumerate().skip(start) {
+        if remaining() < min_remaining {
+            return Ok(false);
+        }
+        f(item)?;
+        cursor.set(index as u64 + 1);
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_for_each_until() {
+        let items = [1u64, 2, 3, 4, 5];
+        let mut cursor = PodU64LE::new(0);
+        let mut sum = 0;
+
+        // Budget runs out after two items
+        let budget = Cell::new(30_000u64);
+        let done = for_each_until(
+            &items,
+            &mut cursor,
+            10_000,
+            || budget.get(),
+            |item| {
+                sum += item;
+                budget.set(budget.get() - 10_000);
+                Ok(())
+            },
+        );
+        assert_eq!(done, Ok(false));
+        assert_eq!((cursor.get(), sum), (2, 3));
+
+        // Next crank resumes from the cursor
+        let done = for_each_until_cu_limit(&items, &mut cursor, 10_000, |item| {
+            sum += item;
+            Ok(())
+        });
+        assert_eq!(done, Ok(true));
+        assert_eq!((cursor.get(), sum), (5, 15));
+
+        // Errors keep the cursor at the failing item
+        cursor.set(1);
+        let done = for_each_until_cu_limit(&items, &mut cursor, 0, |item| {
+            if *item == 3 {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Ok(())
+        });
+        assert_eq!(done, Err(ProgramError::InvalidArgument));
+        assert_eq!(cursor.get(), 2);
+    }
+}
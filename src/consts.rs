@@ -3,9 +3,67 @@ use pinocchio_pubkey::pubkey;
 
 pub const SYSVAR_PROGRAM_ID: Pubkey = pubkey!("Sysvar1111111111111111111111111111111111111");
 
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+pub const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+pub const BPF_LOADER_DEPRECATED_ID: Pubkey = pubkey!("BPFLoader1111111111111111111111111111111111");
+pub const BPF_LOADER_ID: Pubkey = pubkey!("BPFLoader2111111111111111111111111111111111");
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+pub const CLOCK_SYSVAR_ID: Pubkey = pubkey!("SysvarC1ock11111111111111111111111111111111");
+pub const RENT_SYSVAR_ID: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+pub const INSTRUCTIONS_SYSVAR_ID: Pubkey = pubkey!("Sysvar1nstructions1111111111111111111111111");
+pub const SLOT_HASHES_SYSVAR_ID: Pubkey = pubkey!("SysvarS1otHashes111111111111111111111111111");
+
+/// SPL Token and Token-2022 programs.
+pub const TOKEN_PROGRAM_IDS: &[Pubkey] = &[TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID];
+
+/// Metaplex Token Metadata program.
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// Discriminators reserved by pinsteel for its own instructions, events and
+/// account types. Programs should not use them for their own, each value below
+/// is assigned exactly once across all three kinds.
+pub const RESERVED_DISCRIMINATORS: core::ops::RangeInclusive<u8> = 250..=255;
+
 /// Fixed discriminator for the `EmitEvent` instruction.
 pub const EMIT_EVENT_DISCRIMINATOR: u8 = 255;
+/// Event discriminator of [`AccountClosed`](crate::AccountClosed).
+pub const ACCOUNT_CLOSED_EVENT: u8 = 254;
+/// Event discriminator of [`CircuitBreakerTripped`](crate::CircuitBreakerTripped).
+pub const CIRCUIT_BREAKER_TRIPPED_EVENT: u8 = 253;
+/// Event discriminator of [`InstructionExecuted`](crate::InstructionExecuted).
+pub const INSTRUCTION_EXECUTED_EVENT: u8 = 252;
+/// Account discriminator of accounts retired with [`tombstone_account`](crate::tombstone_account),
+/// waiting to be swept.
+pub const TOMBSTONE_DISCRIMINATOR: u8 = 251;
+/// First byte of framed `EmitEvent` data, see [`encode_event_frame`](crate::encode_event_frame).
+pub const EVENT_FRAME_MARKER: u8 = 250;
+
+/// Seed of the event authority PDA signing `EmitEvent` self-CPIs.
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
 
 // Actual limit is 10KB, but `sol_return_data` buffer is 1024 bytes long
 // and 1 byte is used for the discriminator
 pub const MAX_CPI_INSTRUCTION_DATA_LEN: usize = 1024 - 1;
+
+/// Max instruction data length of a CPI.
+pub const MAX_CPI_DATA_LEN: usize = 10 * 1024;
+
+/// Max number of bytes an account may grow by within one instruction.
+pub const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+
+/// Max number of account metas of a CPI.
+pub const MAX_CPI_ACCOUNTS: usize = 64;
+
+/// Max invoke stack height, the top-level instruction is at height 1.
+pub const MAX_INVOKE_STACK_HEIGHT: u64 = 5;
@@ -0,0 +1,65 @@
+//! Preflight checks for cross-program invocations.
+
+use pinocchio::{instruction::Instruction, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::{trace, MAX_CPI_ACCOUNTS, MAX_CPI_DATA_LEN, MAX_INVOKE_STACK_HEIGHT};
+
+pub const ERROR_CPI_DATA_TOO_LONG: u32 = 10;
+pub const ERROR_CPI_TOO_MANY_ACCOUNTS: u32 = 11;
+pub const ERROR_CPI_TOO_DEEP: u32 = 12;
+
+#[cfg(target_os = "solana")]
+extern "C" {
+    fn sol_get_stack_height() -> u64;
+}
+
+/// Current invoke stack height, `1` for a top-level instruction.
+#[inline(always)]
+pub fn get_stack_height() -> u64 {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        sol_get_stack_height()
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    1
+}
+
+/// Validates an instruction against the runtime CPI limits before invoking it,
+/// so that failures carry a precise error instead of an opaque runtime abort.
+///
+/// Logs a warning if the callee would run at the maximum depth, where it can
+/// no longer make CPIs of its own.
+#[track_caller]
+pub fn cpi_preflight(instruction: &Instruction) -> ProgramResult {
+    if instruction.data.len() > MAX_CPI_DATA_LEN {
+        return Err(trace(
+            "CPI instruction data is too long",
+            ProgramError::Custom(ERROR_CPI_DATA_TOO_LONG),
+        ));
+    }
+
+    if instruction.accounts.len() > MAX_CPI_ACCOUNTS {
+        return Err(trace(
+            "CPI has too many accounts",
+            ProgramError::Custom(ERROR_CPI_TOO_MANY_ACCOUNTS),
+        ));
+    }
+
+    let height = get_stack_height();
+    if height >= MAX_INVOKE_STACK_HEIGHT {
+        return Err(trace(
+            "CPI exceeds max invoke depth",
+            ProgramError::Custom(ERROR_CPI_TOO_DEEP),
+        ));
+    }
+    if height + 1 == MAX_INVOKE_STACK_HEIGHT {
+        log!(
+            "CPI callee runs at max invoke depth {}",
+            MAX_INVOKE_STACK_HEIGHT
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+//! Cadence tracking for permissionless crank instructions.
+
+use pinocchio::{program_error::ProgramError, sysvars::clock::Clock, ProgramResult};
+
+use crate::trace;
+
+pub const ERROR_CRANK_NOT_DUE: u32 = 3;
+
+/// Run schedule stored inside a program account.
+///
+/// A crank is due once `interval` seconds have passed since the last run and
+/// the slot has advanced, so it can never run twice within the same slot.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    /// Minimum number of seconds between two runs.
+    pub interval: i64,
+    /// Slot of the last run, `0` if never run.
+    pub last_run_slot: u64,
+    /// Unix timestamp of the last run, `0` if never run.
+    pub last_run_timestamp: i64,
+}
+
+impl Schedule {
+    pub const fn new(interval: i64) -> Self {
+        Self {
+            interval,
+            last_run_slot: 0,
+            last_run_timestamp: 0,
+        }
+    }
+
+    /// Returns `true` if the crank may run at `clock`.
+    #[inline(always)]
+    pub fn due(&self, clock: &Clock) -> bool {
+        if self.last_run_slot == 0 {
+            return true;
+        }
+        clock.slot > self.last_run_slot
+            && clock.unix_timestamp >= self.last_run_timestamp.saturating_add(self.interval)
+    }
+
+    /// Unix timestamp at which the crank becomes due.
+    #[inline(always)]
+    pub fn next_run_timestamp(&self) -> i64 {
+        self.last_run_timestamp.saturating_add(self.interval)
+    }
+
+    /// Fails with [`ERROR_CRANK_NOT_DUE`] if the crank may not run at `clock`.
+    #[track_caller]
+    #[inline(always)]
+    pub fn is_due(&self, clock: &Clock) -> ProgramResult {
+        if !self.due(clock) {
+            return Err(trace(
+                "Crank is not due",
+                ProgramError::Custom(ERROR_CRANK_NOT_DUE),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the crank is due and records a run at `clock`.
+    #[track_caller]
+    pub fn mark_run(&mut self, clock: &Clock) -> ProgramResult {
+        self.is_due(clock)?;
+        self.last_run_slot = clock.slot;
+        self.last_run_timestamp = clock.unix_timestamp;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp,
+        }
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut schedule = Schedule::new(60);
+        assert!(schedule.due(&clock(10, 1_000)));
+
+        schedule.mark_run(&clock(10, 1_000)).unwrap();
+        assert_eq!(schedule.next_run_timestamp(), 1_060);
+
+        // Interval not elapsed
+        assert!(!schedule.due(&clock(20, 1_059)));
+        assert_eq!(
+            schedule.mark_run(&clock(20, 1_059)),
+            Err(ProgramError::Custom(ERROR_CRANK_NOT_DUE))
+        );
+
+        // Same slot
+        assert!(!schedule.due(&clock(10, 1_060)));
+
+        assert!(schedule.due(&clock(20, 1_060)));
+        schedule.mark_run(&clock(20, 1_060)).unwrap();
+        assert_eq!(schedule.last_run_slot, 20);
+    }
+}
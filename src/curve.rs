@@ -0,0 +1,254 @@
+//! Curve25519 helpers backed by the `sol_curve_*` syscalls.
+
+use pinocchio::pubkey::Pubkey;
+
+/// Curve id of the edwards25519 curve.
+pub const CURVE25519_EDWARDS: u64 = 0;
+/// Curve id of the ristretto255 group.
+pub const CURVE25519_RISTRETTO: u64 = 1;
+
+/// Group operations of `sol_curve_group_op`.
+#[cfg(feature = "curve25519")]
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveGroupOp {
+    Add = 0,
+    Sub = 1,
+    /// Scalar (left) times point (right).
+    Mul = 2,
+}
+
+/// Curve25519 point or scalar in its 32 byte encoding.
+pub type CurveBytes = [u8; 32];
+
+#[cfg(target_os = "solana")]
+extern "C" {
+    fn sol_curve_validate_point(curve_id: u64, point: *const u8, result: *mut u8) -> u64;
+    #[cfg(feature = "curve25519")]
+    fn sol_curve_group_op(
+        curve_id: u64,
+        group_op: u64,
+        left: *const u8,
+        right: *const u8,
+        result: *mut u8,
+    ) -> u64;
+    #[cfg(feature = "curve25519")]
+    fn sol_curve_multiscalar_mul(
+        curve_id: u64,
+        scalars: *const u8,
+        points: *const u8,
+        points_len: u64,
+        result: *mut u8,
+    ) -> u64;
+}
+
+/// Returns `true` if `pubkey` is a valid ed25519 point, i.e. could be a wallet key.
+#[cfg(target_os = "solana")]
+#[inline(always)]
+pub fn is_on_curve(pubkey: &Pubkey) -> bool {
+    let mut result = 0u8;
+    // Returns 0 if the point is valid
+    unsafe { sol_curve_validate_point(CURVE25519_EDWARDS, pubkey.as_ptr(), &mut result) == 0 }
+}
+
+/// Returns `true` if `pubkey` is a valid ed25519 point, i.e. could be a wallet key.
+#[cfg(all(feature = "curve25519", not(target_os = "solana")))]
+pub fn is_on_curve(pubkey: &Pubkey) -> bool {
+    curve25519_dalek::edwards::CompressedEdwardsY(*pubkey)
+        .decompress()
+        .is_some()
+}
+
+/// Returns `true` if `pubkey` is not an ed25519 point, as every PDA is.
+#[cfg(any(target_os = "solana", feature = "curve25519"))]
+#[inline(always)]
+pub fn is_off_curve(pubkey: &Pubkey) -> bool {
+    !is_on_curve(pubkey)
+}
+
+/// Returns `true` if `point` is a valid encoding on curve `curve_id`.
+#[cfg(feature = "curve25519")]
+pub fn validate_point(curve_id: u64, point: &CurveBytes) -> bool {
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = 0u8;
+        unsafe { sol_curve_validate_point(curve_id, point.as_ptr(), &mut result) == 0 }
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    match curve_id {
+        CURVE25519_EDWARDS => host::edwards(point).is_some(),
+        CURVE25519_RISTRETTO => host::ristretto(point).is_some(),
+        _ => false,
+    }
+}
+
+/// Applies `op` to `left` and `right`, `None` if an input is invalid.
+#[cfg(feature = "curve25519")]
+pub fn group_op(
+    curve_id: u64,
+    op: CurveGroupOp,
+    left: &CurveBytes,
+    right: &CurveBytes,
+) -> Option<CurveBytes> {
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = [0u8; 32];
+        let status = unsafe {
+            sol_curve_group_op(
+                curve_id,
+                op as u64,
+                left.as_ptr(),
+                right.as_ptr(),
+                result.as_mut_ptr(),
+            )
+        };
+        (status == 0).then_some(result)
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    match curve_id {
+        CURVE25519_EDWARDS => host::edwards_group_op(op, left, right),
+        CURVE25519_RISTRETTO => host::ristretto_group_op(op, left, right),
+        _ => None,
+    }
+}
+
+/// Computes `sum(scalars[i] * points[i])`, `None` if an input is invalid or the
+/// lengths differ.
+#[cfg(feature = "curve25519")]
+pub fn multiscalar_mul(
+    curve_id: u64,
+    scalars: &[CurveBytes],
+    points: &[CurveBytes],
+) -> Option<CurveBytes> {
+    if scalars.len() != points.len() {
+        return None;
+    }
+
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = [0u8; 32];
+        let status = unsafe {
+            sol_curve_multiscalar_mul(
+                curve_id,
+                scalars.as_ptr() as *const u8,
+                points.as_ptr() as *const u8,
+                points.len() as u64,
+                result.as_mut_ptr(),
+            )
+        };
+        (status == 0).then_some(result)
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        // Syscall semantics: a sum of `scalar * point` products
+        let mut products = scalars.iter().zip(points);
+        let (scalar, point) = products.next()?;
+        let first = group_op(curve_id, CurveGroupOp::Mul, scalar, point)?;
+        products.try_fold(first, |acc, (scalar, point)| {
+            let product = group_op(curve_id, CurveGroupOp::Mul, scalar, point)?;
+            group_op(curve_id, CurveGroupOp::Add, &acc, &product)
+        })
+    }
+}
+
+#[cfg(all(feature = "curve25519", not(target_os = "solana")))]
+mod host {
+    use curve25519_dalek::{
+        edwards::{CompressedEdwardsY, EdwardsPoint},
+        ristretto::{CompressedRistretto, RistrettoPoint},
+        scalar::Scalar,
+    };
+
+    use super::{CurveBytes, CurveGroupOp};
+
+    pub fn edwards(point: &CurveBytes) -> Option<EdwardsPoint> {
+        CompressedEdwardsY(*point).decompress()
+    }
+
+    pub fn ristretto(point: &CurveBytes) -> Option<RistrettoPoint> {
+        CompressedRistretto(*point).decompress()
+    }
+
+    fn scalar(bytes: &CurveBytes) -> Option<Scalar> {
+        Scalar::from_canonical_bytes(*bytes).into()
+    }
+
+    pub fn edwards_group_op(
+        op: CurveGroupOp,
+        left: &CurveBytes,
+        right: &CurveBytes,
+    ) -> Option<CurveBytes> {
+        let result = match op {
+            CurveGroupOp::Add => edwards(left)? + edwards(right)?,
+            CurveGroupOp::Sub => edwards(left)? - edwards(right)?,
+            CurveGroupOp::Mul => scalar(left)? * edwards(right)?,
+        };
+        Some(result.compress().to_bytes())
+    }
+
+    pub fn ristretto_group_op(
+        op: CurveGroupOp,
+        left: &CurveBytes,
+        right: &CurveBytes,
+    ) -> Option<CurveBytes> {
+        let result = match op {
+            CurveGroupOp::Add => ristretto(left)? + ristretto(right)?,
+            CurveGroupOp::Sub => ristretto(left)? - ristretto(right)?,
+            CurveGroupOp::Mul => scalar(left)? * ristretto(right)?,
+        };
+        Some(result.compress().to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "curve25519")]
+    #[test]
+    fn test_is_on_curve() {
+        // Compressed ed25519 base point
+        let mut base = [0x66; 32];
+        base[0] = 0x58;
+        assert!(is_on_curve(&base));
+
+        // Roughly half of all 32 byte strings are not valid points
+        assert!((0..=u8::MAX).any(|i| is_off_curve(&[i; 32])));
+    }
+
+    #[cfg(feature = "curve25519")]
+    #[test]
+    fn test_group_ops() {
+        let mut base = [0x66; 32];
+        base[0] = 0x58;
+        let mut two = [0; 32];
+        two[0] = 2;
+        let mut three = [0; 32];
+        three[0] = 3;
+
+        assert!(validate_point(CURVE25519_EDWARDS, &base));
+
+        let doubled = group_op(CURVE25519_EDWARDS, CurveGroupOp::Add, &base, &base).unwrap();
+        assert_eq!(
+            group_op(CURVE25519_EDWARDS, CurveGroupOp::Mul, &two, &base),
+            Some(doubled)
+        );
+        assert_eq!(
+            group_op(CURVE25519_EDWARDS, CurveGroupOp::Sub, &doubled, &base),
+            Some(base)
+        );
+
+        // 2 * B + 1 * 2B == 4 * B
+        let mut one = [0; 32];
+        one[0] = 1;
+        let tripled = group_op(CURVE25519_EDWARDS, CurveGroupOp::Mul, &three, &base).unwrap();
+        assert_eq!(
+            multiscalar_mul(CURVE25519_EDWARDS, &[two, one], &[base, doubled]),
+            group_op(CURVE25519_EDWARDS, CurveGroupOp::Add, &tripled, &base)
+        );
+        assert_eq!(multiscalar_mul(CURVE25519_EDWARDS, &[two], &[]), None);
+    }
+}
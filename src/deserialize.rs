@@ -1,18 +1,150 @@
-use pinocchio::program_error::ProgramError;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey, ProgramResult};
 
 // Tag traits to distinguish between data types
-pub trait Account {}
-pub trait Instruction {}
+pub trait Account {
+    /// Set for types read from data that may not be aligned, e.g. sliced from
+    /// larger buffers: [`AccountDeserialize::try_read`] then copies them out
+    /// instead of failing on misaligned data.
+    const UNALIGNED: bool = false;
+}
+pub trait Instruction {
+    /// Same as [`Account::UNALIGNED`], for [`InstructionDeserialize::try_read`].
+    const UNALIGNED: bool = false;
+
+    /// Validates instruction arguments, see the `validate` arm of `instruction!`.
+    fn validate(&self) -> ProgramResult {
+        Ok(())
+    }
+}
 
 use crate::trace;
 
 pub trait Discriminator {
     fn discriminator() -> u8;
+
+    /// Optional second discriminator byte, namespacing the type per program or domain
+    /// so that accounts of different programs can't be confused.
+    #[inline(always)]
+    fn namespace() -> Option<u8> {
+        None
+    }
+
+    /// Full discriminator of types following a foreign scheme, e.g. 4 or 8 bytes.
+    /// Replaces the `discriminator`/`namespace` bytes when matching if set.
+    const DISCRIMINATOR_BYTES: Option<&'static [u8]> = None;
+
+    /// Writes the discriminator (and namespace, if any) at the start of freshly
+    /// allocated account `data`, which must be at least `size_of::<Self>()` long.
+    #[inline(always)]
+    fn write_discriminator(data: &mut [u8]) {
+        if let Some(bytes) = Self::DISCRIMINATOR_BYTES {
+            data[..bytes.len()].copy_from_slice(bytes);
+            return;
+        }
+        data[0] = Self::discriminator();
+        if let Some(namespace) = Self::namespace() {
+            data[1] = namespace;
+        }
+    }
+
+    /// Returns `true` if `data` starts with the discriminator (and namespace, if any).
+    #[inline(always)]
+    fn matches_discriminator(data: &[u8]) -> bool {
+        if let Some(bytes) = Self::DISCRIMINATOR_BYTES {
+            return data.starts_with(bytes);
+        }
+        match Self::namespace() {
+            None => data.first() == Some(&Self::discriminator()),
+            Some(namespace) => {
+                data.len() >= 2 && data[0] == Self::discriminator() && data[1] == namespace
+            }
+        }
+    }
+}
+
+/// Account types that store the bump of their own PDA.
+pub trait SavedBump {
+    fn bump(&self) -> u8;
+}
+
+/// Initialization state of an account, read from its discriminator: an account
+/// is initialized once it starts with its full discriminator bytes.
+pub trait Initialized {
+    fn is_initialized(&self) -> bool;
+
+    #[track_caller]
+    #[inline(always)]
+    fn assert_initialized(&self) -> ProgramResult {
+        if !self.is_initialized() {
+            return Err(trace(
+                "Account is not initialized",
+                ProgramError::UninitializedAccount,
+            ));
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    #[inline(always)]
+    fn assert_uninitialized(&self) -> ProgramResult {
+        if self.is_initialized() {
+            return Err(trace(
+                "Account is already initialized",
+                ProgramError::AccountAlreadyInitialized,
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<T> Initialized for T
+where
+    T: Discriminator + Account,
+{
+    #[inline(always)]
+    fn is_initialized(&self) -> bool {
+        // SAFETY: any account struct is valid to read as bytes
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        };
+        Self::matches_discriminator(bytes)
+    }
+}
+
+/// Longest discriminator checked by [`is_initialized_data`], e.g. 8 byte Anchor
+/// discriminators.
+pub const MAX_DISCRIMINATOR_LEN: usize = 8;
+
+/// Returns `true` if raw account `data` has a discriminator written, i.e. its
+/// leading [`MAX_DISCRIMINATOR_LEN`] bytes aren't all zero.
+#[inline(always)]
+pub fn is_initialized_data(data: &[u8]) -> bool {
+    data.iter()
+        .take(MAX_DISCRIMINATOR_LEN)
+        .any(|&byte| byte != 0)
 }
 
 pub trait AccountDeserialize {
     fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError>;
     fn try_from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError>;
+
+    /// Copies the account out of `data`, which doesn't need to be aligned.
+    ///
+    /// Use for data sliced from larger buffers. Types built from alignment-1
+    /// fields (e.g. `PodU64LE`) can use the zero-copy methods on any data instead.
+    fn try_from_bytes_unaligned(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy;
+
+    /// Reads the account out of `data` in the mode selected by
+    /// [`Account::UNALIGNED`]: a zero-copy cast requiring aligned data, or an
+    /// unaligned copy.
+    fn try_read(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy;
 }
 
 impl<T> AccountDeserialize for T
@@ -30,7 +162,7 @@ where
         }
 
         /* 2. Check discriminator */
-        if Self::discriminator().ne(&data[0]) {
+        if !Self::matches_discriminator(data) {
             return Err(trace(
                 "Account has wrong discriminator",
                 ProgramError::InvalidAccountData,
@@ -61,7 +193,7 @@ where
         }
 
         /* 2. Check discriminator */
-        if Self::discriminator().ne(&data[0]) {
+        if !Self::matches_discriminator(data) {
             return Err(trace(
                 "Account has wrong discriminator",
                 ProgramError::InvalidAccountData,
@@ -80,10 +212,65 @@ where
         // SAFETY: length, discriminator and alignment are checked above
         Ok(unsafe { &mut *(data.as_mut_ptr() as *mut Self) })
     }
+
+    #[inline]
+    fn try_from_bytes_unaligned(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy,
+    {
+        /* 1. Validate bytes length */
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(trace(
+                "Account has wrong length",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+
+        /* 2. Check discriminator */
+        if !Self::matches_discriminator(data) {
+            return Err(trace(
+                "Account has wrong discriminator",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+
+        /* 3. Unaligned copy */
+        // SAFETY: length and discriminator are checked above
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
+    #[inline(always)]
+    fn try_read(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy,
+    {
+        if Self::UNALIGNED {
+            Self::try_from_bytes_unaligned(data)
+        } else {
+            Self::try_from_bytes(data).copied()
+        }
+    }
+}
+
+/// Account data looked up by key, e.g. mock accounts, the resulting accounts of
+/// a simulated instruction or accounts fetched by a client.
+pub trait AccountStore {
+    fn account_data(&self, key: &Pubkey) -> Option<&[u8]>;
 }
 
 pub trait InstructionDeserialize {
     fn try_from_bytes(data: &[u8]) -> Result<&Self, ProgramError>;
+
+    /// Copies the instruction out of `data`, which doesn't need to be aligned.
+    fn try_from_bytes_unaligned(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy;
+
+    /// Reads the instruction out of `data` in the mode selected by
+    /// [`Instruction::UNALIGNED`].
+    fn try_read(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy;
 }
 
 impl<T> InstructionDeserialize for T
@@ -106,6 +293,33 @@ where
         // SAFETY: length, discriminator and alignment are checked above
         Ok(unsafe { &*(data.as_ptr() as *const Self) })
     }
+
+    #[inline]
+    fn try_from_bytes_unaligned(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy,
+    {
+        /* 1. Validate bytes length */
+        if data.len() != core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        /* 2. Unaligned copy */
+        // SAFETY: length is checked above
+        Ok(unsafe { core::ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+
+    #[inline(always)]
+    fn try_read(data: &[u8]) -> Result<Self, ProgramError>
+    where
+        Self: Sized + Copy,
+    {
+        if Self::UNALIGNED {
+            Self::try_from_bytes_unaligned(data)
+        } else {
+            Self::try_from_bytes(data).copied()
+        }
+    }
 }
 
 // Account data is sometimes stored via a header and body type,
@@ -129,7 +343,7 @@ where
         }
 
         /* 2. Check discriminator */
-        if Self::discriminator().ne(&data[0]) {
+        if !Self::matches_discriminator(data) {
             return Err(ProgramError::InvalidAccountData);
         }
 
@@ -154,7 +368,7 @@ where
         }
 
         /* 2. Check discriminator */
-        if Self::discriminator().ne(&data[0]) {
+        if !Self::matches_discriminator(data) {
             return Err(ProgramError::InvalidAccountData);
         }
 
@@ -172,6 +386,14 @@ where
     }
 }
 
+/// Deserializes instruction arguments and runs their `Instruction::validate` rules.
+#[inline]
+pub fn parse_args<T: Instruction>(data: &[u8]) -> Result<&T, ProgramError> {
+    let args = <T as InstructionDeserialize>::try_from_bytes(data)?;
+    args.validate()?;
+    Ok(args)
+}
+
 pub trait InstructionHeaderDeserialize {
     fn try_header_from_bytes(data: &[u8]) -> Result<(&Self, &[u8]), ProgramError>;
 }
@@ -200,3 +422,168 @@ where
         Ok((unsafe { &*(header.as_ptr() as *const Self) }, body))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+    use crate::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Foreign {
+        discriminator: [u8; 8],
+        value: u64,
+    }
+
+    account!(Foreign, discriminator = [1, 2, 3, 4, 5, 6, 7, 8]);
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Sliced {
+        discriminator: [u8; 8],
+        value: u64,
+    }
+
+    account!(
+        Sliced,
+        discriminator = [2, 0, 0, 0, 0, 0, 0, 0],
+        unaligned = true
+    );
+
+    #[repr(u8)]
+    enum TestInstruction {
+        Deposit = 0,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Deposit {
+        amount: u64,
+    }
+
+    instruction!(TestInstruction, Deposit, unaligned = true);
+
+    #[repr(u8)]
+    enum TestAccount {
+        Vault = 1,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Vault {
+        discriminator: u8,
+        bump: u8,
+        owner: Pubkey,
+        index: PodU64LE,
+    }
+
+    account!(
+        TestAccount,
+        Vault,
+        bump = bump,
+        seeds = [b"vault", owner, index]
+    );
+
+    #[test]
+    fn test_foreign_discriminator() {
+        let mut data = [0u8; 16];
+        data[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        data[8..].copy_from_slice(&9u64.to_le_bytes());
+
+        assert!(Foreign::matches_discriminator(&data));
+        assert_eq!(Foreign::try_from_bytes_unaligned(&data).unwrap().value, 9);
+
+        data[7] = 0;
+        assert!(!Foreign::matches_discriminator(&data));
+        assert!(Foreign::try_from_bytes_unaligned(&data).is_err());
+    }
+
+    #[test]
+    fn test_try_read() {
+        assert!(!<Foreign as Account>::UNALIGNED);
+        assert!(<Sliced as Account>::UNALIGNED);
+
+        let mut data = [0u64; 3];
+        // SAFETY: u64 buffer reinterpreted as 24 bytes
+        let bytes = unsafe { core::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, 24) };
+
+        // Aligned types are read in place, and refuse misaligned data
+        bytes[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes[8..16].copy_from_slice(&9u64.to_le_bytes());
+        assert_eq!(Foreign::try_read(&bytes[..16]).unwrap().value, 9);
+        bytes.copy_within(..16, 1);
+        assert!(Foreign::try_read(&bytes[1..17]).is_err());
+
+        // Unaligned types are copied out of data at any offset
+        bytes[1] = 2;
+        bytes[2..9].fill(0);
+        assert_eq!(Sliced::try_read(&bytes[1..17]).unwrap().value, 9);
+        assert!(Sliced::try_from_bytes(&bytes[1..17]).is_err());
+
+        // Instruction data after its discriminator byte
+        let ix = Deposit { amount: 5 }.to_bytes();
+        assert_eq!(Deposit::try_read(&ix[1..]).unwrap().amount, 5);
+    }
+
+    #[test]
+    fn test_write_discriminator() {
+        // Initialize freshly allocated data, then load it
+        let mut data = [0u64; 2];
+        // SAFETY: `u64` data is aligned for `Foreign`
+        let bytes = unsafe { core::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, 16) };
+        Foreign::write_discriminator(bytes);
+        let foreign = Foreign::try_from_bytes_mut(bytes).unwrap();
+        foreign.value = 5;
+        assert_eq!(foreign.discriminator, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(Foreign::try_from_bytes(bytes).unwrap().value, 5);
+    }
+
+    #[test]
+    fn test_initialized() {
+        let mut foreign = Foreign {
+            discriminator: [0; 8],
+            value: 0,
+        };
+        assert!(!foreign.is_initialized());
+        assert!(foreign.assert_uninitialized().is_ok());
+        assert_eq!(
+            foreign.assert_initialized(),
+            Err(ProgramError::UninitializedAccount)
+        );
+
+        foreign.discriminator = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert!(foreign.is_initialized());
+        assert!(foreign.assert_initialized().is_ok());
+        assert_eq!(
+            foreign.assert_uninitialized(),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        // Any other discriminator is not an initialized `Foreign`
+        foreign.discriminator = [1, 2, 3, 4, 5, 6, 7, 9];
+        assert!(!foreign.is_initialized());
+        assert!(!is_initialized_data(&[]));
+        assert!(!is_initialized_data(&[0; 16]));
+        assert!(is_initialized_data(&[1, 0]));
+        assert!(is_initialized_data(&[0, 0, 0, 0, 0, 0, 0, 1]));
+        assert!(!is_initialized_data(&[0, 0, 0, 0, 0, 0, 0, 0, 1]));
+    }
+
+    #[test]
+    fn test_pda_seeds() {
+        let vault = Vault {
+            discriminator: TestAccount::Vault as u8,
+            bump: 253,
+            owner: [4; 32],
+            index: PodU64LE::new(7),
+        };
+        let seeds = vault.pda_seeds();
+        assert_eq!(seeds, [&b"vault"[..], &[4; 32], &7u64.to_le_bytes()]);
+
+        let signer = vault.signer_seeds();
+        assert_eq!(signer.bump(), 253);
+        assert_eq!(signer.seeds(), &seeds);
+        assert_eq!((vault.discriminator, vault.bump()), (1, 253));
+    }
+}
@@ -0,0 +1,376 @@
+//! Human readable dumps of account data, for debugging layout mismatches
+//! between on-chain data and client expectations.
+
+use std::{
+    fmt::Write as _,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{PodU128LE, PodU16LE, PodU32LE, PodU64LE};
+
+/// Wire type of a field, all integers being little-endian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+    Pubkey,
+    Bytes(usize),
+}
+
+/// Formats the value of a field from its raw bytes.
+pub trait DumpField {
+    const KIND: FieldKind;
+
+    fn dump_value(bytes: &[u8]) -> String;
+}
+
+/// Offset, size, wire type and decoder of an account struct field.
+#[derive(Clone, Copy)]
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FieldKind,
+    pub decode: fn(&[u8]) -> String,
+}
+
+/// Field layout of an account type, see [`account_layout!`](crate::account_layout).
+pub trait AccountLayout: Sized {
+    const NAME: &'static str;
+
+    fn fields() -> Vec<FieldLayout>;
+}
+
+/// Size of the field returned by `_field`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub const fn field_size<T, F>(_field: fn(&T) -> &F) -> usize {
+    core::mem::size_of::<F>()
+}
+
+/// Wire type of the field returned by `_field`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub const fn field_kind<T, F: DumpField>(_field: fn(&T) -> &F) -> FieldKind {
+    F::KIND
+}
+
+/// Alignment of the field returned by `_field`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub const fn field_align<T, F>(_field: fn(&T) -> &F) -> usize {
+    core::mem::align_of::<F>()
+}
+
+/// Wire type of a field declared `as Pubkey`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub const fn field_kind_as<T, F>(_field: fn(&T) -> &F, kind: FieldKind) -> FieldKind {
+    assert!(
+        matches!(kind, FieldKind::Pubkey) && core::mem::size_of::<F>() == 32,
+        "only 32 byte fields can be declared `as Pubkey`"
+    );
+    kind
+}
+
+/// Decoder of the field returned by `_field`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub fn field_decoder<T, F: DumpField>(_field: fn(&T) -> &F) -> fn(&[u8]) -> String {
+    F::dump_value
+}
+
+/// Decoder of a field declared `as Pubkey`, used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub fn field_decoder_as<T, F>(_field: fn(&T) -> &F, _kind: FieldKind) -> fn(&[u8]) -> String {
+    |bytes| bs58::encode(bytes).into_string()
+}
+
+/// Returns `true` if `fields`, as `(offset, size, align)` in declaration order,
+/// cover a `repr(C)` struct of `size` and `align` with no field left out.
+/// Used by [`account_layout!`](crate::account_layout).
+#[doc(hidden)]
+pub const fn layout_is_complete(
+    fields: &[(usize, usize, usize)],
+    size: usize,
+    align: usize,
+) -> bool {
+    let mut end = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        let (offset, field_size, field_align) = fields[i];
+        // Only alignment padding may sit between two listed fields
+        if offset != end.next_multiple_of(field_align) {
+            return false;
+        }
+        end = offset + field_size;
+        i += 1;
+    }
+    end.next_multiple_of(align) == size
+}
+
+/// Pretty-prints the fields of account `T` found in `bytes`, one per line, with
+/// their offset, size, raw bytes and decoded value.
+///
+/// ```text
+/// Counter (48 bytes, data 48 bytes)
+///      0     1  discriminator  02                        2
+///      1     1  bump           fe                        254
+///      ...
+/// ```
+///
+/// Fields beyond the end of `bytes` are reported as truncated, and the output
+/// only depends on the input, so it can be diffed between runs.
+pub fn dump<T: AccountLayout>(bytes: &[u8]) -> String {
+    let fields = T::fields();
+    let name_width = fields.iter().map(|f| f.name.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{} ({} bytes, data {} bytes)",
+        T::NAME,
+        core::mem::size_of::<T>(),
+        bytes.len()
+    );
+    for field in &fields {
+        let _ = write!(
+            out,
+            "  {:>4}  {:>4}  {:<name_width$}  ",
+            field.offset, field.size, field.name
+        );
+        match bytes.get(field.offset..field.offset + field.size) {
+            Some(raw) => {
+                let _ = writeln!(out, "{:<24}  {}", hex_preview(raw), (field.decode)(raw));
+            }
+            None => {
+                let _ = writeln!(out, "<truncated>");
+            }
+        }
+    }
+    if bytes.len() > core::mem::size_of::<T>() {
+        let _ = writeln!(
+            out,
+            "  {} trailing bytes",
+            bytes.len() - core::mem::size_of::<T>()
+        );
+    }
+    out
+}
+
+/// Hex of `bytes`, abbreviated past 8 bytes.
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut out = hex(&bytes[..bytes.len().min(8)]);
+    if bytes.len() > 8 {
+        out.push_str("..");
+    }
+    out
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+macro_rules! dump_int {
+    ($($ty:ty => $kind:ident),*) => {
+        $(impl DumpField for $ty {
+            const KIND: FieldKind = FieldKind::$kind;
+
+            fn dump_value(bytes: &[u8]) -> String {
+                let mut raw = [0u8; core::mem::size_of::<$ty>()];
+                raw.copy_from_slice(bytes);
+                <$ty>::from_le_bytes(raw).to_string()
+            }
+        })*
+    };
+}
+
+dump_int!(
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128
+);
+
+macro_rules! dump_pod {
+    ($($pod:ty => $ty:ty),*) => {
+        $(impl DumpField for $pod {
+            const KIND: FieldKind = <$ty as DumpField>::KIND;
+
+            fn dump_value(bytes: &[u8]) -> String {
+                <$ty as DumpField>::dump_value(bytes)
+            }
+        })*
+    };
+}
+
+dump_pod!(PodU16LE => u16, PodU32LE => u32, PodU64LE => u64, PodU128LE => u128);
+
+impl DumpField for bool {
+    const KIND: FieldKind = FieldKind::Bool;
+
+    fn dump_value(bytes: &[u8]) -> String {
+        match bytes[0] {
+            0 => "false".into(),
+            1 => "true".into(),
+            b => std::format!("invalid bool ({})", b),
+        }
+    }
+}
+
+/// Byte arrays are shown as hex, declare pubkey fields `as Pubkey` in
+/// [`account_layout!`](crate::account_layout) to show them as base58.
+impl<const N: usize> DumpField for [u8; N] {
+    const KIND: FieldKind = FieldKind::Bytes(N);
+
+    fn dump_value(bytes: &[u8]) -> String {
+        hex(bytes)
+    }
+}
+
+/// Declare the field layout of an account struct for [`dump`].
+///
+/// Every field type must implement [`DumpField`], and all fields must be
+/// listed in declaration order, which is checked at compile time. Byte arrays
+/// holding pubkeys are declared `as Pubkey`.
+///
+/// ```ignore
+/// account_layout!(Counter { discriminator, bump, authority as Pubkey, count });
+///
+/// println!("{}", dump::<Counter>(&account.data));
+/// ```
+#[macro_export]
+macro_rules! account_layout {
+    ($struct_name:ident { $($field:ident $(as $kind:ident)?),+ $(,)? }) => {
+        const _: () = {
+            $(let _ = $crate::account_layout!(@kind $struct_name, $field $(, $kind)?);)+
+            assert!(
+                $crate::layout_is_complete(
+                    &[$((
+                        core::mem::offset_of!($struct_name, $field),
+                        $crate::field_size(|s: &$struct_name| &s.$field),
+                        $crate::field_align(|s: &$struct_name| &s.$field),
+                    )),+],
+                    core::mem::size_of::<$struct_name>(),
+                    core::mem::align_of::<$struct_name>(),
+                ),
+                concat!(
+                    "account_layout! must list every field of `",
+                    stringify!($struct_name),
+                    "` in declaration order"
+                )
+            );
+        };
+
+        impl $crate::AccountLayout for $struct_name {
+            const NAME: &'static str = stringify!($struct_name);
+
+            fn fields() -> std::vec::Vec<$crate::FieldLayout> {
+                std::vec![$($crate::FieldLayout {
+                    name: stringify!($field),
+                    offset: core::mem::offset_of!($struct_name, $field),
+                    size: $crate::field_size(|s: &$struct_name| &s.$field),
+                    kind: $crate::account_layout!(@kind $struct_name, $field $(, $kind)?),
+                    decode: $crate::account_layout!(@decode $struct_name, $field $(, $kind)?),
+                },)+]
+            }
+        }
+    };
+    (@kind $struct_name:ident, $field:ident) => {
+        $crate::field_kind(|s: &$struct_name| &s.$field)
+    };
+    (@kind $struct_name:ident, $field:ident, $kind:ident) => {
+        $crate::field_kind_as(|s: &$struct_name| &s.$field, $crate::FieldKind::$kind)
+    };
+    (@decode $struct_name:ident, $field:ident) => {
+        $crate::field_decoder(|s: &$struct_name| &s.$field)
+    };
+    (@decode $struct_name:ident, $field:ident, $kind:ident) => {
+        $crate::field_decoder_as(|s: &$struct_name| &s.$field, $crate::FieldKind::$kind)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    struct Counter {
+        discriminator: u8,
+        active: bool,
+        authority: [u8; 32],
+        count: PodU64LE,
+    }
+
+    account_layout!(Counter {
+        discriminator,
+        active,
+        authority as Pubkey,
+        count
+    });
+
+    #[repr(C)]
+    struct Hashed {
+        kind: u8,
+        value: u64,
+        hash: [u8; 32],
+    }
+
+    account_layout!(Hashed { kind, value, hash });
+
+    #[test]
+    fn test_layout_is_complete() {
+        // (offset, size, align) of `Hashed`
+        let fields = [(0, 1, 1), (8, 8, 8), (16, 32, 1)];
+        assert!(layout_is_complete(&fields, 48, 8));
+        assert!(!layout_is_complete(&fields[1..], 48, 8));
+        assert!(!layout_is_complete(&[fields[0], fields[2]], 48, 8));
+        assert!(!layout_is_complete(
+            &[fields[1], fields[0], fields[2]],
+            48,
+            8
+        ));
+
+        let hash = &Hashed::fields()[2];
+        assert_eq!(hash.kind, FieldKind::Bytes(32));
+        assert_eq!((hash.decode)(&[0xab; 32]), "ab".repeat(32));
+    }
+
+    #[test]
+    fn test_dump() {
+        let mut data = [0u8; 42];
+        data[0] = 2;
+        data[1] = 1;
+        data[34..].copy_from_slice(&7u64.to_le_bytes());
+
+        let out = dump::<Counter>(&data);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "Counter (42 bytes, data 42 bytes)");
+        assert_eq!(
+            lines[1],
+            "     0     1  discriminator  02                        2"
+        );
+        assert!(lines[2].ends_with("true"));
+        assert!(lines[3].contains("0000000000000000.."));
+        assert!(lines[3].ends_with("11111111111111111111111111111111"));
+        assert!(lines[4].ends_with("7"));
+
+        let out = dump::<Counter>(&data[..40]);
+        assert!(out.lines().nth(4).unwrap().ends_with("<truncated>"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Error code catalog collected by the `errors!` macro.
+
+/// One error variant: code, variant name and message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorEntry {
+    pub code: u32,
+    pub name: &'static str,
+    pub message: &'static str,
+}
+
+/// Looks up the entry of `code`, e.g. to map `Custom(6003)` back to its name.
+pub fn find_error(catalog: &[ErrorEntry], code: u32) -> Option<&ErrorEntry> {
+    catalog.iter().find(|e| e.code == code)
+}
+
+/// Dumps a catalog as a JSON array of `{ "code", "name", "message" }` objects,
+/// for explorers and support tooling.
+#[cfg(feature = "client")]
+pub fn error_catalog_json(catalog: &[ErrorEntry]) -> std::string::String {
+    use std::{fmt::Write as _, string::String};
+
+    fn escape(s: &str, out: &mut String) {
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(out, "\\u{:04x}", c as u32);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    let mut out = String::from("[");
+    for (i, e) in catalog.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{{\"code\":{},\"name\":\"", e.code);
+        escape(e.name, &mut out);
+        out.push_str("\",\"message\":\"");
+        escape(e.message, &mut out);
+        out.push_str("\"}");
+    }
+    out.push(']');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    errors! {
+        pub enum TestError {
+            InsufficientFunds = 6000 => "Insufficient funds",
+            Expired = 6001 => "Offer \"expired\"",
+        }
+    }
+
+    #[test]
+    fn test_errors_catalog() {
+        assert_eq!(TestError::Expired as u32, 6001);
+        assert_eq!(TestError::InsufficientFunds.name(), "InsufficientFunds");
+        assert_eq!(TestError::Expired.message(), "Offer \"expired\"");
+        assert_eq!(TestError::CATALOG.len(), 2);
+        assert_eq!(
+            find_error(TestError::CATALOG, 6000).map(|e| e.name),
+            Some("InsufficientFunds")
+        );
+        assert_eq!(find_error(TestError::CATALOG, 1), None);
+        assert_eq!(
+            pinocchio::program_error::ProgramError::from(TestError::Expired),
+            pinocchio::program_error::ProgramError::Custom(6001)
+        );
+
+        #[cfg(feature = "client")]
+        assert_eq!(
+            error_catalog_json(TestError::CATALOG),
+            r#"[{"code":6000,"name":"InsufficientFunds","message":"Insufficient funds"},{"code":6001,"name":"Expired","message":"Offer \"expired\""}]"#
+        );
+    }
+}
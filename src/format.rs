@@ -0,0 +1,88 @@
+//! No-alloc number formatting into stack buffers, for log and memo strings.
+
+/// Buffer length fitting any `u64` or `i64`.
+pub const FMT_INT_LEN: usize = 20;
+/// Buffer length fitting any lamport amount as SOL, e.g. `18446744073.709551615`.
+pub const FMT_SOL_LEN: usize = 21;
+
+const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Writes the digits of `value` right-aligned into `buf`, returns the start index.
+#[inline(always)]
+fn write_digits(mut value: u64, buf: &mut [u8], mut end: usize) -> usize {
+    loop {
+        end -= 1;
+        buf[end] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            return end;
+        }
+    }
+}
+
+#[inline(always)]
+fn as_str(bytes: &[u8]) -> &str {
+    // SAFETY: only ASCII digits, `-` and `.` are written
+    unsafe { core::str::from_utf8_unchecked(bytes) }
+}
+
+/// Formats `value` in decimal.
+///
+/// ```ignore
+/// let mut buf = [0; FMT_INT_LEN];
+/// log!("{}", fmt_u64(amount, &mut buf));
+/// ```
+pub fn fmt_u64(value: u64, buf: &mut [u8; FMT_INT_LEN]) -> &str {
+    let start = write_digits(value, buf, FMT_INT_LEN);
+    as_str(&buf[start..])
+}
+
+/// Formats `value` in decimal, with a leading `-` if negative.
+pub fn fmt_i64(value: i64, buf: &mut [u8; FMT_INT_LEN]) -> &str {
+    let mut start = write_digits(value.unsigned_abs(), buf, FMT_INT_LEN);
+    if value < 0 {
+        start -= 1;
+        buf[start] = b'-';
+    }
+    as_str(&buf[start..])
+}
+
+/// Formats `lamports` as SOL with all 9 decimals, e.g. `1.500000000`.
+pub fn fmt_lamports_as_sol(lamports: u64, buf: &mut [u8; FMT_SOL_LEN]) -> &str {
+    let fraction = lamports % LAMPORTS_PER_SOL;
+    // Zero-pad the fraction to 9 digits
+    for (i, b) in buf[FMT_SOL_LEN - 9..].iter_mut().enumerate() {
+        *b = b'0' + (fraction / 10u64.pow(8 - i as u32) % 10) as u8;
+    }
+    buf[FMT_SOL_LEN - 10] = b'.';
+    let start = write_digits(lamports / LAMPORTS_PER_SOL, buf, FMT_SOL_LEN - 10);
+    as_str(&buf[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt_ints() {
+        let mut buf = [0; FMT_INT_LEN];
+        assert_eq!(fmt_u64(0, &mut buf), "0");
+        assert_eq!(fmt_u64(1234, &mut buf), "1234");
+        assert_eq!(fmt_u64(u64::MAX, &mut buf), "18446744073709551615");
+        assert_eq!(fmt_i64(-42, &mut buf), "-42");
+        assert_eq!(fmt_i64(i64::MIN, &mut buf), "-9223372036854775808");
+        assert_eq!(fmt_i64(7, &mut buf), "7");
+    }
+
+    #[test]
+    fn test_fmt_lamports_as_sol() {
+        let mut buf = [0; FMT_SOL_LEN];
+        assert_eq!(fmt_lamports_as_sol(0, &mut buf), "0.000000000");
+        assert_eq!(fmt_lamports_as_sol(1_500_000_000, &mut buf), "1.500000000");
+        assert_eq!(fmt_lamports_as_sol(5_000, &mut buf), "0.000005000");
+        assert_eq!(
+            fmt_lamports_as_sol(u64::MAX, &mut buf),
+            "18446744073.709551615"
+        );
+    }
+}
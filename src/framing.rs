@@ -0,0 +1,124 @@
+//! Versioned wire format of self-CPI events.
+//!
+//! After the `EMIT_EVENT_DISCRIMINATOR` byte, the instruction data carries:
+//!
+//! | offset | size | field                                      |
+//! |--------|------|--------------------------------------------|
+//! | 0      | 1    | marker, [`EVENT_FRAME_MARKER`]             |
+//! | 1      | 1    | frame version, [`EVENT_FRAME_VERSION`]     |
+//! | 2      | 1    | flags, [`EVENT_FRAME_FLAG_CHECKSUM`]       |
+//! | 3      | 2    | event length, little-endian `u16`          |
+//! | 5      | 4    | first 4 bytes of the keccak of the event   |
+//! | 9      | len  | event bytes                                |
+//!
+//! Consumers use the length and checksum to detect truncated CPI data.
+//! The checksum is zero if the flag is not set.
+//!
+//! The marker is a reserved discriminator, so frames can't be mistaken for
+//! unframed events emitted with `EmitEvent::invoke_signed_legacy`, which start
+//! with the event's own discriminator.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{hash, EVENT_FRAME_MARKER, MAX_CPI_INSTRUCTION_DATA_LEN};
+
+pub const EVENT_FRAME_VERSION: u8 = 1;
+pub const EVENT_FRAME_FLAG_CHECKSUM: u8 = 1 << 0;
+pub const EVENT_FRAME_HEADER_LEN: usize = 9;
+
+/// Max event length that fits into a framed self-CPI.
+pub const MAX_FRAMED_EVENT_LEN: usize = MAX_CPI_INSTRUCTION_DATA_LEN - EVENT_FRAME_HEADER_LEN;
+
+#[inline(always)]
+fn checksum(data: &[u8]) -> [u8; 4] {
+    let h = hash(data);
+    [h[0], h[1], h[2], h[3]]
+}
+
+/// Writes the framed `data` into `out`, returns the number of bytes written.
+pub fn encode_event_frame(
+    data: &[u8],
+    with_checksum: bool,
+    out: &mut [u8],
+) -> Result<usize, ProgramError> {
+    let len = EVENT_FRAME_HEADER_LEN + data.len();
+    if data.is_empty() || data.len() > MAX_FRAMED_EVENT_LEN || out.len() < len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    out[0] = EVENT_FRAME_MARKER;
+    out[1] = EVENT_FRAME_VERSION;
+    out[2] = if with_checksum {
+        EVENT_FRAME_FLAG_CHECKSUM
+    } else {
+        0
+    };
+    out[3..5].copy_from_slice(&(data.len() as u16).to_le_bytes());
+    out[5..9].copy_from_slice(&if with_checksum {
+        checksum(data)
+    } else {
+        [0; 4]
+    });
+    out[EVENT_FRAME_HEADER_LEN..len].copy_from_slice(data);
+
+    Ok(len)
+}
+
+/// Returns `true` if `data` starts with [`EVENT_FRAME_MARKER`], i.e. is a frame
+/// rather than an unframed event.
+#[inline(always)]
+pub fn is_event_frame(data: &[u8]) -> bool {
+    data.first() == Some(&EVENT_FRAME_MARKER)
+}
+
+/// Validates a frame and returns the event bytes.
+pub fn decode_event_frame(frame: &[u8]) -> Result<&[u8], ProgramError> {
+    if frame.len() < EVENT_FRAME_HEADER_LEN
+        || !is_event_frame(frame)
+        || frame[1] != EVENT_FRAME_VERSION
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let len = u16::from_le_bytes([frame[3], frame[4]]) as usize;
+    let data = &frame[EVENT_FRAME_HEADER_LEN..];
+    if data.len() != len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if frame[2] & EVENT_FRAME_FLAG_CHECKSUM != 0 && frame[5..9] != checksum(data) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_frame() {
+        let mut buf = [0u8; 64];
+        let len = encode_event_frame(b"event", true, &mut buf).unwrap();
+        assert_eq!(len, EVENT_FRAME_HEADER_LEN + 5);
+        assert_eq!(decode_event_frame(&buf[..len]).unwrap(), b"event");
+
+        // Truncated
+        assert!(decode_event_frame(&buf[..len - 1]).is_err());
+
+        // Corrupted
+        buf[len - 1] ^= 1;
+        assert!(decode_event_frame(&buf[..len]).is_err());
+
+        // No checksum
+        let len = encode_event_frame(b"event", false, &mut buf).unwrap();
+        assert_eq!(&buf[5..9], &[0; 4]);
+        assert_eq!(decode_event_frame(&buf[..len]).unwrap(), b"event");
+
+        // Unknown version
+        buf[1] = EVENT_FRAME_VERSION + 1;
+        assert!(decode_event_frame(&buf[..len]).is_err());
+        assert!(!is_event_frame(b"event"));
+    }
+}
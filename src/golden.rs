@@ -0,0 +1,152 @@
+//! Golden-file wire format tests for instructions, events and accounts.
+//!
+//! Serialized sample values are compared against checked-in hex files, so layout
+//! or discriminator changes fail tests before they break deployed clients.
+//! Set `PINSTEEL_UPDATE_GOLDEN=1` to (re)write the files after an intended change.
+//!
+//! ```ignore
+//! golden_test!(wire_format, "tests/golden", {
+//!     "deposit" => Deposit { amount: PodU64LE::new(100) }.to_bytes(),
+//!     "deposited" => Deposited { amount: 100 }.to_bytes(),
+//! });
+//! ```
+
+use std::{
+    fmt::Write as _,
+    format,
+    path::{Path, PathBuf},
+    string::String,
+    vec::Vec,
+};
+
+/// Environment variable that makes [`GoldenFiles`] write files instead of comparing.
+pub const UPDATE_GOLDEN_ENV: &str = "PINSTEEL_UPDATE_GOLDEN";
+
+/// Bytes per line in golden files.
+const GOLDEN_LINE_LEN: usize = 32;
+
+/// Directory of golden files, collecting mismatches across checks.
+pub struct GoldenFiles {
+    dir: PathBuf,
+    update: bool,
+    mismatches: Vec<String>,
+}
+
+impl GoldenFiles {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().into(),
+            update: std::env::var_os(UPDATE_GOLDEN_ENV).is_some(),
+            mismatches: Vec::new(),
+        }
+    }
+
+    /// Writes golden files instead of comparing against them.
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Compares `bytes` against the golden file `<name>.hex`.
+    pub fn check(&mut self, name: &str, bytes: impl AsRef<[u8]>) -> &mut Self {
+        let path = self.dir.join(format!("{}.hex", name));
+        let encoded = encode_golden(bytes.as_ref());
+        if self.update {
+            if let Err(e) =
+                std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(&path, &encoded))
+            {
+                self.mismatches.push(format!(
+                    "{}: failed to write {}: {}",
+                    name,
+                    path.display(),
+                    e
+                ));
+            }
+            return self;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(expected) if expected == encoded => {}
+            Ok(expected) => self.mismatches.push(format!(
+                "{}: wire format changed\n--- {}\n{}+++ actual\n{}",
+                name,
+                path.display(),
+                expected,
+                encoded
+            )),
+            Err(_) => self.mismatches.push(format!(
+                "{}: missing golden file {}, run with {}=1 to create it",
+                name,
+                path.display(),
+                UPDATE_GOLDEN_ENV
+            )),
+        }
+        self
+    }
+
+    /// Descriptions of all failed checks so far.
+    pub fn mismatches(&self) -> &[String] {
+        &self.mismatches
+    }
+
+    /// Panics if any check failed.
+    #[track_caller]
+    pub fn assert(&self) {
+        if !self.mismatches.is_empty() {
+            panic!("{}", self.mismatches.join("\n"));
+        }
+    }
+}
+
+/// Hex encodes `bytes`, [`GOLDEN_LINE_LEN`] bytes per line, for readable diffs.
+pub fn encode_golden(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2 + bytes.len() / GOLDEN_LINE_LEN + 1);
+    for line in bytes.chunks(GOLDEN_LINE_LEN) {
+        for byte in line {
+            let _ = write!(out, "{:02x}", byte);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Declare a test comparing serialized sample values against golden files in `dir`.
+#[macro_export]
+macro_rules! golden_test {
+    ($name:ident, $dir:expr, { $($file:literal => $bytes:expr),+ $(,)? }) => {
+        #[test]
+        fn $name() {
+            let mut golden = $crate::GoldenFiles::new(concat!(env!("CARGO_MANIFEST_DIR"), "/", $dir));
+            $(golden.check($file, $bytes);)+
+            golden.assert();
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_files() {
+        assert_eq!(
+            encode_golden(&[0xab; 33]),
+            format!("{}\nab\n", "ab".repeat(32))
+        );
+
+        let dir = std::env::temp_dir().join(format!("pinsteel-golden-{}", std::process::id()));
+        GoldenFiles::new(&dir)
+            .update(true)
+            .check("ix", [1u8, 2, 3])
+            .assert();
+
+        let mut golden = GoldenFiles::new(&dir).update(false);
+        golden.check("ix", [1u8, 2, 3]);
+        assert!(golden.mismatches().is_empty());
+        golden.check("ix", [1u8, 2, 4]).check("missing", [0u8]);
+        assert_eq!(golden.mismatches().len(), 2);
+        assert!(golden.mismatches()[0].starts_with("ix: wire format changed"));
+        assert!(golden.mismatches()[1].starts_with("missing: missing golden file"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
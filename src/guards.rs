@@ -0,0 +1,83 @@
+//! User-protection checks for swap and liquidity instructions.
+
+use pinocchio::{program_error::ProgramError, sysvars::clock::Clock, ProgramResult};
+
+use crate::trace;
+
+pub const ERROR_DEADLINE_EXCEEDED: u32 = 6;
+pub const ERROR_SLIPPAGE_MIN_OUT: u32 = 7;
+pub const ERROR_SLIPPAGE_MAX_IN: u32 = 8;
+
+/// Fails if `clock` is past the user supplied unix `deadline`.
+#[track_caller]
+#[inline(always)]
+pub fn check_deadline(clock: &Clock, deadline: i64) -> ProgramResult {
+    if clock.unix_timestamp > deadline {
+        return Err(trace(
+            "Deadline exceeded",
+            ProgramError::Custom(ERROR_DEADLINE_EXCEEDED),
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if the output amount is below the user supplied minimum.
+#[track_caller]
+#[inline(always)]
+pub fn check_min_out(actual: u64, min_out: u64) -> ProgramResult {
+    if actual < min_out {
+        return Err(trace(
+            "Output amount is below minimum",
+            ProgramError::Custom(ERROR_SLIPPAGE_MIN_OUT),
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if the input amount is above the user supplied maximum.
+#[track_caller]
+#[inline(always)]
+pub fn check_max_in(actual: u64, max_in: u64) -> ProgramResult {
+    if actual > max_in {
+        return Err(trace(
+            "Input amount is above maximum",
+            ProgramError::Custom(ERROR_SLIPPAGE_MAX_IN),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_deadline() {
+        let clock = Clock {
+            slot: 1,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: 100,
+        };
+        assert!(check_deadline(&clock, 100).is_ok());
+        assert_eq!(
+            check_deadline(&clock, 99),
+            Err(ProgramError::Custom(ERROR_DEADLINE_EXCEEDED))
+        );
+    }
+
+    #[test]
+    fn test_check_slippage() {
+        assert!(check_min_out(10, 10).is_ok());
+        assert_eq!(
+            check_min_out(9, 10),
+            Err(ProgramError::Custom(ERROR_SLIPPAGE_MIN_OUT))
+        );
+        assert!(check_max_in(10, 10).is_ok());
+        assert_eq!(
+            check_max_in(11, 10),
+            Err(ProgramError::Custom(ERROR_SLIPPAGE_MAX_IN))
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Heap staging for structs too large for the 4KB SBF stack frame.
+//!
+//! `Box::new(value)` builds `value` on the stack before moving it to the heap,
+//! which overflows the stack frame for large account structs. [`HeapStaged`]
+//! allocates zeroed memory directly and lets the struct be filled in place.
+//! [`const_assert_stack_size!`](crate::const_assert_stack_size) catches structs
+//! that need it at compile time.
+
+use core::{
+    alloc::Layout,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use alloc::alloc::{alloc_zeroed, dealloc, handle_alloc_error};
+use pinocchio::program_error::ProgramError;
+
+use crate::Account;
+
+/// Size of an SBF stack frame.
+pub const MAX_STACK_FRAME_SIZE: usize = 4096;
+
+/// Fails compilation if `$ty` is larger than `$max` bytes, by default
+/// [`MAX_STACK_FRAME_SIZE`], e.g. for handler arguments or locals built on the
+/// stack. Larger structs belong in a [`HeapStaged`] or a scratch account.
+///
+/// ```ignore
+/// const_assert_stack_size!(SwapArgs);
+/// // Leave room for the handler's other locals
+/// const_assert_stack_size!(SwapArgs, 1024);
+/// ```
+#[macro_export]
+macro_rules! const_assert_stack_size {
+    ($ty:ty) => {
+        $crate::const_assert_stack_size!($ty, $crate::MAX_STACK_FRAME_SIZE);
+    };
+    ($ty:ty, $max:expr) => {
+        const _: () = assert!(
+            core::mem::size_of::<$ty>() <= $max,
+            concat!("`", stringify!($ty), "` is too large for the stack")
+        );
+    };
+}
+
+/// Heap allocated `T` that never passes through the stack.
+///
+/// Limited to [`Account`] types, which are plain data valid for any byte
+/// pattern, including all zeroes.
+pub struct HeapStaged<T: Account> {
+    ptr: NonNull<T>,
+}
+
+impl<T: Account> HeapStaged<T> {
+    /// Allocates a zero-initialized `T`.
+    pub fn zeroed() -> Self {
+        let layout = Layout::new::<T>();
+        if layout.size() == 0 {
+            return Self {
+                ptr: NonNull::dangling(),
+            };
+        }
+        // SAFETY: layout has non-zero size
+        let ptr = unsafe { alloc_zeroed(layout) } as *mut T;
+        match NonNull::new(ptr) {
+            Some(ptr) => Self { ptr },
+            None => handle_alloc_error(layout),
+        }
+    }
+
+    /// Copies `data` into a new heap allocation, `data` doesn't need to be aligned.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != core::mem::size_of::<T>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut staged = Self::zeroed();
+        staged.as_bytes_mut().copy_from_slice(data);
+        Ok(staged)
+    }
+
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: `ptr` points to an initialized `T`
+        unsafe {
+            core::slice::from_raw_parts(self.ptr.as_ptr() as *const u8, core::mem::size_of::<T>())
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: `ptr` points to an initialized `T`, valid for any byte pattern
+        unsafe {
+            core::slice::from_raw_parts_mut(self.ptr.as_ptr() as *mut u8, core::mem::size_of::<T>())
+        }
+    }
+}
+
+impl<T: Account> Deref for HeapStaged<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        // SAFETY: `ptr` points to an initialized `T`
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: Account> DerefMut for HeapStaged<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: `ptr` points to an initialized `T` owned by `self`
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: Account> Drop for HeapStaged<T> {
+    fn drop(&mut self) {
+        let layout = Layout::new::<T>();
+        if layout.size() != 0 {
+            // SAFETY: allocated in `zeroed` with the same layout
+            unsafe { dealloc(self.ptr.as_ptr() as *mut u8, layout) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    struct Large {
+        header: u64,
+        body: [u64; 4096],
+    }
+
+    impl Account for Large {}
+
+    crate::const_assert_stack_size!(u64);
+    crate::const_assert_stack_size!([u8; MAX_STACK_FRAME_SIZE]);
+    crate::const_assert_stack_size!(Large, 8 * 4097);
+
+    #[test]
+    fn test_heap_staged() {
+        let mut staged = HeapStaged::<Large>::zeroed();
+        assert_eq!(staged.header, 0);
+        assert!(staged.body.iter().all(|&x| x == 0));
+
+        staged.body[4095] = 7;
+        let copy = HeapStaged::<Large>::from_bytes(staged.as_bytes()).unwrap();
+        assert_eq!(copy.body[4095], 7);
+
+        assert!(HeapStaged::<Large>::from_bytes(&[0; 8]).is_err());
+    }
+}
@@ -3,14 +3,17 @@ use pinocchio::{
     cpi::invoke_signed,
     instruction::{AccountMeta, Instruction, Signer},
     program_error::ProgramError,
-    pubkey::Pubkey,
-    sysvars::{rent::Rent, Sysvar},
+    pubkey::{find_program_address, Pubkey},
     ProgramResult,
 };
 
 use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
 
-use crate::{EMIT_EVENT_DISCRIMINATOR, MAX_CPI_INSTRUCTION_DATA_LEN};
+use crate::{
+    encode_event_frame, event, fast_memset, LamportsExt, Loggable, PdaSigner, SyscallSysvars,
+    SysvarSource, ACCOUNT_CLOSED_EVENT, EMIT_EVENT_DISCRIMINATOR, EVENT_AUTHORITY_SEED,
+    MAX_CPI_INSTRUCTION_DATA_LEN, MAX_PERMITTED_DATA_INCREASE,
+};
 
 /// Create a new program account.
 ///
@@ -40,12 +43,22 @@ impl CreateProgramAccount<'_> {
     /// Create a new PDA.
     #[inline(always)]
     pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        self.invoke_signed_with(signers, &SyscallSysvars)
+    }
+
+    /// Same as [`Self::invoke_signed`], reading rent from `sysvars`.
+    #[inline(always)]
+    pub fn invoke_signed_with(
+        &self,
+        signers: &[Signer],
+        sysvars: &impl SysvarSource,
+    ) -> ProgramResult {
         if self.pda.lamports() == 0 {
             // If balance is zero, create account
             return CreateAccount {
                 from: self.payer,
                 to: self.pda,
-                lamports: Rent::get()?.minimum_balance(self.space).max(1),
+                lamports: sysvars.rent()?.minimum_balance(self.space).max(1),
                 space: self.space as u64,
                 owner: self.owner,
             }
@@ -57,7 +70,8 @@ impl CreateProgramAccount<'_> {
         // in order to get around it, you need to fund the account with enough lamports to be rent exempt,
         // then allocate the required space and set the owner to the current program
 
-        let required_lamports = Rent::get()?
+        let required_lamports = sysvars
+            .rent()?
             .minimum_balance(self.space)
             .max(1)
             .saturating_sub(self.pda.lamports());
@@ -112,11 +126,18 @@ pub struct ResizeProgramAccount<'a> {
 impl ResizeProgramAccount<'_> {
     #[inline(always)]
     pub fn invoke(&self) -> ProgramResult {
+        self.invoke_with(&SyscallSysvars)
+    }
+
+    /// Same as [`Self::invoke`], reading rent from `sysvars`.
+    #[inline(always)]
+    pub fn invoke_with(&self, sysvars: &impl SysvarSource) -> ProgramResult {
         if self.pda.owner() != self.program {
             return Err(ProgramError::IllegalOwner);
         }
 
-        let required_lamports = Rent::get()?
+        let required_lamports = sysvars
+            .rent()?
             .minimum_balance(self.space)
             .max(1)
             .saturating_sub(self.pda.lamports());
@@ -134,6 +155,114 @@ impl ResizeProgramAccount<'_> {
 
         Ok(())
     }
+
+    /// Same as `invoke`, but safe to use for shrinking and re-growing accounts:
+    /// 1. Bytes cut off by shrinking are zeroed, so they can't reappear on a later grow
+    /// 2. Bytes exposed by growing are zeroed
+    ///
+    /// Lamports above the new rent-exempt minimum stay in the account.
+    pub fn invoke_zeroed(&self) -> ProgramResult {
+        self.invoke_zeroed_with(&SyscallSysvars)
+    }
+
+    /// Same as [`Self::invoke_zeroed`], reading rent from `sysvars`.
+    pub fn invoke_zeroed_with(&self, sysvars: &impl SysvarSource) -> ProgramResult {
+        if !self.pda.is_owned_by(self.program) {
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        let old_len = self.pda.data_len();
+
+        // 1) Zero the truncated tail before shrinking
+        if self.space < old_len {
+            fast_memset(&mut self.pda.try_borrow_mut_data()?[self.space..], 0);
+        }
+
+        let rent_exempt = sysvars.rent()?.minimum_balance(self.space).max(1);
+        let lamports = self.pda.lamports();
+
+        if rent_exempt > lamports {
+            Transfer {
+                from: self.payer,
+                to: self.pda,
+                lamports: rent_exempt - lamports,
+            }
+            .invoke()?;
+        }
+
+        self.pda.resize(self.space)?;
+
+        // 2) Zero the exposed bytes after growing
+        if self.space > old_len {
+            fast_memset(&mut self.pda.try_borrow_mut_data()?[old_len..], 0);
+        }
+
+        Ok(())
+    }
+}
+
+/// Create a program account larger than the 10KB a single instruction may allocate.
+///
+/// The first call creates the account with up to [`MAX_PERMITTED_DATA_INCREASE`] bytes,
+/// every following call (in later instructions) grows it by up to the same amount.
+/// The current data length is the progress marker: `invoke_signed` returns `true`
+/// once the account reached `space` bytes. Account data must not be used before that.
+///
+/// ### Accounts:
+///   0. `[WRITE, SIGNER]` Funding account
+///   1. `[WRITE, SIGNER]` PDA account
+pub struct CreateLargeProgramAccount<'a> {
+    /// Funding account.
+    pub payer: &'a AccountInfo,
+
+    /// PDA account.
+    pub pda: &'a AccountInfo,
+
+    /// Final number of bytes of memory to allocate.
+    pub space: usize,
+
+    /// Address of program that will own the new account.
+    pub owner: &'a Pubkey,
+}
+
+impl CreateLargeProgramAccount<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> Result<bool, ProgramError> {
+        self.invoke_signed(&[])
+    }
+
+    /// Performs the next allocation step, returns `true` if the account is complete.
+    pub fn invoke_signed(&self, signers: &[Signer]) -> Result<bool, ProgramError> {
+        // 1) Create the account with the first chunk
+        if !self.pda.is_owned_by(self.owner) {
+            CreateProgramAccount {
+                payer: self.payer,
+                pda: self.pda,
+                space: self.space.min(MAX_PERMITTED_DATA_INCREASE),
+                owner: self.owner,
+            }
+            .invoke_signed(signers)?;
+
+            return Ok(self.pda.data_len() >= self.space);
+        }
+
+        // 2) Grow by one chunk per instruction
+        let len = self.pda.data_len();
+        if len >= self.space {
+            return Ok(true);
+        }
+
+        let next = self.space.min(len + MAX_PERMITTED_DATA_INCREASE);
+        ResizeProgramAccount {
+            payer: self.payer,
+            pda: self.pda,
+            space: next,
+            program: self.owner,
+        }
+        .invoke()?;
+
+        Ok(next == self.space)
+    }
 }
 
 /// Close a program account
@@ -156,15 +285,60 @@ impl CloseProgramAccount<'_> {
     pub fn invoke(&self) -> ProgramResult {
         // Defund by transferring all SOL to the destination account.
         // Use direct lamports manipulation, SystemProgram::Transfer can't work with data carrying accounts.
-        *self.destination.try_borrow_mut_lamports()? += *self.account.try_borrow_lamports()?;
-        *self.account.try_borrow_mut_lamports()? = 0;
+        self.account
+            .transfer_lamports_to(self.destination, self.account.lamports())?;
+
+        // Zeroize the data, so it can't be read if the account is revived within the transaction
+        fast_memset(&mut self.account.try_borrow_mut_data()?, 0);
 
         // Resize the account to 1 byte and close it
         self.account.resize(0)?;
         self.account.close()
     }
+
+    /// Same as `invoke`, but first writes an [`AccountClosed`] receipt into return
+    /// data, so indexers can reconcile the account lifecycle.
+    ///
+    /// Use [`Self::receipt`] instead to emit it through `EmitEvent`.
+    #[inline(always)]
+    pub fn close_with_receipt(&self) -> ProgramResult {
+        self.receipt()?.log_return();
+        self.invoke()
+    }
+
+    /// Receipt of the account as it is right before closing.
+    pub fn receipt(&self) -> Result<AccountClosed, ProgramError> {
+        Ok(AccountClosed {
+            event: ACCOUNT_CLOSED_EVENT,
+            discriminator: self
+                .account
+                .try_borrow_data()?
+                .first()
+                .copied()
+                .unwrap_or(0),
+            _padding: [0; 6],
+            account: *self.account.key(),
+            lamports: self.account.lamports(),
+        })
+    }
 }
 
+/// Receipt of a [`CloseProgramAccount::close_with_receipt`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountClosed {
+    /// Always [`ACCOUNT_CLOSED_EVENT`].
+    pub event: u8,
+    /// Type discriminator of the closed account, `0` if it had no data.
+    pub discriminator: u8,
+    pub _padding: [u8; 6],
+    pub account: Pubkey,
+    /// Lamports returned to the destination.
+    pub lamports: u64,
+}
+
+event!(AccountClosed);
+
 /// Log an event by making a self-CPI that can be subscribed to by clients.
 ///
 /// This way of logging events is more reliable than `log` or `log_return` because RPCs are less likely
@@ -178,6 +352,10 @@ impl CloseProgramAccount<'_> {
 /// being invoked by the same program. Requiring this PDA to be a signer during `invoke_signed`
 /// syscall ensures that the program is the one doing the logging.
 ///
+/// Event data is wrapped in a versioned frame with length and checksum (see [`encode_event_frame`]).
+/// [`Self::invoke_signed_legacy`] emits it as-is after the discriminator instead, for indexers that
+/// don't decode frames yet.
+///
 /// ### Accounts:
 ///   0. `[]` Program ID account
 ///   1. `[SIGNER]` Event authority account
@@ -193,7 +371,20 @@ pub struct EmitEvent<'a> {
 }
 
 impl EmitEvent<'_> {
+    #[inline(always)]
     pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        self.invoke_signed_framed(signers, true)
+    }
+
+    /// Same as `invoke_signed`, but skips the checksum to save compute units.
+    #[inline(always)]
+    pub fn invoke_signed_without_checksum(&self, signers: &[Signer]) -> ProgramResult {
+        self.invoke_signed_framed(signers, false)
+    }
+
+    /// Same as `invoke_signed`, but without the frame: the event data follows
+    /// the discriminator as-is.
+    pub fn invoke_signed_legacy(&self, signers: &[Signer]) -> ProgramResult {
         // Check if data length is within the limits
         if self.data.len() > MAX_CPI_INSTRUCTION_DATA_LEN || self.data.is_empty() {
             return Err(ProgramError::InvalidInstructionData);
@@ -202,8 +393,22 @@ impl EmitEvent<'_> {
         buf[0] = EMIT_EVENT_DISCRIMINATOR;
         buf[1..1 + self.data.len()].copy_from_slice(self.data);
 
-        let instruction_data =
-            unsafe { core::slice::from_raw_parts(buf.as_ptr() as _, 1 + self.data.len()) };
+        self.invoke_signed_with_data(&buf[..1 + self.data.len()], signers)
+    }
+
+    fn invoke_signed_framed(&self, signers: &[Signer], with_checksum: bool) -> ProgramResult {
+        let mut buf = [0; 1 + MAX_CPI_INSTRUCTION_DATA_LEN];
+        buf[0] = EMIT_EVENT_DISCRIMINATOR;
+        let len = encode_event_frame(self.data, with_checksum, &mut buf[1..])?;
+
+        self.invoke_signed_with_data(&buf[..1 + len], signers)
+    }
+
+    fn invoke_signed_with_data(
+        &self,
+        instruction_data: &[u8],
+        signers: &[Signer],
+    ) -> ProgramResult {
         let instruction = Instruction {
             program_id: self.program_id,
             accounts: &[AccountMeta::readonly_signer(self.event_authority.key())],
@@ -214,3 +419,19 @@ impl EmitEvent<'_> {
         Ok(())
     }
 }
+
+/// Derives the event authority PDA of `program_id`.
+#[inline(always)]
+pub fn event_authority_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(&[EVENT_AUTHORITY_SEED], program_id)
+}
+
+/// Signer seeds of the event authority PDA, for `EmitEvent::invoke_signed`.
+///
+/// ```ignore
+/// event_authority_signer(bump).with_signer(|signers| emit.invoke_signed(signers))?;
+/// ```
+#[inline(always)]
+pub const fn event_authority_signer(bump: u8) -> PdaSigner<'static, 1> {
+    PdaSigner::new([EVENT_AUTHORITY_SEED], bump)
+}
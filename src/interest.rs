@@ -0,0 +1,164 @@
+//! Interest accrual over elapsed time, on `WAD` (1e18) fixed-point rates.
+
+use crate::{mul_div, to_u64_checked, widen, Rounding, U256};
+
+/// Fixed-point scale of rates: `WAD` is 100%.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+/// How interest compounds over the elapsed period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compounding {
+    /// `principal * (1 + r * t)`.
+    Linear,
+    /// Per-second compounding `principal * (1 + r)^t`, approximated by the first
+    /// four terms of its binomial expansion. Slightly undershoots, the more the
+    /// higher `r * t`.
+    Compound,
+}
+
+/// Growth factor over `elapsed_secs` at `rate_per_second` (`WAD` scaled), `WAD` scaled.
+/// Saturates at `u128::MAX`.
+pub fn accrual_factor(rate_per_second: u128, elapsed_secs: u64, compounding: Compounding) -> u128 {
+    let t = widen(elapsed_secs);
+    let factor = match compounding {
+        Compounding::Linear => rate_per_second
+            .checked_mul(t)
+            .and_then(|growth| WAD.checked_add(growth)),
+        Compounding::Compound => compound_factor(rate_per_second, t),
+    };
+    factor.unwrap_or(u128::MAX)
+}
+
+/// `1 + rt + t(t-1)/2 * r^2 + t(t-1)(t-2)/6 * r^3`, `None` if it exceeds `u128`.
+fn compound_factor(r: u128, t: u128) -> Option<u128> {
+    let first = r.checked_mul(t)?;
+    // `t` is at most `u64::MAX`, so `t * (t - 1)` fits
+    let pairs = t * t.saturating_sub(1) / 2;
+    let second = mul3_div(pairs, r, r, WAD)?;
+    // t(t-1)(t-2)/6 * r^3 = second * (t-2) * r / 3
+    let third = mul3_div(second, t.saturating_sub(2), r, 3 * WAD)?;
+    WAD.checked_add(first)?
+        .checked_add(second)?
+        .checked_add(third)
+}
+
+/// `a * b * c / denominator` rounded down, `None` if it exceeds `u128`.
+fn mul3_div(a: u128, b: u128, c: u128, denominator: u128) -> Option<u128> {
+    if c == 0 {
+        return Some(0);
+    }
+    // (q * d + rem) * c / d = q * c + rem * c / d
+    let (quotient, remainder) = U256::full_mul(a, b).div_rem(denominator)?;
+    quotient.to_u128()?.checked_mul(c)?.checked_add(mul_div(
+        remainder,
+        c,
+        denominator,
+        Rounding::Down,
+    )?)
+}
+
+/// `principal` with interest accrued over `elapsed_secs`, rounded down.
+/// Saturates at `u64::MAX`.
+///
+/// ```ignore
+/// // 5% APR as a per-second rate
+/// let rate = 5 * WAD / 100 / SECONDS_PER_YEAR as u128;
+/// let owed = accrue(borrowed, rate, now - last_update, Compounding::Compound);
+/// ```
+pub fn accrue(
+    principal: u64,
+    rate_per_second: u128,
+    elapsed_secs: u64,
+    compounding: Compounding,
+) -> u64 {
+    let factor = accrual_factor(rate_per_second, elapsed_secs, compounding);
+    widen(principal)
+        .checked_mul(factor)
+        .map_or(u64::MAX, |scaled| {
+            to_u64_checked(scaled / WAD).unwrap_or(u64::MAX)
+        })
+}
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference `(1 + r)^t` in floating point.
+    fn reference(principal: u64, rate: f64, t: u64) -> f64 {
+        let mut value = principal as f64;
+        for _ in 0..t {
+            value *= 1.0 + rate;
+        }
+        value
+    }
+
+    fn assert_close(actual: u64, expected: f64, tolerance: f64) {
+        let error = (actual as f64 - expected) / expected;
+        assert!(
+            -tolerance < error && error < tolerance,
+            "{actual} vs {expected}"
+        );
+    }
+
+    #[test]
+    fn test_accrue_linear() {
+        let rate = WAD / 100;
+        assert_eq!(accrue(1_000, rate, 10, Compounding::Linear), 1_100);
+        assert_eq!(accrue(1_000, rate, 0, Compounding::Linear), 1_000);
+        assert_eq!(accrue(u64::MAX, rate, 10, Compounding::Linear), u64::MAX);
+    }
+
+    #[test]
+    fn test_accrue_compound_matches_reference() {
+        // 10% APR over 30 days, per-second compounding
+        let apr = 0.10;
+        let rate = (apr * WAD as f64 / SECONDS_PER_YEAR as f64) as u128;
+        let t = 30 * 24 * 60 * 60;
+        let principal = 1_000_000_000_000;
+
+        let expected = reference(principal, apr / SECONDS_PER_YEAR as f64, t);
+        assert_close(
+            accrue(principal, rate, t, Compounding::Compound),
+            expected,
+            1e-8,
+        );
+        assert!(accrue(principal, rate, t, Compounding::Linear) < expected as u64);
+
+        assert_eq!(accrue(1_000, WAD / 10, 1, Compounding::Compound), 1_100);
+        assert_eq!(accrue(1_000, WAD / 10, 2, Compounding::Compound), 1_210);
+    }
+
+    #[test]
+    fn test_accrue_saturates() {
+        assert_eq!(
+            accrue(u64::MAX, u128::MAX, u64::MAX, Compounding::Compound),
+            u64::MAX
+        );
+        assert_eq!(accrual_factor(u128::MAX, 2, Compounding::Linear), u128::MAX);
+        assert_eq!(
+            accrual_factor(u128::MAX, 2, Compounding::Compound),
+            u128::MAX
+        );
+        assert_eq!(
+            accrual_factor(100 * WAD, u64::MAX, Compounding::Compound),
+            u128::MAX
+        );
+    }
+
+    #[test]
+    fn test_accrual_factor_extreme_rates() {
+        // 100% per second: 1 + 100 + 4950 + 161700
+        assert_eq!(
+            accrual_factor(WAD, 100, Compounding::Compound),
+            166_751 * WAD
+        );
+        // Tiny rate over the longest period, intermediates exceed u128 but the
+        // factor doesn't
+        assert_eq!(
+            accrual_factor(1, u64::MAX, Compounding::Compound),
+            1_235_771_550_098_625_576_951
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Views between pinocchio's `AccountInfo` and `solana_program`'s, for programs
+//! migrating piecemeal from solana-program to pinocchio and pinsteel.
+//!
+//! Both views point at the same account in the runtime input buffer, so helpers
+//! written against either one can be shared. Borrows are tracked separately by
+//! each view: don't hold a borrow on one view while writing through the other.
+
+use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+use solana_program::{
+    account_info::AccountInfo as SolanaAccountInfo, pubkey::Pubkey as SolanaPubkey,
+};
+
+/// Offset of the key in the runtime account header, see `MockAccount`.
+const KEY_OFFSET: usize = 8;
+
+/// Reinterprets a pinocchio pubkey as a `solana_program` one, both being 32 bytes.
+#[inline(always)]
+pub fn to_solana_pubkey(key: &Pubkey) -> &SolanaPubkey {
+    // SAFETY: `SolanaPubkey` is a `#[repr(transparent)]` wrapper of `[u8; 32]`
+    unsafe { &*(key as *const Pubkey as *const SolanaPubkey) }
+}
+
+/// Reinterprets a `solana_program` pubkey as a pinocchio one.
+#[inline(always)]
+pub fn from_solana_pubkey(key: &SolanaPubkey) -> &Pubkey {
+    // SAFETY: `SolanaPubkey` is a `#[repr(transparent)]` wrapper of `[u8; 32]`
+    unsafe { &*(key as *const SolanaPubkey as *const Pubkey) }
+}
+
+/// Conversion of pinocchio accounts into `solana_program` views.
+pub trait ToSolanaAccountInfo {
+    /// Returns a `solana_program` view of the same account.
+    ///
+    /// The view reports `rent_epoch` as `u64::MAX`, i.e. rent exempt, since
+    /// pinocchio does not expose it.
+    ///
+    /// # Safety
+    /// The account data and lamports must not be borrowed through `self`
+    /// while the view is used, as the view bypasses pinocchio's borrow tracking.
+    unsafe fn to_solana_account_info(&self) -> SolanaAccountInfo<'_>;
+}
+
+impl ToSolanaAccountInfo for AccountInfo {
+    #[inline(always)]
+    unsafe fn to_solana_account_info(&self) -> SolanaAccountInfo<'_> {
+        SolanaAccountInfo::new(
+            to_solana_pubkey(self.key()),
+            self.is_signer(),
+            self.is_writable(),
+            self.borrow_mut_lamports_unchecked(),
+            self.borrow_mut_data_unchecked(),
+            to_solana_pubkey(self.owner()),
+            self.executable(),
+            u64::MAX,
+        )
+    }
+}
+
+/// Returns the pinocchio view of an account deserialized by the solana-program
+/// entrypoint.
+///
+/// # Safety
+/// `account` must point into the runtime input buffer, as accounts passed to a
+/// solana-program entrypoint do. Accounts built any other way, e.g. in tests,
+/// don't have the runtime account header in front of their key.
+#[inline(always)]
+pub unsafe fn from_solana_account_info(account: &SolanaAccountInfo) -> AccountInfo {
+    let header = (account.key as *const SolanaPubkey as *mut u8).sub(KEY_OFFSET);
+    core::mem::transmute::<*mut u8, AccountInfo>(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockAccount;
+
+    #[test]
+    fn test_solana_account_info_roundtrip() {
+        let mut account = MockAccount::new([1; 32], [2; 32], 42, &[7, 8, 9]).writable(true);
+        let info = account.info();
+
+        let solana = unsafe { info.to_solana_account_info() };
+        assert_eq!(solana.key.to_bytes(), [1; 32]);
+        assert_eq!(solana.owner.to_bytes(), [2; 32]);
+        assert_eq!(solana.lamports(), 42);
+        assert_eq!(&solana.data.borrow()[..], &[7, 8, 9]);
+        assert!(solana.is_writable && !solana.is_signer);
+
+        **solana.lamports.borrow_mut() = 50;
+        solana.data.borrow_mut()[0] = 1;
+        drop(solana);
+
+        let solana = unsafe { info.to_solana_account_info() };
+        let back = unsafe { from_solana_account_info(&solana) };
+        assert_eq!(back.key(), &[1; 32]);
+        assert_eq!(back.lamports(), 50);
+        drop(solana);
+
+        drop(info);
+        assert_eq!(account.lamports(), 50);
+        assert_eq!(account.data(), &[1, 8, 9]);
+    }
+}
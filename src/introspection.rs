@@ -0,0 +1,220 @@
+//! Transaction introspection through the Instructions sysvar.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::instructions::{Instructions, IntrospectedInstruction},
+    ProgramResult,
+};
+
+use crate::trace;
+
+pub const ERROR_MISSING_PRECEDING_INSTRUCTION: u32 = 13;
+pub const ERROR_FEE_PAYER_UNKNOWN: u32 = 23;
+
+#[inline(always)]
+fn matches(ix: &IntrospectedInstruction, program_id: &Pubkey, data_prefix: &[u8]) -> bool {
+    ix.get_program_id().eq(program_id) && ix.get_instruction_data().starts_with(data_prefix)
+}
+
+/// Fails unless an instruction of `program_id` whose data starts with
+/// `data_prefix` appears anywhere before the current instruction.
+///
+/// Used for flows that must be set up earlier in the same transaction,
+/// e.g. a compute-budget or token-transfer instruction.
+#[track_caller]
+pub fn require_preceding_instruction(
+    instructions_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+    data_prefix: &[u8],
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let current = instructions.load_current_index() as usize;
+
+    for index in 0..current {
+        if matches(
+            &instructions.load_instruction_at(index)?,
+            program_id,
+            data_prefix,
+        ) {
+            return Ok(());
+        }
+    }
+
+    Err(trace(
+        "Required preceding instruction is missing",
+        ProgramError::Custom(ERROR_MISSING_PRECEDING_INSTRUCTION),
+    ))
+}
+
+/// Same as [`require_preceding_instruction`], but the matching instruction
+/// must immediately precede the current one.
+#[track_caller]
+pub fn require_previous_instruction(
+    instructions_sysvar: &AccountInfo,
+    program_id: &Pubkey,
+    data_prefix: &[u8],
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let current = instructions.load_current_index() as usize;
+
+    if current > 0
+        && matches(
+            &instructions.load_instruction_at(current - 1)?,
+            program_id,
+            data_prefix,
+        )
+    {
+        return Ok(());
+    }
+
+    Err(trace(
+        "Required previous instruction is missing",
+        ProgramError::Custom(ERROR_MISSING_PRECEDING_INSTRUCTION),
+    ))
+}
+
+/// Returns the transaction fee payer, passed as the first account of the
+/// instruction by convention.
+///
+/// Programs can't read the account keys of the message, where the fee payer
+/// comes first, and the instructions sysvar doesn't single it out either. So
+/// `accounts[0]` is only checked to be a writable signer, like every fee payer.
+/// With several writable signers, e.g. a separate payer and authority, the
+/// client may pass any of them: don't rely on this alone to rebate fees.
+#[track_caller]
+pub fn fee_payer(accounts: &[AccountInfo]) -> Result<&AccountInfo, ProgramError> {
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !payer.is_signer() || !payer.is_writable() {
+        return Err(trace(
+            "Fee payer must be a writable signer",
+            ProgramError::Custom(ERROR_FEE_PAYER_UNKNOWN),
+        ));
+    }
+    Ok(payer)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{as_account_infos, MockAccount, Validation, INSTRUCTIONS_SYSVAR_ID};
+
+    /// Instructions sysvar data of `ixs`, without accounts, executing `current`.
+    fn instructions_data(ixs: &[(Pubkey, &[u8])], current: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(ixs.len() as u16).to_le_bytes());
+        let mut offset = 2 + 2 * ixs.len();
+        for (_, ix_data) in ixs {
+            data.extend_from_slice(&(offset as u16).to_le_bytes());
+            offset += 2 + 32 + 2 + ix_data.len();
+        }
+        for (program_id, ix_data) in ixs {
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(program_id);
+            data.extend_from_slice(&(ix_data.len() as u16).to_le_bytes());
+            data.extend_from_slice(ix_data);
+        }
+        data.extend_from_slice(&current.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_require_instruction() {
+        let (a, b, program) = ([1; 32], [2; 32], [3; 32]);
+        let ixs: [(Pubkey, &[u8]); 3] = [(a, &[1, 2]), (b, &[3, 4]), (program, &[9, 9])];
+        let mut sysvar = MockAccount::new(
+            INSTRUCTIONS_SYSVAR_ID,
+            [0; 32],
+            100,
+            &instructions_data(&ixs, 2),
+        );
+        let mut first = MockAccount::new(
+            INSTRUCTIONS_SYSVAR_ID,
+            [0; 32],
+            100,
+            &instructions_data(&ixs, 0),
+        );
+        let missing = Err(ProgramError::Custom(ERROR_MISSING_PRECEDING_INSTRUCTION));
+
+        assert_eq!(
+            require_preceding_instruction(&sysvar.info(), &a, &[1]),
+            Ok(())
+        );
+        assert_eq!(
+            require_preceding_instruction(&sysvar.info(), &b, &[]),
+            Ok(())
+        );
+        assert_eq!(
+            require_previous_instruction(&sysvar.info(), &b, &[3, 4]),
+            Ok(())
+        );
+        // Only before the current instruction
+        assert_eq!(
+            require_preceding_instruction(&sysvar.info(), &program, &[]),
+            missing
+        );
+        // Not immediately before
+        assert_eq!(
+            require_previous_instruction(&sysvar.info(), &a, &[1]),
+            missing
+        );
+        // Prefix mismatch
+        assert_eq!(
+            require_preceding_instruction(&sysvar.info(), &b, &[4]),
+            missing
+        );
+        assert_eq!(
+            require_previous_instruction(&sysvar.info(), &b, &[3, 4, 5]),
+            missing
+        );
+        assert_eq!(
+            require_preceding_instruction(&sysvar.info(), &[4; 32], &[]),
+            missing
+        );
+
+        // Nothing precedes the first instruction
+        assert_eq!(
+            require_preceding_instruction(&first.info(), &a, &[]),
+            missing
+        );
+        assert_eq!(
+            require_previous_instruction(&first.info(), &a, &[]),
+            missing
+        );
+
+        let mut not_sysvar = MockAccount::new([5; 32], [0; 32], 100, &instructions_data(&ixs, 2));
+        assert!(require_preceding_instruction(&not_sysvar.info(), &a, &[]).is_err());
+    }
+
+    #[test]
+    fn test_fee_payer() {
+        let mut payer = MockAccount::new([1; 32], [0; 32], 100, &[])
+            .signer(true)
+            .writable(true);
+        let mut authority = MockAccount::new([2; 32], [0; 32], 100, &[]).signer(true);
+
+        let infos = [payer.info(), authority.info()];
+        let accounts = as_account_infos(&infos);
+        assert_eq!(fee_payer(accounts).map(AccountInfo::key), Ok(&[1; 32]));
+        assert!(matches!(
+            fee_payer(&accounts[1..]),
+            Err(ProgramError::Custom(ERROR_FEE_PAYER_UNKNOWN))
+        ));
+        assert!(matches!(
+            fee_payer(&[]),
+            Err(ProgramError::NotEnoughAccountKeys)
+        ));
+
+        assert!(Validation::default()
+            .is_fee_payer()
+            .run(&accounts[0])
+            .is_ok());
+        assert_eq!(
+            Validation::default().is_fee_payer().run(&accounts[1]),
+            Err(ProgramError::Custom(ERROR_FEE_PAYER_UNKNOWN))
+        );
+    }
+}
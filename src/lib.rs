@@ -2,22 +2,167 @@
 #![allow(unexpected_cfgs)]
 
 pub extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+mod access_log;
 mod accounts;
+mod allocator;
+#[cfg(feature = "alt-bn128")]
+mod alt_bn128;
+mod anchor;
+mod args;
+mod auction;
+#[cfg(all(feature = "bench", not(target_os = "solana")))]
+mod bench;
+#[cfg(feature = "build")]
+mod build;
+mod circuit_breaker;
+#[cfg(feature = "client")]
+mod client;
+mod cluster;
+#[cfg(feature = "client")]
+mod codegen;
+mod compute;
 mod consts;
+mod cpi;
+mod crank;
+mod curve;
 mod deserialize;
+#[cfg(feature = "client")]
+mod dump;
+mod errors;
+mod format;
+mod framing;
+#[cfg(feature = "std")]
+mod golden;
+mod guards;
+mod heap;
 mod instructions;
+mod interest;
+#[cfg(feature = "solana-program")]
+mod interop;
+mod introspection;
 mod keccak;
 mod logging;
 pub mod macros;
+mod mem;
+mod metadata;
+#[cfg(any(feature = "build", feature = "client"))]
+mod offchain;
+#[cfg(any(feature = "pyth", feature = "switchboard"))]
+mod oracle;
+mod panic_context;
+mod permit;
+mod pod_enum;
+#[cfg(feature = "poseidon")]
+mod poseidon;
+mod queue;
+mod random;
+mod rate_limit;
+mod ratio;
+mod registry;
+#[cfg(feature = "client")]
+mod resolver;
+mod rewards;
+mod schema;
+mod scratch;
+mod seeds;
+mod selection;
+mod signer;
+mod singleton;
+mod snapshot;
+mod stack_vec;
+mod sysvars;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+mod ticks;
+mod token;
+#[cfg(feature = "client")]
+mod transaction;
+mod twap;
 mod uint;
+mod user_record;
 mod utils;
+mod vesting;
+mod wormhole;
 
+pub use access_log::*;
 pub use accounts::*;
+pub use allocator::*;
+#[cfg(feature = "alt-bn128")]
+pub use alt_bn128::*;
+pub use anchor::*;
+pub use args::*;
+pub use auction::*;
+#[cfg(all(feature = "bench", not(target_os = "solana")))]
+pub use bench::*;
+#[cfg(feature = "build")]
+pub use build::*;
+pub use circuit_breaker::*;
+#[cfg(feature = "client")]
+pub use client::*;
+pub use cluster::*;
+#[cfg(feature = "client")]
+pub use codegen::*;
+pub use compute::*;
 pub use consts::*;
+pub use cpi::*;
+pub use crank::*;
+pub use curve::*;
 pub use deserialize::*;
+#[cfg(feature = "client")]
+pub use dump::*;
+pub use errors::*;
+pub use format::*;
+pub use framing::*;
+#[cfg(feature = "std")]
+pub use golden::*;
+pub use guards::*;
+pub use heap::*;
 pub use instructions::*;
+pub use interest::*;
+#[cfg(feature = "solana-program")]
+pub use interop::*;
+pub use introspection::*;
 pub use keccak::*;
 pub use logging::*;
+pub use mem::*;
+pub use metadata::*;
+#[cfg(any(feature = "build", feature = "client"))]
+pub use offchain::*;
+#[cfg(any(feature = "pyth", feature = "switchboard"))]
+pub use oracle::*;
+pub use panic_context::*;
+pub use permit::*;
+#[cfg(feature = "poseidon")]
+pub use poseidon::*;
+pub use queue::*;
+pub use random::*;
+pub use rate_limit::*;
+pub use ratio::*;
+pub use registry::*;
+#[cfg(feature = "client")]
+pub use resolver::*;
+pub use rewards::*;
+pub use schema::*;
+pub use scratch::*;
+pub use seeds::*;
+pub use selection::*;
+pub use signer::*;
+pub use singleton::*;
+pub use snapshot::*;
+pub use stack_vec::*;
+pub use sysvars::*;
+#[cfg(any(test, feature = "testing"))]
+pub use testing::*;
+pub use ticks::*;
+pub use token::*;
+#[cfg(feature = "client")]
+pub use transaction::*;
+pub use twap::*;
 pub use uint::*;
+pub use user_record::*;
 pub use utils::*;
+pub use vesting::*;
+pub use wormhole::*;
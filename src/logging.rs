@@ -1,5 +1,5 @@
 use core::panic::Location;
-use pinocchio::program_error::ProgramError;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
 use pinocchio_log::log;
 
 /// Logs the call trace and returns the error.
@@ -10,6 +10,27 @@ pub fn trace(msg: &str, error: ProgramError) -> ProgramError {
     error
 }
 
+/// Same as [`trace`], but also logs both keys of a failed key comparison.
+#[track_caller]
+pub fn trace_keys(msg: &str, left: &Pubkey, right: &Pubkey, error: ProgramError) -> ProgramError {
+    let error = trace(msg, error);
+    pinocchio::pubkey::log(left);
+    pinocchio::pubkey::log(right);
+    error
+}
+
+/// Writes a typed diagnostic `payload` into return data and returns `error`, so
+/// failed simulations expose machine-readable details (e.g. required vs provided).
+///
+/// ```ignore
+/// return Err(abort_with(MyError::SlippageExceeded.into(), &SlippageDetails { min_out, out }));
+/// ```
+#[track_caller]
+pub fn abort_with<T: Loggable>(error: ProgramError, payload: &T) -> ProgramError {
+    payload.log_return();
+    error
+}
+
 /// Supports logging.
 pub trait Loggable {
     fn log(&self);
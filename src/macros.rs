@@ -44,14 +44,89 @@ macro_rules! impl_to_bytes_mut {
 
 #[macro_export]
 macro_rules! account {
-    ($discriminator_name:ident, $struct_name:ident) => {
+    // Every arm takes a trailing `unaligned = true` for types read from data that
+    // may not be aligned, see `Account::UNALIGNED`.
+    //
+    // PDA accounts listing their seeds, byte string literals or fields whose type is
+    // `AsRef<[u8]>` (e.g. `Pubkey`, `[u8; N]` or pods), so signing always uses the stored values:
+    // `seeds = [b"vault", owner, index]`.
+    ($discriminator_name:ident, $struct_name:ident, bump = $bump:ident, seeds = [$($seed:tt),+ $(,)?] $(, unaligned = $unaligned:literal)?) => {
+        $crate::account!($discriminator_name, $struct_name, bump = $bump $(, unaligned = $unaligned)?);
+
+        impl $struct_name {
+            /// Seeds of the account's PDA, bump excluded.
+            #[inline(always)]
+            pub fn pda_seeds(&self) -> [&[u8]; 0 $(+ $crate::account!(@one $seed))+] {
+                [$($crate::account!(@seed self $seed)),+]
+            }
+
+            /// Signer seeds for `invoke_signed`, with the stored bump.
+            #[inline(always)]
+            pub fn signer_seeds(&self) -> $crate::PdaSigner<'_, { 0 $(+ $crate::account!(@one $seed))+ }> {
+                $crate::PdaSigner::new(self.pda_seeds(), self.$bump)
+            }
+        }
+    };
+    ($discriminator_name:ident, $struct_name:ident, bump = $bump:ident $(, unaligned = $unaligned:literal)?) => {
+        $crate::account!($discriminator_name, $struct_name $(, unaligned = $unaligned)?);
+
+        impl $crate::SavedBump for $struct_name {
+            #[inline(always)]
+            fn bump(&self) -> u8 {
+                self.$bump
+            }
+        }
+    };
+    // Namespaced accounts store `$namespace` as the second byte, right after the discriminator,
+    // so they can't also store their bump there.
+    ($discriminator_name:ident, $struct_name:ident, namespace = $namespace:expr, bump = $($rest:tt)*) => {
+        compile_error!(
+            "`namespace` and `bump` can't be combined: the namespace is stored at byte 1, where PDA accounts keep their bump"
+        );
+    };
+    ($discriminator_name:ident, $struct_name:ident, namespace = $namespace:expr $(, unaligned = $unaligned:literal)?) => {
+        $crate::account!(@impl $struct_name, $discriminator_name::$struct_name as u8, Some($namespace), None, [$($unaligned)?]);
+    };
+    // Foreign discriminator schemes, e.g. `discriminator = [0x21, 0x0b, 0x31, 0x62, 0xb5, 0x65, 0xb1, 0x0d]`
+    // for an 8 byte Anchor discriminator. The account struct must start with these bytes.
+    ($struct_name:ident, discriminator = [$($byte:expr),+ $(,)?] $(, unaligned = $unaligned:literal)?) => {
+        $crate::account!(@impl $struct_name, [$($byte),+][0], None, Some(&[$($byte),+]), [$($unaligned)?]);
+    };
+    ($discriminator_name:ident, $struct_name:ident $(, unaligned = $unaligned:literal)?) => {
+        $crate::account!(@impl $struct_name, $discriminator_name::$struct_name as u8, None, None, [$($unaligned)?]);
+    };
+    (@flag) => {
+        false
+    };
+    (@flag $flag:literal) => {
+        $flag
+    };
+    (@one $seed:tt) => {
+        1
+    };
+    (@seed $s:ident $lit:literal) => {
+        &$lit[..]
+    };
+    (@seed $s:ident $field:ident) => {
+        core::convert::AsRef::<[u8]>::as_ref(&$s.$field)
+    };
+    (@impl $struct_name:ident, $discriminator:expr, $namespace:expr, $bytes:expr, [$($unaligned:literal)?]) => {
         $crate::impl_to_bytes!($struct_name);
-        impl $crate::Account for $struct_name {}
-        
+        impl $crate::Account for $struct_name {
+            const UNALIGNED: bool = $crate::account!(@flag $($unaligned)?);
+        }
+
         impl $crate::Discriminator for $struct_name {
+            const DISCRIMINATOR_BYTES: Option<&'static [u8]> = $bytes;
+
             #[inline(always)]
             fn discriminator() -> u8 {
-                $discriminator_name::$struct_name as u8
+                $discriminator
+            }
+
+            #[inline(always)]
+            fn namespace() -> Option<u8> {
+                $namespace
             }
         }
 
@@ -141,6 +216,53 @@ macro_rules! error {
     };
 }
 
+/// Declare an error enum with its messages and a [`ErrorEntry`](crate::ErrorEntry)
+/// catalog, implementing `From<_> for ProgramError` like [`error!`].
+///
+/// ```ignore
+/// errors! {
+///     pub enum MyError {
+///         InsufficientFunds = 6000 => "Insufficient funds",
+///         Expired = 6001 => "Offer expired",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! errors {
+    ($vis:vis enum $name:ident { $($variant:ident = $code:literal => $message:literal),* $(,)? }) => {
+        #[repr(u32)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        $vis enum $name {
+            $($variant = $code,)*
+        }
+
+        impl $name {
+            /// All variants, for off-chain error code lookup.
+            pub const CATALOG: &'static [$crate::ErrorEntry] = &[
+                $($crate::ErrorEntry {
+                    code: $code,
+                    name: stringify!($variant),
+                    message: $message,
+                },)*
+            ];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)*
+                }
+            }
+
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $message,)*
+                }
+            }
+        }
+
+        $crate::error!($name);
+    };
+}
+
 /// Declare a log-gable event struct.
 #[macro_export]
 macro_rules! event {
@@ -160,8 +282,67 @@ macro_rules! event {
 
 #[macro_export]
 macro_rules! instruction {
-    ($discriminator_name:ident, $struct_name:ident) => {
-        impl $crate::Instruction for $struct_name {}
+    // Every arm takes a trailing `unaligned = true`, see `Instruction::UNALIGNED`.
+    //
+    // Expected accounts, in order, with the modes of `cpi_interface!`. Generates the
+    // `$accounts_name` struct of account keys building the instruction's `AccountMeta`s:
+    //   `accounts DepositAccounts { user: writable_signer, vault: writable }`
+    ($discriminator_name:ident, $struct_name:ident, accounts $accounts_name:ident {
+        $($account:ident : $mode:ident),* $(,)?
+    } $(, unaligned = $unaligned:literal)?) => {
+        $crate::instruction!($discriminator_name, $struct_name, validate {}, accounts $accounts_name {
+            $($account: $mode),*
+        } $(, unaligned = $unaligned)?);
+    };
+    ($discriminator_name:ident, $struct_name:ident, validate {
+        $($field:ident : $rule:ident $(($($arg:tt)*))?),* $(,)?
+    }, accounts $accounts_name:ident {
+        $($account:ident : $mode:ident),* $(,)?
+    } $(, unaligned = $unaligned:literal)?) => {
+        $crate::instruction!($discriminator_name, $struct_name, validate {
+            $($field: $rule $(($($arg)*))?),*
+        } $(, unaligned = $unaligned)?);
+
+        impl $crate::InstructionAccounts for $struct_name {
+            const ACCOUNTS: &'static [$crate::AccountSpec] =
+                &[$($crate::AccountSpec::$mode(stringify!($account))),*];
+        }
+
+        /// Account keys of the instruction, in the order the program expects them.
+        pub struct $accounts_name<'a> {
+            $(pub $account: &'a pinocchio::pubkey::Pubkey,)*
+        }
+
+        impl<'a> $accounts_name<'a> {
+            /// Number of accounts.
+            pub const LEN: usize = 0 $(+ $crate::instruction!(@one $account))*;
+
+            /// Account metas, flagged like the instruction's `InstructionAccounts::ACCOUNTS`.
+            #[inline(always)]
+            pub fn metas(
+                &self,
+            ) -> [pinocchio::instruction::AccountMeta<'a>; 0 $(+ $crate::instruction!(@one $account))*] {
+                [$(pinocchio::instruction::AccountMeta::$mode(self.$account)),*]
+            }
+        }
+    };
+    ($discriminator_name:ident, $struct_name:ident $(, unaligned = $unaligned:literal)?) => {
+        $crate::instruction!($discriminator_name, $struct_name, validate {} $(, unaligned = $unaligned)?);
+    };
+    // Per-field argument rules, checked by `Instruction::validate`:
+    //   `non_zero`, `range(1..=10_000)`, `valid_pubkey`
+    ($discriminator_name:ident, $struct_name:ident, validate {
+        $($field:ident : $rule:ident $(($($arg:tt)*))?),* $(,)?
+    } $(, unaligned = $unaligned:literal)?) => {
+        impl $crate::Instruction for $struct_name {
+            const UNALIGNED: bool = $crate::account!(@flag $($unaligned)?);
+
+            #[track_caller]
+            fn validate(&self) -> pinocchio::ProgramResult {
+                $($crate::instruction!(@rule self.$field, stringify!($field), $rule $(($($arg)*))?)?;)*
+                Ok(())
+            }
+        }
 
         impl $crate::Discriminator for $struct_name {
             #[inline(always)]
@@ -172,19 +353,309 @@ macro_rules! instruction {
 
         // Compared to a standard "to_bytes" impl add a header with discriminator
         impl $struct_name {
+            /// Serialized length, discriminator included.
+            pub const LEN: usize = 1 + core::mem::size_of::<Self>();
+
             pub fn to_bytes(&self) -> $crate::alloc::vec::Vec<u8> {
-                unsafe {
-                    [
-                        [$discriminator_name::$struct_name as u8].to_vec(),
-                        core::slice::from_raw_parts(
-                            self as *const _ as *const u8,
-                            core::mem::size_of::<Self>(),
-                        )
-                        .to_vec(),
-                    ]
-                    .concat()
+                let mut bytes = $crate::alloc::vec![0; Self::LEN];
+                self.write_to(&mut bytes);
+                bytes
+            }
+
+            /// Serializes into `buf` without allocating, returns the number of bytes written.
+            ///
+            /// # Panics
+            /// If `buf` is shorter than [`Self::LEN`].
+            #[inline(always)]
+            pub fn write_to(&self, buf: &mut [u8]) -> usize {
+                buf[0] = $discriminator_name::$struct_name as u8;
+                // SAFETY:
+                // 1. `self` lives as long as the source slice,
+                // 2. length is exactly `size_of::<Self>()`.
+                buf[1..Self::LEN].copy_from_slice(unsafe {
+                    core::slice::from_raw_parts(
+                        self as *const _ as *const u8,
+                        core::mem::size_of::<Self>(),
+                    )
+                });
+                Self::LEN
+            }
+        }
+    };
+    (@one $account:ident) => {
+        1
+    };
+    (@rule $value:expr, $name:expr, non_zero) => {
+        $crate::check_arg_non_zero(&$value, $name)
+    };
+    (@rule $value:expr, $name:expr, range($($range:tt)*)) => {
+        $crate::check_arg_range(&$value, $($range)*, $name)
+    };
+    (@rule $value:expr, $name:expr, valid_pubkey) => {
+        $crate::check_arg_pubkey(&$value, $name)
+    };
+}
+
+/// Declare a PDA seed schema once and derive everything else from it.
+///
+/// Seeds are either byte string literals or typed fields implementing
+/// [`SeedBytes`](crate::SeedBytes), in the order they are hashed:
+///
+/// ```ignore
+/// seeds!(pub struct VaultSeeds { b"vault", owner: Pubkey, index: u64 });
+///
+/// let seeds = VaultSeeds::new(*owner.key(), 7);
+/// let (pda, bump) = seeds.find_program_address(&crate::ID);
+/// seeds.validate(vault_info, &crate::ID, bump)?;
+/// seeds.signer(bump).with_signer(|signers| cpi.invoke_signed(signers))?;
+/// ```
+#[macro_export]
+macro_rules! seeds {
+    ($vis:vis struct $name:ident { $($body:tt)* }) => {
+        $crate::seeds!(@parse $vis $name [] [] $($body)*);
+    };
+
+    // Literal seed
+    (@parse $vis:vis $name:ident [$($fields:tt)*] [$($seeds:tt)*] $lit:literal $(, $($rest:tt)*)?) => {
+        $crate::seeds!(@parse $vis $name [$($fields)*] [$($seeds)* (lit $lit)] $($($rest)*)?);
+    };
+
+    // Typed seed
+    (@parse $vis:vis $name:ident [$($fields:tt)*] [$($seeds:tt)*] $field:ident : $ty:ty $(, $($rest:tt)*)?) => {
+        $crate::seeds!(@parse $vis $name [$($fields)* ($field: $ty)] [$($seeds)* (field $field)] $($($rest)*)?);
+    };
+
+    (@parse $vis:vis $name:ident [$(($field:ident : $ty:ty))*] [$(($kind:ident $seed:tt))*]) => {
+        $vis struct $name {
+            $(pub $field: <$ty as $crate::SeedBytes>::Bytes,)*
+        }
+
+        // Reject invalid seed schemas at compile time instead of with `InvalidSeeds`
+        const _: () = {
+            assert!(
+                0 $(+ $crate::seeds!(@one $kind))* < pinocchio::pubkey::MAX_SEEDS,
+                "too many seeds, the bump needs one more"
+            );
+            $(assert!(
+                $crate::seeds!(@len $kind $seed) <= pinocchio::pubkey::MAX_SEED_LEN,
+                "seed is longer than MAX_SEED_LEN"
+            );)*
+            $(assert!(
+                core::mem::size_of::<<$ty as $crate::SeedBytes>::Bytes>()
+                    <= pinocchio::pubkey::MAX_SEED_LEN,
+                "seed is longer than MAX_SEED_LEN"
+            );)*
+        };
+
+        impl $name {
+            /// Number of seeds, bump excluded.
+            pub const LEN: usize = 0 $(+ $crate::seeds!(@one $kind))*;
+
+            #[inline(always)]
+            pub fn new($($field: $ty),*) -> Self {
+                Self {
+                    $($field: $crate::SeedBytes::seed_bytes(&$field),)*
                 }
             }
+
+            /// Seeds in derivation order, bump excluded.
+            #[inline(always)]
+            pub fn seeds(&self) -> [&[u8]; 0 $(+ $crate::seeds!(@one $kind))*] {
+                [$($crate::seeds!(@ref self $kind $seed)),*]
+            }
+
+            /// Finds the canonical PDA and bump.
+            #[inline(always)]
+            pub fn find_program_address(
+                &self,
+                program_id: &pinocchio::pubkey::Pubkey,
+            ) -> (pinocchio::pubkey::Pubkey, u8) {
+                pinocchio::pubkey::find_program_address(&self.seeds(), program_id)
+            }
+
+            /// Signer seeds for `invoke_signed`.
+            #[inline(always)]
+            pub fn signer(
+                &self,
+                bump: u8,
+            ) -> $crate::PdaSigner<'_, { 0 $(+ $crate::seeds!(@one $kind))* }> {
+                $crate::PdaSigner::new(self.seeds(), bump)
+            }
+
+            /// Runs `Validation::has_seeds_with_bump` against `ai`.
+            #[inline(always)]
+            pub fn validate(
+                &self,
+                ai: &pinocchio::account_info::AccountInfo,
+                program_id: &pinocchio::pubkey::Pubkey,
+                bump: u8,
+            ) -> pinocchio::ProgramResult {
+                $crate::Validation::default()
+                    .has_seeds_with_bump(&self.seeds(), program_id, bump)
+                    .run(ai)
+            }
         }
     };
+
+    (@one $kind:ident) => {
+        1
+    };
+    // Typed seeds are checked through their `SeedBytes::Bytes` size
+    (@len lit $lit:literal) => {
+        $lit.len()
+    };
+    (@len field $field:ident) => {
+        0
+    };
+    (@ref $s:ident lit $lit:literal) => {
+        &$lit[..]
+    };
+    (@ref $s:ident field $field:ident) => {
+        core::convert::AsRef::<[u8]>::as_ref(&$s.$field)
+    };
+}
+
+/// Declare a typed CPI caller for an instruction of another pinsteel program.
+///
+/// The argument struct must be declared with [`instruction!`] in the callee crate,
+/// accounts are listed in the order the callee expects them, each with its
+/// `AccountMeta` constructor: `readonly`, `writable`, `readonly_signer` or
+/// `writable_signer`.
+///
+/// ```ignore
+/// cpi_interface!(pub struct DepositCpi => vault_program::Deposit {
+///     user: writable_signer,
+///     vault: writable,
+///     system_program: readonly,
+/// });
+///
+/// DepositCpi {
+///     program_id: &vault_program::ID,
+///     user,
+///     vault,
+///     system_program,
+///     args: &Deposit { amount },
+/// }
+/// .invoke()?;
+/// ```
+#[macro_export]
+macro_rules! cpi_interface {
+    ($vis:vis struct $name:ident => $args:ty { $($account:ident : $mode:ident),* $(,)? }) => {
+        $vis struct $name<'a> {
+            /// Callee program ID.
+            pub program_id: &'a pinocchio::pubkey::Pubkey,
+            $(pub $account: &'a pinocchio::account_info::AccountInfo,)*
+            /// Instruction arguments.
+            pub args: &'a $args,
+        }
+
+        impl $name<'_> {
+            #[inline(always)]
+            pub fn invoke(&self) -> pinocchio::ProgramResult {
+                self.invoke_signed(&[])
+            }
+
+            pub fn invoke_signed(
+                &self,
+                signers: &[pinocchio::instruction::Signer],
+            ) -> pinocchio::ProgramResult {
+                let mut data = [0u8; <$args>::LEN];
+                self.args.write_to(&mut data);
+
+                let accounts = [$($crate::cpi_interface!(@meta self.$account, $mode)),*];
+                let instruction = pinocchio::instruction::Instruction {
+                    program_id: self.program_id,
+                    accounts: &accounts,
+                    data: &data,
+                };
+
+                pinocchio::cpi::invoke_signed(&instruction, &[$(self.$account),*], signers)
+            }
+        }
+    };
+    (@meta $account:expr, readonly) => {
+        pinocchio::instruction::AccountMeta::readonly($account.key())
+    };
+    (@meta $account:expr, writable) => {
+        pinocchio::instruction::AccountMeta::writable($account.key())
+    };
+    (@meta $account:expr, readonly_signer) => {
+        pinocchio::instruction::AccountMeta::readonly_signer($account.key())
+    };
+    (@meta $account:expr, writable_signer) => {
+        pinocchio::instruction::AccountMeta::writable_signer($account.key())
+    };
+}
+
+/// Returns a traced error, logging both keys, unless the two `&Pubkey`s are equal.
+///
+/// The error defaults to `ProgramError::InvalidArgument`.
+///
+/// ```ignore
+/// assert_keys_eq!(vault.key(), &config.vault);
+/// assert_keys_eq!(mint.key(), &config.mint, MyError::WrongMint);
+/// ```
+#[macro_export]
+macro_rules! assert_keys_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_keys_eq!(
+            $left,
+            $right,
+            pinocchio::program_error::ProgramError::InvalidArgument
+        )
+    };
+    ($left:expr, $right:expr, $error:expr $(,)?) => {{
+        let (left, right): (&pinocchio::pubkey::Pubkey, &pinocchio::pubkey::Pubkey) =
+            ($left, $right);
+        if !$crate::pubkey_eq(left, right) {
+            return Err($crate::trace_keys(
+                concat!(
+                    "Keys differ: ",
+                    stringify!($left),
+                    " != ",
+                    stringify!($right)
+                ),
+                left,
+                right,
+                $error.into(),
+            )
+            .into());
+        }
+    }};
+}
+
+/// Returns a traced error, logging both keys, if the two `&Pubkey`s are equal.
+///
+/// The error defaults to `ProgramError::InvalidArgument`.
+///
+/// ```ignore
+/// assert_keys_neq!(from.key(), to.key());
+/// ```
+#[macro_export]
+macro_rules! assert_keys_neq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_keys_neq!(
+            $left,
+            $right,
+            pinocchio::program_error::ProgramError::InvalidArgument
+        )
+    };
+    ($left:expr, $right:expr, $error:expr $(,)?) => {{
+        let (left, right): (&pinocchio::pubkey::Pubkey, &pinocchio::pubkey::Pubkey) =
+            ($left, $right);
+        if $crate::pubkey_eq(left, right) {
+            return Err($crate::trace_keys(
+                concat!(
+                    "Keys are equal: ",
+                    stringify!($left),
+                    " == ",
+                    stringify!($right)
+                ),
+                left,
+                right,
+                $error.into(),
+            )
+            .into());
+        }
+    }};
 }
@@ -0,0 +1,78 @@
+//! Slice utilities backed by the `sol_mem*` syscalls, cheaper than core's byte
+//! loops for large buffers under SBF.
+
+use core::cmp::Ordering;
+
+#[cfg(target_os = "solana")]
+extern "C" {
+    fn sol_memcpy_(dst: *mut u8, src: *const u8, n: u64);
+    fn sol_memcmp_(s1: *const u8, s2: *const u8, n: u64, result: *mut i32);
+    fn sol_memset_(s: *mut u8, c: u8, n: u64);
+}
+
+/// Lexicographically compares `a` and `b`, like `a.cmp(b)`.
+#[inline(always)]
+pub fn fast_memcmp(a: &[u8], b: &[u8]) -> Ordering {
+    let n = a.len().min(b.len());
+
+    #[cfg(target_os = "solana")]
+    let prefix = {
+        let mut result = 0i32;
+        unsafe { sol_memcmp_(a.as_ptr(), b.as_ptr(), n as u64, &mut result) };
+        result.cmp(&0)
+    };
+
+    #[cfg(not(target_os = "solana"))]
+    let prefix = a[..n].cmp(&b[..n]);
+
+    prefix.then(a.len().cmp(&b.len()))
+}
+
+/// Copies `src` into `dst`.
+///
+/// # Panics
+/// If the slices have different lengths, like `copy_from_slice`.
+#[inline(always)]
+pub fn fast_memcpy(dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "fast_memcpy length mismatch");
+
+    #[cfg(target_os = "solana")]
+    // SAFETY: lengths are equal and `&mut` guarantees the slices don't overlap
+    unsafe {
+        sol_memcpy_(dst.as_mut_ptr(), src.as_ptr(), src.len() as u64)
+    };
+
+    #[cfg(not(target_os = "solana"))]
+    dst.copy_from_slice(src);
+}
+
+/// Sets every byte of `buf` to `value`, e.g. to zeroize account data.
+#[inline(always)]
+pub fn fast_memset(buf: &mut [u8], value: u8) {
+    #[cfg(target_os = "solana")]
+    // SAFETY: writes exactly `buf.len()` bytes
+    unsafe {
+        sol_memset_(buf.as_mut_ptr(), value, buf.len() as u64)
+    };
+
+    #[cfg(not(target_os = "solana"))]
+    buf.fill(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_mem() {
+        assert_eq!(fast_memcmp(b"abc", b"abc"), Ordering::Equal);
+        assert_eq!(fast_memcmp(b"abc", b"abd"), Ordering::Less);
+        assert_eq!(fast_memcmp(b"abc", b"ab"), Ordering::Greater);
+
+        let mut buf = [0u8; 4];
+        fast_memcpy(&mut buf, b"wxyz");
+        assert_eq!(&buf, b"wxyz");
+        fast_memset(&mut buf[1..], 0);
+        assert_eq!(buf, [b'w', 0, 0, 0]);
+    }
+}
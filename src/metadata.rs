@@ -0,0 +1,330 @@
+//! Helpers for Metaplex Token Metadata accounts.
+//!
+//! Provides PDA derivation for `Metadata` and `MasterEdition` accounts and a
+//! minimal, allocation-free reader over the borsh-encoded `Metadata` account.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+
+use crate::{trace, TOKEN_METADATA_PROGRAM_ID};
+
+/// Seed prefix shared by all Token Metadata PDAs.
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// Seed suffix of the `MasterEdition` PDA.
+pub const EDITION_SEED: &[u8] = b"edition";
+
+/// `Key::MetadataV1` account tag.
+pub const METADATA_V1_KEY: u8 = 4;
+
+/// Size of a serialized `Creator` (address + verified + share).
+const CREATOR_LEN: usize = 32 + 1 + 1;
+
+/// Derives the `Metadata` PDA of a mint.
+#[inline(always)]
+pub fn metadata_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(
+        &[METADATA_SEED, TOKEN_METADATA_PROGRAM_ID.as_ref(), mint],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Derives the `MasterEdition` PDA of a mint.
+#[inline(always)]
+pub fn master_edition_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    find_program_address(
+        &[
+            METADATA_SEED,
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint,
+            EDITION_SEED,
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Creator entry of a `Metadata` account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Creator<'a> {
+    pub address: &'a Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+/// Collection entry of a `Metadata` account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Collection<'a> {
+    pub verified: bool,
+    pub key: &'a Pubkey,
+}
+
+/// Read-only view over a borsh-encoded `Metadata` account.
+///
+/// Only the fields commonly needed on-chain are decoded. String fields are
+/// returned as raw bytes with Metaplex's null padding trimmed.
+pub struct MetadataView<'a> {
+    pub update_authority: &'a Pubkey,
+    pub mint: &'a Pubkey,
+    pub name: &'a [u8],
+    pub symbol: &'a [u8],
+    pub uri: &'a [u8],
+    pub seller_fee_basis_points: u16,
+    creators: Option<&'a [u8]>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub collection: Option<Collection<'a>>,
+}
+
+impl<'a> MetadataView<'a> {
+    /// Parses `Metadata` account data.
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let mut r = Reader { data };
+
+        if r.u8()? != METADATA_V1_KEY {
+            return Err(trace(
+                "Metadata has wrong key",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+
+        let update_authority = r.pubkey()?;
+        let mint = r.pubkey()?;
+        let name = r.string()?;
+        let symbol = r.string()?;
+        let uri = r.string()?;
+        let seller_fee_basis_points = u16::from_le_bytes(r.array::<2>()?);
+        let creators = if r.bool()? {
+            let count = u32::from_le_bytes(r.array::<4>()?) as usize;
+            let len = count
+                .checked_mul(CREATOR_LEN)
+                .ok_or(ProgramError::InvalidAccountData)?;
+            Some(r.take(len)?)
+        } else {
+            None
+        };
+        let primary_sale_happened = r.bool()?;
+        let is_mutable = r.bool()?;
+
+        // edition_nonce: Option<u8>
+        if r.bool()? {
+            r.take(1)?;
+        }
+        // token_standard: Option<u8>
+        if r.bool()? {
+            r.take(1)?;
+        }
+        // collection: Option<Collection>, older accounts may end before this field
+        let collection = if !r.data.is_empty() && r.bool()? {
+            let verified = r.bool()?;
+            let key = r.pubkey()?;
+            Some(Collection { verified, key })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            update_authority,
+            mint,
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points,
+            creators,
+            primary_sale_happened,
+            is_mutable,
+            collection,
+        })
+    }
+
+    /// Iterates over the creators list, if present.
+    pub fn creators(&self) -> impl Iterator<Item = Creator<'a>> {
+        self.creators
+            .unwrap_or(&[])
+            .chunks_exact(CREATOR_LEN)
+            .map(|c| Creator {
+                // SAFETY: chunk is exactly `CREATOR_LEN` bytes long
+                address: c[..32].try_into().unwrap(),
+                verified: c[32] != 0,
+                share: c[33],
+            })
+    }
+
+    /// Returns the verified collection key, if any.
+    #[inline(always)]
+    pub fn verified_collection(&self) -> Option<&'a Pubkey> {
+        match self.collection {
+            Some(Collection {
+                verified: true,
+                key,
+            }) => Some(key),
+            _ => None,
+        }
+    }
+}
+
+/// Asserts that `metadata` is the Token Metadata account of `mint` and that it
+/// belongs to the verified collection `collection_mint`.
+///
+/// Metadata PDA derivation is skipped, ownership by the Token Metadata program
+/// together with the stored mint is sufficient to bind the account to the mint.
+pub fn assert_verified_collection(
+    metadata: &AccountInfo,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> ProgramResult {
+    if !metadata.is_owned_by(&TOKEN_METADATA_PROGRAM_ID) {
+        return Err(trace(
+            "Metadata has wrong owner",
+            ProgramError::InvalidAccountOwner,
+        ));
+    }
+
+    let data = metadata.try_borrow_data()?;
+    let view = MetadataView::try_from_bytes(&data)?;
+
+    if view.mint.ne(mint) {
+        return Err(trace(
+            "Metadata has wrong mint",
+            ProgramError::InvalidAccountData,
+        ));
+    }
+
+    match view.verified_collection() {
+        Some(key) if key.eq(collection_mint) => Ok(()),
+        _ => Err(trace(
+            "Metadata is not in verified collection",
+            ProgramError::InvalidAccountData,
+        )),
+    }
+}
+
+/// Minimal borsh cursor.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[inline(always)]
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        if self.data.len() < len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    #[inline(always)]
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ProgramError> {
+        // SAFETY: `take` returns exactly N bytes
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.take(1)?[0])
+    }
+
+    #[inline(always)]
+    fn bool(&mut self) -> Result<bool, ProgramError> {
+        Ok(self.u8()? != 0)
+    }
+
+    #[inline(always)]
+    fn pubkey(&mut self) -> Result<&'a Pubkey, ProgramError> {
+        // SAFETY: `take` returns exactly 32 bytes
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn string(&mut self) -> Result<&'a [u8], ProgramError> {
+        let len = u32::from_le_bytes(self.array::<4>()?) as usize;
+        let bytes = self.take(len)?;
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(len);
+        Ok(&bytes[..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn push_string(buf: &mut Vec<u8>, s: &[u8], pad: usize) {
+        buf.extend_from_slice(&(pad as u32).to_le_bytes());
+        buf.extend_from_slice(s);
+        buf.resize(buf.len() + pad - s.len(), 0);
+    }
+
+    fn sample(collection: Option<(bool, Pubkey)>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.push(METADATA_V1_KEY);
+        buf.extend_from_slice(&[1; 32]);
+        buf.extend_from_slice(&[2; 32]);
+        push_string(&mut buf, b"Ape", 32);
+        push_string(&mut buf, b"APE", 10);
+        push_string(&mut buf, b"https://x", 200);
+        buf.extend_from_slice(&500u16.to_le_bytes());
+        buf.push(1);
+        buf.extend_from_slice(&2u32.to_le_bytes());
+        buf.extend_from_slice(&[3; 32]);
+        buf.extend_from_slice(&[1, 60]);
+        buf.extend_from_slice(&[4; 32]);
+        buf.extend_from_slice(&[0, 40]);
+        buf.extend_from_slice(&[1, 1]);
+        buf.extend_from_slice(&[1, 255]);
+        buf.extend_from_slice(&[1, 0]);
+        match collection {
+            Some((verified, key)) => {
+                buf.extend_from_slice(&[1, verified as u8]);
+                buf.extend_from_slice(&key);
+            }
+            None => buf.push(0),
+        }
+        buf
+    }
+
+    #[test]
+    fn test_metadata_view() {
+        let data = sample(Some((true, [5; 32])));
+        let view = MetadataView::try_from_bytes(&data).unwrap();
+
+        assert_eq!(view.update_authority, &[1; 32]);
+        assert_eq!(view.mint, &[2; 32]);
+        assert_eq!(view.name, b"Ape");
+        assert_eq!(view.symbol, b"APE");
+        assert_eq!(view.uri, b"https://x");
+        assert_eq!(view.seller_fee_basis_points, 500);
+        assert!(view.primary_sale_happened);
+        assert!(view.is_mutable);
+        assert_eq!(view.verified_collection(), Some(&[5; 32]));
+
+        let creators: Vec<_> = view.creators().collect();
+        assert_eq!(creators.len(), 2);
+        assert_eq!(creators[0].address, &[3; 32]);
+        assert!(creators[0].verified);
+        assert_eq!(creators[1].share, 40);
+        assert!(!creators[1].verified);
+    }
+
+    #[test]
+    fn test_metadata_view_unverified_collection() {
+        let data = sample(Some((false, [5; 32])));
+        let view = MetadataView::try_from_bytes(&data).unwrap();
+        assert_eq!(view.verified_collection(), None);
+
+        let data = sample(None);
+        let view = MetadataView::try_from_bytes(&data).unwrap();
+        assert!(view.collection.is_none());
+    }
+
+    #[test]
+    fn test_metadata_view_truncated() {
+        let data = sample(None);
+        assert!(MetadataView::try_from_bytes(&data[..100]).is_err());
+    }
+}
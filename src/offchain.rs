@@ -0,0 +1,41 @@
+//! Host-side PDA derivation, shared by the build script helpers and clients.
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use pinocchio::pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN, PDA_MARKER};
+use sha2::{Digest, Sha256};
+
+/// Host-side `create_program_address`. Returns `None` if the seeds are
+/// invalid or the derived address lies on the ed25519 curve.
+pub fn create_program_address_offchain(seeds: &[&[u8]], program_id: &Pubkey) -> Option<Pubkey> {
+    if seeds.len() > MAX_SEEDS || seeds.iter().any(|s| s.len() > MAX_SEED_LEN) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    for seed in seeds {
+        hasher.update(seed);
+    }
+    hasher.update(program_id);
+    hasher.update(PDA_MARKER);
+    let hash: Pubkey = hasher.finalize().into();
+
+    // A valid PDA must not be a valid ed25519 point
+    if CompressedEdwardsY(hash).decompress().is_some() {
+        return None;
+    }
+    Some(hash)
+}
+
+/// Host-side `find_program_address`.
+pub fn find_program_address_offchain(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+    if seeds.len() >= MAX_SEEDS {
+        return None;
+    }
+
+    (0..=u8::MAX).rev().find_map(|bump| {
+        let bump_seed = [bump];
+        let mut with_bump = seeds.to_vec();
+        with_bump.push(&bump_seed);
+        create_program_address_offchain(&with_bump, program_id).map(|pda| (pda, bump))
+    })
+}
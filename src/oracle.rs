@@ -0,0 +1,417 @@
+//! Readers for oracle price accounts.
+//!
+//! Accounts are parsed directly from their on-chain byte layout, so no oracle
+//! SDK is required. Each oracle is enabled with its own cargo feature.
+
+use pinocchio::{program_error::ProgramError, ProgramResult};
+
+use crate::trace;
+
+pub const ERROR_ORACLE_STALE: u32 = 4;
+pub const ERROR_ORACLE_CONFIDENCE: u32 = 5;
+
+/// Checks that `conf` is within `max_conf_bps` basis points of `value`.
+#[track_caller]
+#[inline(always)]
+fn check_confidence(value: u128, conf: u128, max_conf_bps: u16) -> ProgramResult {
+    if conf.saturating_mul(10_000) > value.saturating_mul(max_conf_bps as u128) {
+        return Err(trace(
+            "Oracle confidence interval is too wide",
+            ProgramError::Custom(ERROR_ORACLE_CONFIDENCE),
+        ));
+    }
+    Ok(())
+}
+
+#[track_caller]
+#[inline(always)]
+fn stale() -> ProgramError {
+    trace(
+        "Oracle price is stale",
+        ProgramError::Custom(ERROR_ORACLE_STALE),
+    )
+}
+
+#[cfg(feature = "pyth")]
+pub use pyth::*;
+
+#[cfg(feature = "pyth")]
+mod pyth {
+    use pinocchio::{
+        account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey,
+        sysvars::clock::Clock, ProgramResult,
+    };
+    use pinocchio_pubkey::pubkey;
+
+    use super::{check_confidence, stale};
+    use crate::trace;
+
+    /// Pyth pull oracle receiver program.
+    pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey =
+        pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+    /// Anchor discriminator of the `PriceUpdateV2` account.
+    pub const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+    /// Price reported by a Pyth `PriceUpdateV2` account.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PythPrice {
+        pub price: i64,
+        pub conf: u64,
+        pub exponent: i32,
+        pub publish_time: i64,
+    }
+
+    impl PythPrice {
+        /// Fails if the confidence interval exceeds `max_conf_bps` of the price.
+        #[track_caller]
+        pub fn check_confidence(&self, max_conf_bps: u16) -> ProgramResult {
+            check_confidence(
+                self.price.unsigned_abs() as u128,
+                self.conf as u128,
+                max_conf_bps,
+            )
+        }
+    }
+
+    /// Decoded Pyth `PriceUpdateV2` account.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PythPriceUpdate {
+        pub write_authority: Pubkey,
+        /// `true` if all Wormhole guardian signatures were verified.
+        pub fully_verified: bool,
+        pub feed_id: [u8; 32],
+        pub price: PythPrice,
+        pub ema_price: PythPrice,
+        pub posted_slot: u64,
+    }
+
+    impl PythPriceUpdate {
+        /// Parses `PriceUpdateV2` account data.
+        pub fn try_from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+            if data.len() < 8 || data[..8] != PYTH_PRICE_UPDATE_DISCRIMINATOR {
+                return Err(trace(
+                    "Price update has wrong discriminator",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+
+            // `VerificationLevel` is a borsh enum: `Partial { num_signatures: u8 }` or `Full`
+            let (fully_verified, body) = match data.get(40) {
+                Some(0) => (false, &data[42..]),
+                Some(1) => (true, &data[41..]),
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+
+            // feed_id (32) + price (8) + conf (8) + exponent (4) + publish_time (8)
+            // + prev_publish_time (8) + ema_price (8) + ema_conf (8) + posted_slot (8)
+            if body.len() < 92 {
+                return Err(trace(
+                    "Price update has wrong length",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+
+            let i64_at = |o: usize| i64::from_le_bytes(body[o..o + 8].try_into().unwrap());
+            let u64_at = |o: usize| u64::from_le_bytes(body[o..o + 8].try_into().unwrap());
+            let exponent = i32::from_le_bytes(body[48..52].try_into().unwrap());
+            let publish_time = i64_at(52);
+
+            Ok(Self {
+                write_authority: data[8..40].try_into().unwrap(),
+                fully_verified,
+                feed_id: body[..32].try_into().unwrap(),
+                price: PythPrice {
+                    price: i64_at(32),
+                    conf: u64_at(40),
+                    exponent,
+                    publish_time,
+                },
+                ema_price: PythPrice {
+                    price: i64_at(68),
+                    conf: u64_at(76),
+                    exponent,
+                    publish_time,
+                },
+                posted_slot: u64_at(84),
+            })
+        }
+
+        /// Loads a `PriceUpdateV2` account owned by the Pyth receiver program.
+        pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+            if !account.is_owned_by(&PYTH_RECEIVER_PROGRAM_ID) {
+                return Err(trace(
+                    "Price update has wrong owner",
+                    ProgramError::InvalidAccountOwner,
+                ));
+            }
+            Self::try_from_bytes(&account.try_borrow_data()?)
+        }
+
+        /// Returns the price of `feed_id` if it was published at most
+        /// `max_age` seconds before `clock` and was fully verified.
+        #[track_caller]
+        pub fn get_price_no_older_than(
+            &self,
+            clock: &Clock,
+            max_age: u64,
+            feed_id: &[u8; 32],
+        ) -> Result<PythPrice, ProgramError> {
+            if !self.fully_verified {
+                return Err(trace(
+                    "Price update is not fully verified",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+            if self.feed_id.ne(feed_id) {
+                return Err(trace(
+                    "Price update has wrong feed id",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+            if self
+                .price
+                .publish_time
+                .saturating_add(max_age.min(i64::MAX as u64) as i64)
+                < clock.unix_timestamp
+            {
+                return Err(stale());
+            }
+            Ok(self.price)
+        }
+    }
+}
+
+#[cfg(feature = "switchboard")]
+pub use switchboard::*;
+
+#[cfg(feature = "switchboard")]
+mod switchboard {
+    use pinocchio::{
+        account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey,
+        sysvars::clock::Clock, ProgramResult,
+    };
+    use pinocchio_pubkey::pubkey;
+
+    use super::{check_confidence, stale};
+    use crate::trace;
+
+    /// Switchboard on-demand program.
+    pub const SWITCHBOARD_PROGRAM_ID: Pubkey =
+        pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+
+    /// Anchor discriminator of the `PullFeedAccountData` account.
+    pub const SWITCHBOARD_PULL_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+    /// Number of decimals of Switchboard result values.
+    pub const SWITCHBOARD_PRECISION: u32 = 18;
+
+    /// Byte offset of `CurrentResult` within the account, discriminator included.
+    ///
+    /// `PullFeedAccountData` of the `switchboard-on-demand` crate is `repr(C)`:
+    /// 8 discriminator bytes, `submissions: [OracleSubmission; 32]` (64 bytes
+    /// each), `authority`, `queue`, `feed_hash` (32 each), `initialized_slot`,
+    /// `permissions`, `max_variance` (8 each), `min_responses: u32`,
+    /// `name: [u8; 32]`, 2 padding bytes, `historical_result_idx` and
+    /// `min_sample_size` (1 each), `last_update_timestamp`, `lut_slot` (8 each)
+    /// and 32 reserved bytes, followed by `result`.
+    const RESULT_OFFSET: usize = 2264;
+
+    /// Size of `CurrentResult`: `value`, `std_dev`, `mean`, `range`,
+    /// `min_value`, `max_value` (`i128` each), `num_samples` at 96, then
+    /// `submission_idx`, 6 padding bytes, and `slot`, `min_slot`, `max_slot`
+    /// from 104.
+    const RESULT_LEN: usize = 128;
+
+    /// Latest aggregated result of a Switchboard pull feed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SwitchboardResult {
+        /// Median value, scaled by `10^SWITCHBOARD_PRECISION`.
+        pub value: i128,
+        /// Standard deviation of the samples, same scale as `value`.
+        pub std_dev: i128,
+        pub num_samples: u8,
+        /// Slot at which the result was produced.
+        pub slot: u64,
+    }
+
+    impl SwitchboardResult {
+        /// Parses `PullFeedAccountData` account data.
+        pub fn try_from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+            if data.len() < RESULT_OFFSET + RESULT_LEN
+                || data[..8] != SWITCHBOARD_PULL_FEED_DISCRIMINATOR
+            {
+                return Err(trace(
+                    "Pull feed has wrong layout",
+                    ProgramError::InvalidAccountData,
+                ));
+            }
+
+            let result = &data[RESULT_OFFSET..RESULT_OFFSET + RESULT_LEN];
+            Ok(Self {
+                value: i128::from_le_bytes(result[..16].try_into().unwrap()),
+                std_dev: i128::from_le_bytes(result[16..32].try_into().unwrap()),
+                num_samples: result[96],
+                slot: u64::from_le_bytes(result[104..112].try_into().unwrap()),
+            })
+        }
+
+        /// Loads a pull feed account owned by the Switchboard program.
+        pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+            if !account.is_owned_by(&SWITCHBOARD_PROGRAM_ID) {
+                return Err(trace(
+                    "Pull feed has wrong owner",
+                    ProgramError::InvalidAccountOwner,
+                ));
+            }
+            Self::try_from_bytes(&account.try_borrow_data()?)
+        }
+
+        /// Returns the result if it was produced at most `max_age` slots
+        /// before `clock`.
+        ///
+        /// Unlike Pyth, Switchboard results only record the slot, so the age
+        /// is measured in slots rather than seconds.
+        #[track_caller]
+        pub fn get_price_no_older_than(
+            &self,
+            clock: &Clock,
+            max_age: u64,
+        ) -> Result<SwitchboardResult, ProgramError> {
+            if self.num_samples == 0 || self.slot.saturating_add(max_age) < clock.slot {
+                return Err(stale());
+            }
+            Ok(*self)
+        }
+
+        /// Fails if the standard deviation exceeds `max_conf_bps` of the value.
+        #[track_caller]
+        pub fn check_confidence(&self, max_conf_bps: u16) -> ProgramResult {
+            check_confidence(
+                self.value.unsigned_abs(),
+                self.std_dev.unsigned_abs(),
+                max_conf_bps,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_confidence() {
+        assert!(check_confidence(10_000, 100, 100).is_ok());
+        assert_eq!(
+            check_confidence(10_000, 101, 100),
+            Err(ProgramError::Custom(ERROR_ORACLE_CONFIDENCE))
+        );
+    }
+
+    #[cfg(feature = "pyth")]
+    #[test]
+    fn test_pyth_price_update() {
+        use pinocchio::sysvars::clock::Clock;
+
+        let mut data = alloc::vec::Vec::new();
+        data.extend_from_slice(&PYTH_PRICE_UPDATE_DISCRIMINATOR);
+        data.extend_from_slice(&[7; 32]);
+        data.push(1);
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&150_00000000i64.to_le_bytes());
+        data.extend_from_slice(&1_000000u64.to_le_bytes());
+        data.extend_from_slice(&(-8i32).to_le_bytes());
+        data.extend_from_slice(&1_000i64.to_le_bytes());
+        data.extend_from_slice(&990i64.to_le_bytes());
+        data.extend_from_slice(&149_00000000i64.to_le_bytes());
+        data.extend_from_slice(&2_000000u64.to_le_bytes());
+        data.extend_from_slice(&42u64.to_le_bytes());
+
+        let update = PythPriceUpdate::try_from_bytes(&data).unwrap();
+        assert!(update.fully_verified);
+        assert_eq!(update.posted_slot, 42);
+        assert_eq!(update.ema_price.price, 149_00000000);
+
+        let clock = Clock {
+            slot: 50,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: 1_030,
+        };
+        let price = update
+            .get_price_no_older_than(&clock, 30, &[9; 32])
+            .unwrap();
+        assert_eq!(price.price, 150_00000000);
+        assert_eq!(price.exponent, -8);
+        assert!(price.check_confidence(1).is_ok());
+
+        assert_eq!(
+            update.get_price_no_older_than(&clock, 29, &[9; 32]),
+            Err(ProgramError::Custom(ERROR_ORACLE_STALE))
+        );
+        assert!(update
+            .get_price_no_older_than(&clock, 30, &[8; 32])
+            .is_err());
+    }
+
+    #[cfg(feature = "switchboard")]
+    #[test]
+    fn test_switchboard_result() {
+        use pinocchio::sysvars::clock::Clock;
+
+        // `PullFeedAccountData` up to `result`
+        let mut data = alloc::vec::Vec::new();
+        data.extend_from_slice(&SWITCHBOARD_PULL_FEED_DISCRIMINATOR);
+        data.extend_from_slice(&[1; 32 * 64]);
+        data.extend_from_slice(&[2; 3 * 32]);
+        data.extend_from_slice(&[3; 3 * 8 + 4 + 32 + 2 + 1 + 1 + 2 * 8 + 32]);
+        assert_eq!(data.len(), 2264);
+
+        // `CurrentResult`
+        let value = 150 * 10i128.pow(SWITCHBOARD_PRECISION);
+        data.extend_from_slice(&value.to_le_bytes());
+        data.extend_from_slice(&(value / 1_000).to_le_bytes());
+        data.extend_from_slice(&[4; 4 * 16]);
+        data.push(5);
+        data.extend_from_slice(&[0; 7]);
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&[6; 2 * 8]);
+        // Rest of the account
+        data.extend_from_slice(&[7; 64]);
+
+        let result = SwitchboardResult::try_from_bytes(&data).unwrap();
+        assert_eq!(
+            result,
+            SwitchboardResult {
+                value,
+                std_dev: value / 1_000,
+                num_samples: 5,
+                slot: 42,
+            }
+        );
+        assert!(result.check_confidence(10).is_ok());
+        assert_eq!(
+            result.check_confidence(9),
+            Err(ProgramError::Custom(ERROR_ORACLE_CONFIDENCE))
+        );
+
+        let clock = Clock {
+            slot: 50,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp: 0,
+        };
+        assert_eq!(result.get_price_no_older_than(&clock, 8), Ok(result));
+        assert_eq!(
+            result.get_price_no_older_than(&clock, 7),
+            Err(ProgramError::Custom(ERROR_ORACLE_STALE))
+        );
+
+        assert!(SwitchboardResult::try_from_bytes(&data[..2264 + 127]).is_err());
+        data[0] ^= 1;
+        assert!(SwitchboardResult::try_from_bytes(&data).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! Panic diagnostics: the dispatcher and validation record what the program
+//! was doing, and [`panic_handler!`](crate::panic_handler) logs it when a handler
+//! panics, e.g. on an `unwrap()`.
+//!
+//! ```ignore
+//! panic_handler!();
+//!
+//! pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+//!     let (ix, data) = parse_instruction::<MyInstruction>(&crate::ID, program_id, data)?;
+//!     match ix {
+//!         MyInstruction::Deposit => {
+//!             set_panic_handler("deposit");
+//!             process_deposit(accounts, data)
+//!         }
+//!         ...
+//!     }
+//! }
+//! ```
+//!
+//! Recording is enabled with the `panic-context` feature, otherwise the setters
+//! compile to nothing. The context is kept in a reserved slot at the start of
+//! the heap, since programs can't have writable statics. [`tracking_allocator!`](crate::tracking_allocator)
+//! reserves [`HEAP_RESERVED_LEN`] bytes for it; pinocchio's default allocator only
+//! reserves its own state, so a nearly full heap can overwrite the context.
+//! Off-chain nothing is recorded.
+
+use pinocchio::pubkey::Pubkey;
+
+#[cfg(all(target_os = "solana", feature = "panic-context"))]
+use crate::HEAP_RESERVED_LEN;
+
+/// Offset of the panic context in the heap, after the allocator state.
+#[cfg(all(target_os = "solana", feature = "panic-context"))]
+const PANIC_CONTEXT_OFFSET: usize = 16;
+
+/// Panic context slot, zeroed with the heap at the start of the instruction.
+#[cfg(all(target_os = "solana", feature = "panic-context"))]
+#[repr(C)]
+struct PanicContext {
+    has_instruction: u8,
+    instruction: u8,
+    _padding: [u8; 6],
+    handler_ptr: *const u8,
+    handler_len: usize,
+    account: *const Pubkey,
+}
+
+#[cfg(all(target_os = "solana", feature = "panic-context"))]
+const _: () = assert!(
+    PANIC_CONTEXT_OFFSET + core::mem::size_of::<PanicContext>() <= HEAP_RESERVED_LEN,
+    "panic context does not fit the reserved heap prefix"
+);
+
+#[cfg(all(target_os = "solana", feature = "panic-context"))]
+#[inline(always)]
+fn context() -> *mut PanicContext {
+    (crate::HEAP_START_ADDRESS + PANIC_CONTEXT_OFFSET) as *mut PanicContext
+}
+
+/// Records the instruction discriminator being dispatched.
+#[inline(always)]
+pub fn set_panic_instruction(_instruction: u8) {
+    #[cfg(all(target_os = "solana", feature = "panic-context"))]
+    // SAFETY: the slot is reserved in the heap, programs are single threaded
+    unsafe {
+        (*context()).has_instruction = 1;
+        (*context()).instruction = _instruction;
+    }
+}
+
+/// Records the name of the handler being run.
+#[inline(always)]
+pub fn set_panic_handler(_name: &'static str) {
+    #[cfg(all(target_os = "solana", feature = "panic-context"))]
+    // SAFETY: the slot is reserved in the heap, programs are single threaded
+    unsafe {
+        (*context()).handler_ptr = _name.as_ptr();
+        (*context()).handler_len = _name.len();
+    }
+}
+
+/// Records the last validated account. Account keys live in the input buffer
+/// for the whole instruction, so only a pointer is kept.
+#[inline(always)]
+pub fn set_panic_account(_key: &Pubkey) {
+    #[cfg(all(target_os = "solana", feature = "panic-context"))]
+    // SAFETY: the slot is reserved in the heap, programs are single threaded
+    unsafe {
+        (*context()).account = _key as *const Pubkey;
+    }
+}
+
+/// Logs the recorded instruction, handler and last validated account.
+#[cold]
+pub fn log_panic_context() {
+    #[cfg(all(target_os = "solana", feature = "panic-context"))]
+    {
+        use pinocchio_log::log;
+
+        // SAFETY: the slot is reserved in the heap and only written by the setters
+        let context = unsafe { &*context() };
+        if context.has_instruction != 0 {
+            log!("panic in instruction {}", context.instruction);
+        }
+        if context.handler_len != 0 {
+            // SAFETY: recorded from a `&'static str`
+            let handler = unsafe {
+                core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+                    context.handler_ptr,
+                    context.handler_len,
+                ))
+            };
+            log!("panic in handler '{}'", handler);
+        }
+        if !context.account.is_null() {
+            log!("last validated account:");
+            // SAFETY: recorded from an account key, which outlives the instruction
+            pinocchio::pubkey::log(unsafe { &*context.account });
+        }
+    }
+}
+
+/// Declare a `#[panic_handler]` for `no_std` programs that logs the panic
+/// location and the [`log_panic_context`] before aborting.
+///
+/// Replaces pinocchio's `nostd_panic_handler!`.
+#[macro_export]
+macro_rules! panic_handler {
+    () => {
+        #[cfg(target_os = "solana")]
+        #[panic_handler]
+        fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+            if let Some(location) = info.location() {
+                pinocchio_log::log!("panicked at {}:{}", location.file(), location.line());
+            } else {
+                pinocchio_log::log!("panicked");
+            }
+            $crate::log_panic_context();
+            // SAFETY: aborting is always safe
+            unsafe { pinocchio::syscalls::abort() }
+        }
+    };
+}
@@ -0,0 +1,250 @@
+//! Off-chain signed permits, verified through the Ed25519 precompile.
+//!
+//! An authority signs a [`Permit`] off-chain, the client adds an Ed25519
+//! signature verification instruction for it to the transaction, and the
+//! program checks that instruction through the Instructions sysvar. Each permit
+//! is consumed once, against a nonce counter the program keeps per authority.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, instructions::Instructions},
+    ProgramResult,
+};
+
+use crate::{hashv, impl_to_bytes, trace, ED25519_PROGRAM_ID, HASH_LENGTH};
+
+pub const ERROR_PERMIT_EXPIRED: u32 = 15;
+pub const ERROR_PERMIT_INVALID: u32 = 16;
+pub const ERROR_PERMIT_REPLAYED: u32 = 29;
+
+/// Domain separator prefixed to the hashed permit fields.
+pub const PERMIT_DOMAIN: &[u8; 16] = b"pinsteel-permit\0";
+
+/// Authorization granted by `authority` to `grantee` for `program`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Permit {
+    pub authority: Pubkey,
+    pub grantee: Pubkey,
+    /// Program the permit is valid for, prevents cross-program replay.
+    pub program: Pubkey,
+    /// Program defined permission bits.
+    pub scope: u64,
+    /// Value of the authority's nonce counter the permit consumes, see [`Permit::check`].
+    pub nonce: u64,
+    /// Unix timestamp after which the permit is no longer valid.
+    pub expires_at: i64,
+}
+
+impl_to_bytes!(Permit);
+
+impl Permit {
+    /// Digest the authority signs: keccak of the domain separator followed by
+    /// each field, integers little-endian.
+    pub fn message(&self) -> [u8; HASH_LENGTH] {
+        hashv(&[
+            PERMIT_DOMAIN,
+            &self.authority,
+            &self.grantee,
+            &self.program,
+            &self.scope.to_le_bytes(),
+            &self.nonce.to_le_bytes(),
+            &self.expires_at.to_le_bytes(),
+        ])
+    }
+
+    /// Checks expiry, target program, grantee and required `scope` bits, then
+    /// consumes the permit by advancing `nonce`, the authority's counter, which
+    /// the permit must match.
+    #[track_caller]
+    pub fn check(
+        &self,
+        clock: &Clock,
+        program_id: &Pubkey,
+        grantee: &Pubkey,
+        scope: u64,
+        nonce: &mut u64,
+    ) -> ProgramResult {
+        if clock.unix_timestamp > self.expires_at {
+            return Err(trace(
+                "Permit expired",
+                ProgramError::Custom(ERROR_PERMIT_EXPIRED),
+            ));
+        }
+        if self.program.ne(program_id) || self.grantee.ne(grantee) || self.scope & scope != scope {
+            return Err(trace(
+                "Permit does not grant access",
+                ProgramError::Custom(ERROR_PERMIT_INVALID),
+            ));
+        }
+        if self.nonce != *nonce {
+            return Err(trace(
+                "Permit nonce already used",
+                ProgramError::Custom(ERROR_PERMIT_REPLAYED),
+            ));
+        }
+        *nonce = nonce
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Fails unless an Ed25519 precompile instruction before the current one
+    /// verified `authority`'s signature over [`Self::message`].
+    #[track_caller]
+    pub fn verify_signature(&self, instructions_sysvar: &AccountInfo) -> ProgramResult {
+        let instructions = Instructions::try_from(instructions_sysvar)?;
+        let current = instructions.load_current_index() as usize;
+        let message = self.message();
+
+        for index in 0..current {
+            let ix = instructions.load_instruction_at(index)?;
+            if ix.get_program_id().eq(&ED25519_PROGRAM_ID)
+                && ed25519_verifies(ix.get_instruction_data(), &self.authority, &message)
+            {
+                return Ok(());
+            }
+        }
+
+        Err(trace(
+            "Permit signature is not verified",
+            ProgramError::Custom(ERROR_PERMIT_INVALID),
+        ))
+    }
+}
+
+/// Returns `true` if Ed25519 precompile instruction `data` verifies a signature
+/// of `pubkey` over `message`, with both stored in the instruction itself.
+fn ed25519_verifies(data: &[u8], pubkey: &Pubkey, message: &[u8]) -> bool {
+    // Header: num_signatures (u8) + padding (u8), then 14 byte offsets per signature
+    const OFFSETS_LEN: usize = 14;
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+    let Some(&count) = data.first() else {
+        return false;
+    };
+
+    (0..count as usize).any(|i| {
+        let start = 2 + i * OFFSETS_LEN;
+        let Some(offsets) = data.get(start..start + OFFSETS_LEN) else {
+            return false;
+        };
+        let u16_at = |o: usize| u16::from_le_bytes([offsets[o], offsets[o + 1]]);
+
+        // signature_instruction_index (2), public_key_instruction_index (6) and
+        // message_instruction_index (12) must point at this instruction
+        if u16_at(2) != CURRENT_INSTRUCTION
+            || u16_at(6) != CURRENT_INSTRUCTION
+            || u16_at(12) != CURRENT_INSTRUCTION
+        {
+            return false;
+        }
+
+        let pubkey_offset = u16_at(4) as usize;
+        let (message_offset, message_len) = (u16_at(8) as usize, u16_at(10) as usize);
+
+        data.get(pubkey_offset..pubkey_offset + 32) == Some(pubkey.as_ref())
+            && data.get(message_offset..message_offset + message_len) == Some(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn ed25519_ix(pubkey: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let pubkey_offset = 2 + 14;
+        let signature_offset = pubkey_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&[1, 0]);
+        for v in [
+            signature_offset as u16,
+            u16::MAX,
+            pubkey_offset as u16,
+            u16::MAX,
+            message_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(pubkey);
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_ed25519_verifies() {
+        let permit = Permit {
+            authority: [1; 32],
+            grantee: [2; 32],
+            program: [3; 32],
+            scope: 0b11,
+            nonce: 1,
+            expires_at: 100,
+        };
+        let message = permit.message();
+        let data = ed25519_ix(&permit.authority, &message);
+
+        assert!(ed25519_verifies(&data, &permit.authority, &message));
+        assert!(!ed25519_verifies(&data, &[9; 32], &message));
+        assert!(!ed25519_verifies(&data, &permit.authority, &message[1..]));
+        assert!(!ed25519_verifies(&data[..20], &permit.authority, &message));
+
+        let other = Permit { nonce: 2, ..permit };
+        assert!(!ed25519_verifies(
+            &data,
+            &permit.authority,
+            &other.message()
+        ));
+    }
+
+    #[test]
+    fn test_permit_check() {
+        let permit = Permit {
+            authority: [1; 32],
+            grantee: [2; 32],
+            program: [3; 32],
+            scope: 0b11,
+            nonce: 1,
+            expires_at: 100,
+        };
+        let clock = |unix_timestamp| Clock {
+            slot: 0,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp,
+        };
+
+        let mut nonce = 1;
+        assert!(permit
+            .check(&clock(100), &[3; 32], &[2; 32], 0b01, &mut nonce)
+            .is_ok());
+        assert_eq!(nonce, 2);
+        assert_eq!(
+            permit.check(&clock(100), &[3; 32], &[2; 32], 0b01, &mut nonce),
+            Err(ProgramError::Custom(ERROR_PERMIT_REPLAYED))
+        );
+        assert_eq!(nonce, 2);
+
+        let mut nonce = 1;
+        assert_eq!(
+            permit.check(&clock(101), &[3; 32], &[2; 32], 0b01, &mut nonce),
+            Err(ProgramError::Custom(ERROR_PERMIT_EXPIRED))
+        );
+        assert!(permit
+            .check(&clock(1), &[3; 32], &[2; 32], 0b100, &mut nonce)
+            .is_err());
+        assert!(permit
+            .check(&clock(1), &[4; 32], &[2; 32], 0b01, &mut nonce)
+            .is_err());
+        assert_eq!(nonce, 1);
+    }
+}
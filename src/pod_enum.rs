@@ -0,0 +1,172 @@
+//! Zero-copy `u8`-backed enums for account and instruction structs.
+
+/// Declare a `u8`-backed enum together with a `#[repr(transparent)]` pod wrapper
+/// that can be embedded in `#[repr(C)]` account structs.
+///
+/// The wrapper stores the raw byte, so account bytes with an unknown value can
+/// still be cast zero-copy; `get` validates the byte against the declared
+/// variants and fails with `InvalidAccountData` otherwise.
+///
+/// ```ignore
+/// pod_enum! {
+///     pub enum GameState(PodGameState) {
+///         Open = 0,
+///         Running = 1,
+///         Settled = 2,
+///     }
+/// }
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// pub struct Game {
+///     pub state: PodGameState,
+///     ...
+/// }
+///
+/// match game.state.get()? {
+///     GameState::Open => ...,
+///     ...
+/// }
+/// game.state.set(GameState::Settled);
+/// ```
+#[macro_export]
+macro_rules! pod_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident($pod:ident) {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(u8)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant = $value,)+
+        }
+
+        impl $name {
+            /// All variants, in declaration order.
+            pub const VARIANTS: &'static [Self] = &[$(Self::$variant,)+];
+
+            #[inline(always)]
+            pub const fn as_u8(self) -> u8 {
+                self as u8
+            }
+
+            /// Returns the variant with value `value`, or `None` if there is none.
+            #[inline(always)]
+            pub const fn from_u8(value: u8) -> Option<Self> {
+                match value {
+                    $($value => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            #[inline(always)]
+            fn from(value: $name) -> Self {
+                value.as_u8()
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = pinocchio::program_error::ProgramError;
+
+            #[track_caller]
+            #[inline(always)]
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Self::from_u8(value).ok_or_else(|| {
+                    $crate::trace(
+                        concat!("Invalid ", stringify!($name), " value"),
+                        pinocchio::program_error::ProgramError::InvalidAccountData,
+                    )
+                })
+            }
+        }
+
+        #[doc = concat!("`", stringify!($name), "` stored as a raw byte with alignment 1, for use in account and instruction structs.")]
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis struct $pod(u8);
+
+        impl $pod {
+            #[inline(always)]
+            pub const fn new(value: $name) -> Self {
+                Self(value as u8)
+            }
+
+            /// Returns the stored variant, failing if the byte is not a valid value.
+            #[track_caller]
+            #[inline(always)]
+            pub fn get(&self) -> Result<$name, pinocchio::program_error::ProgramError> {
+                $name::try_from(self.0)
+            }
+
+            #[inline(always)]
+            pub fn set(&mut self, value: $name) {
+                self.0 = value as u8;
+            }
+
+            /// The raw stored byte, which might not be a valid value.
+            #[inline(always)]
+            pub const fn raw(&self) -> u8 {
+                self.0
+            }
+
+            #[inline(always)]
+            pub const fn is_valid(&self) -> bool {
+                $name::from_u8(self.0).is_some()
+            }
+        }
+
+        impl From<$name> for $pod {
+            #[inline(always)]
+            fn from(value: $name) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl PartialEq<$name> for $pod {
+            #[inline(always)]
+            fn eq(&self, other: &$name) -> bool {
+                self.0 == *other as u8
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use pinocchio::program_error::ProgramError;
+
+    pod_enum! {
+        enum State(PodState) {
+            Open = 0,
+            Closed = 2,
+        }
+    }
+
+    #[test]
+    fn test_pod_enum() {
+        assert_eq!(State::Closed.as_u8(), 2);
+        assert_eq!(State::from_u8(0), Some(State::Open));
+        assert_eq!(State::from_u8(1), None);
+        assert_eq!(State::try_from(2), Ok(State::Closed));
+        assert_eq!(State::try_from(3), Err(ProgramError::InvalidAccountData));
+        assert_eq!(State::VARIANTS, &[State::Open, State::Closed]);
+
+        let mut pod = PodState::new(State::Open);
+        assert_eq!(pod, State::Open);
+        pod.set(State::Closed);
+        assert_eq!(pod.get(), Ok(State::Closed));
+        assert_eq!(core::mem::size_of::<PodState>(), 1);
+        assert_eq!(core::mem::align_of::<PodState>(), 1);
+
+        // SAFETY: `PodState` is a transparent `u8`
+        let invalid: PodState = unsafe { core::mem::transmute(1u8) };
+        assert!(!invalid.is_valid());
+        assert_eq!(invalid.raw(), 1);
+        assert_eq!(invalid.get(), Err(ProgramError::InvalidAccountData));
+    }
+}
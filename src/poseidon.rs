@@ -0,0 +1,140 @@
+//! Poseidon hash over the BN254 scalar field, backed by the `sol_poseidon` syscall.
+//!
+//! ZK-compatible alternative to keccak for merkle trees verified in circuits.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::trace;
+
+pub const POSEIDON_HASH_LENGTH: usize = 32;
+/// Maximum number of inputs accepted by the syscall.
+pub const POSEIDON_MAX_INPUTS: usize = 12;
+
+/// Poseidon parameter sets supported by the runtime.
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoseidonParameters {
+    /// BN254 with x^5 S-boxes, circom compatible.
+    Bn254X5 = 0,
+}
+
+/// Byte order of the inputs and the hash.
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoseidonEndianness {
+    BigEndian = 0,
+    LittleEndian = 1,
+}
+
+#[cfg(target_os = "solana")]
+extern "C" {
+    fn sol_poseidon(
+        parameters: u64,
+        endianness: u64,
+        vals: *const u8,
+        val_len: u64,
+        hash_result: *mut u8,
+    ) -> u64;
+}
+
+/// Hashes 1 to [`POSEIDON_MAX_INPUTS`] field elements of at most 32 bytes each.
+///
+/// Fails if an input is not a valid field element.
+#[track_caller]
+pub fn poseidon_hashv(
+    parameters: PoseidonParameters,
+    endianness: PoseidonEndianness,
+    vals: &[&[u8]],
+) -> Result<[u8; POSEIDON_HASH_LENGTH], ProgramError> {
+    if vals.is_empty() || vals.len() > POSEIDON_MAX_INPUTS {
+        return Err(trace(
+            "Invalid number of Poseidon inputs",
+            ProgramError::InvalidArgument,
+        ));
+    }
+    if vals
+        .iter()
+        .any(|v| v.is_empty() || v.len() > POSEIDON_HASH_LENGTH)
+    {
+        return Err(trace(
+            "Invalid Poseidon input length",
+            ProgramError::InvalidArgument,
+        ));
+    }
+
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = [0u8; POSEIDON_HASH_LENGTH];
+        let status = unsafe {
+            sol_poseidon(
+                parameters as u64,
+                endianness as u64,
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                result.as_mut_ptr(),
+            )
+        };
+        if status != 0 {
+            return Err(trace(
+                "Poseidon input is not a field element",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        Ok(result)
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+        let PoseidonParameters::Bn254X5 = parameters;
+        let mut hasher = Poseidon::<ark_bn254::Fr>::new_circom(vals.len())
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        let result = match endianness {
+            PoseidonEndianness::BigEndian => hasher.hash_bytes_be(vals),
+            PoseidonEndianness::LittleEndian => hasher.hash_bytes_le(vals),
+        };
+        result.map_err(|_| {
+            trace(
+                "Poseidon input is not a field element",
+                ProgramError::InvalidArgument,
+            )
+        })
+    }
+}
+
+/// [`poseidon_hashv`] with the circom compatible big-endian BN254 parameters.
+#[inline(always)]
+pub fn poseidon_hash(vals: &[&[u8]]) -> Result<[u8; POSEIDON_HASH_LENGTH], ProgramError> {
+    poseidon_hashv(
+        PoseidonParameters::Bn254X5,
+        PoseidonEndianness::BigEndian,
+        vals,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poseidon_hash() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let mut two = [0u8; 32];
+        two[31] = 2;
+        // circom poseidon([1, 2])
+        assert_eq!(
+            poseidon_hash(&[&one, &two]).unwrap(),
+            [
+                0x11, 0x5c, 0xc0, 0xf5, 0xe7, 0xd6, 0x90, 0x41, 0x3d, 0xf6, 0x4c, 0x6b, 0x96, 0x62,
+                0xe9, 0xcf, 0x2a, 0x36, 0x17, 0xf2, 0x74, 0x32, 0x45, 0x51, 0x9e, 0x19, 0x60, 0x7a,
+                0x44, 0x17, 0x18, 0x9a
+            ]
+        );
+
+        assert!(poseidon_hash(&[]).is_err());
+        assert!(poseidon_hash(&[&[0u8; 33]]).is_err());
+        assert!(poseidon_hash(&[&[0xff; 32]]).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Zero-copy FIFO queue living in account data, for consumers that need to
+//! replay events on-chain rather than parse them from transaction logs.
+//!
+//! ```ignore
+//! // SAFETY: `Fill` is a `repr(C)` struct of pods without padding
+//! unsafe impl QueueEntry for Fill {}
+//!
+//! // Producer
+//! let mut queue = EventQueue::<Fill>::load(&mut queue_info.try_borrow_mut_data()?[8..])?;
+//! let seq = queue.push(fill)?;
+//!
+//! // Consumer, after processing every entry up to `last_seq`
+//! queue.consume_up_to(last_seq)?;
+//! ```
+
+use core::marker::PhantomData;
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{trace, PodU128LE, PodU16LE, PodU32LE, PodU64LE};
+
+pub const ERROR_QUEUE_FULL: u32 = 26;
+
+/// Queue header, followed by the ring of entries.
+///
+/// Entries are identified by sequence numbers increasing by one per push;
+/// entry `seq` is stored in slot `seq % capacity`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueHeader {
+    /// Sequence number of the oldest entry.
+    pub head_seq: PodU64LE,
+    /// Sequence number of the next entry pushed.
+    pub next_seq: PodU64LE,
+    /// Number of slots, set on first load.
+    pub capacity: PodU64LE,
+}
+
+pub const QUEUE_HEADER_LEN: usize = core::mem::size_of::<QueueHeader>();
+
+/// Plain data that queue entries are read back as.
+///
+/// # Safety
+/// `Self` must have no padding, and any `size_of::<Self>()` bytes must be a
+/// valid `Self`, e.g. `repr(C)` structs of integers, byte arrays and pods.
+pub unsafe trait QueueEntry: Copy {}
+
+macro_rules! queue_entry {
+    ($($ty:ty),*) => {
+        $(unsafe impl QueueEntry for $ty {})*
+    };
+}
+
+queue_entry!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+queue_entry!(PodU16LE, PodU32LE, PodU64LE, PodU128LE);
+
+unsafe impl<const N: usize> QueueEntry for [u8; N] {}
+
+/// FIFO of `T` over a byte buffer, e.g. the data of a dedicated PDA.
+///
+/// Entries are copied in and out unaligned, so `T` needs no alignment.
+pub struct EventQueue<'a, T: QueueEntry> {
+    header: &'a mut QueueHeader,
+    body: &'a mut [u8],
+    capacity: u64,
+    _entry: PhantomData<T>,
+}
+
+impl<'a, T: QueueEntry> EventQueue<'a, T> {
+    /// Account space of a queue holding `capacity` entries.
+    pub const fn space(capacity: usize) -> usize {
+        QUEUE_HEADER_LEN + capacity * core::mem::size_of::<T>()
+    }
+
+    /// Loads the queue stored in `data`, zeroed data being an empty queue.
+    ///
+    /// Fails if the data was resized since the first load, or holds a header
+    /// that isn't a valid queue state.
+    #[track_caller]
+    pub fn load(data: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::space(1) || core::mem::size_of::<T>() == 0 {
+            return Err(trace(
+                "Queue data too small",
+                ProgramError::AccountDataTooSmall,
+            ));
+        }
+        let (header, body) = data.split_at_mut(QUEUE_HEADER_LEN);
+        // SAFETY: `QueueHeader` is built from alignment 1 pods and `header` is its size
+        let header = unsafe { &mut *(header.as_mut_ptr() as *mut QueueHeader) };
+        let capacity = (body.len() / core::mem::size_of::<T>()) as u64;
+
+        match header.capacity.get() {
+            0 => header.capacity.set(capacity),
+            stored if stored != capacity => {
+                return Err(trace(
+                    "Queue capacity does not match its data",
+                    ProgramError::InvalidAccountData,
+                ))
+            }
+            _ => {}
+        }
+        let (head_seq, next_seq) = (header.head_seq.get(), header.next_seq.get());
+        if head_seq > next_seq || next_seq - head_seq > capacity {
+            return Err(trace(
+                "Queue header is corrupt",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+
+        Ok(Self {
+            header,
+            body,
+            capacity,
+            _entry: PhantomData,
+        })
+    }
+
+    #[inline(always)]
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> u64 {
+        self.header.next_seq.get() - self.header.head_seq.get()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity
+    }
+
+    #[inline(always)]
+    pub fn head_seq(&self) -> u64 {
+        self.header.head_seq.get()
+    }
+
+    #[inline(always)]
+    pub fn next_seq(&self) -> u64 {
+        self.header.next_seq.get()
+    }
+
+    /// Appends `entry`, returning its sequence number. Fails if the queue is full.
+    #[track_caller]
+    pub fn push(&mut self, entry: T) -> Result<u64, ProgramError> {
+        if self.is_full() {
+            return Err(trace(
+                "Queue is full",
+                ProgramError::Custom(ERROR_QUEUE_FULL),
+            ));
+        }
+        let seq = self.header.next_seq.get();
+        // SAFETY: the slot is in bounds, see `slot`
+        unsafe { core::ptr::write_unaligned(self.slot(seq) as *mut T, entry) };
+        self.header.next_seq.set(seq + 1);
+        Ok(seq)
+    }
+
+    /// Oldest entry, if any.
+    #[inline(always)]
+    pub fn peek(&self) -> Option<T> {
+        self.get(self.head_seq())
+    }
+
+    /// Entry `seq`, if it is still in the queue.
+    pub fn get(&self, seq: u64) -> Option<T> {
+        if seq < self.head_seq() || seq >= self.next_seq() {
+            return None;
+        }
+        // SAFETY: the slot is in bounds and was written by `push`
+        Some(unsafe { core::ptr::read_unaligned(self.slot(seq) as *const T) })
+    }
+
+    /// Removes every entry up to and including `seq`, returning how many were
+    /// removed. Entries consumed before are skipped; fails if `seq` was not
+    /// pushed yet.
+    #[track_caller]
+    pub fn consume_up_to(&mut self, seq: u64) -> Result<u64, ProgramError> {
+        if seq >= self.next_seq() {
+            return Err(trace(
+                "Consuming entries not pushed yet",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        let head = self.head_seq();
+        if seq < head {
+            return Ok(0);
+        }
+        self.header.head_seq.set(seq + 1);
+        Ok(seq + 1 - head)
+    }
+
+    /// Pointer to the slot of `seq`, which is in bounds as `capacity` slots fit the body.
+    #[inline(always)]
+    fn slot(&self, seq: u64) -> *const u8 {
+        let offset = (seq % self.capacity) as usize * core::mem::size_of::<T>();
+        self.body[offset..].as_ptr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_queue() {
+        let mut data = [0u8; EventQueue::<u32>::space(3) + 2];
+        let mut queue = EventQueue::<u32>::load(&mut data).unwrap();
+        assert_eq!(queue.capacity(), 3);
+        assert!(queue.is_empty());
+        assert_eq!(queue.peek(), None);
+
+        assert_eq!(queue.push(10), Ok(0));
+        assert_eq!(queue.push(11), Ok(1));
+        assert_eq!(queue.push(12), Ok(2));
+        assert_eq!(queue.push(13), Err(ProgramError::Custom(ERROR_QUEUE_FULL)));
+
+        assert_eq!(queue.consume_up_to(1), Ok(2));
+        assert_eq!(queue.consume_up_to(0), Ok(0));
+        assert_eq!(queue.peek(), Some(12));
+
+        // Wraps around the ring
+        assert_eq!(queue.push(13), Ok(3));
+        assert_eq!(queue.push(14), Ok(4));
+        assert_eq!(queue.get(3), Some(13));
+        assert_eq!(queue.get(1), None);
+        assert_eq!(queue.consume_up_to(5), Err(ProgramError::InvalidArgument));
+        assert_eq!(queue.consume_up_to(4), Ok(3));
+        assert!(queue.is_empty());
+
+        // State persists in the data
+        let queue = EventQueue::<u32>::load(&mut data).unwrap();
+        assert_eq!((queue.head_seq(), queue.next_seq()), (5, 5));
+    }
+
+    #[test]
+    fn test_event_queue_rejects_invalid_headers() {
+        let mut data = [0u8; EventQueue::<u32>::space(3)];
+        EventQueue::<u32>::load(&mut data).unwrap();
+
+        // Resized data or a different entry type
+        assert_eq!(
+            EventQueue::<u32>::load(&mut data[..EventQueue::<u32>::space(2)]).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+        assert!(EventQueue::<u16>::load(&mut data).is_err());
+
+        let header = |data: &mut [u8], head_seq: u64, next_seq: u64| {
+            data[..8].copy_from_slice(&head_seq.to_le_bytes());
+            data[8..16].copy_from_slice(&next_seq.to_le_bytes());
+        };
+        header(&mut data, 2, 1);
+        assert!(EventQueue::<u32>::load(&mut data).is_err());
+        header(&mut data, 1, 5);
+        assert!(EventQueue::<u32>::load(&mut data).is_err());
+        header(&mut data, 2, 5);
+        assert_eq!(EventQueue::<u32>::load(&mut data).unwrap().len(), 3);
+    }
+}
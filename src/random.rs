@@ -0,0 +1,92 @@
+//! Pseudo-randomness derived from recent slot hashes.
+//!
+//! # Manipulation risk
+//!
+//! Slot hashes are known to the current leader before the transaction lands
+//! and to everyone once it does. A leader can withhold blocks, and any user can
+//! simulate and only submit favorable outcomes. Only use this where the value
+//! at stake is lower than the cost of such attacks, and prefer a VRF through
+//! [`RandomnessSource`] otherwise.
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock,
+};
+
+use crate::{hashv, trace, SLOT_HASHES_SYSVAR_ID};
+
+/// Supplies a 32 byte random seed for `user_key`.
+///
+/// Implemented by [`SlotHashRandomness`], VRF readers (e.g. a Switchboard
+/// randomness account) can implement it to be swapped in without changing callers.
+pub trait RandomnessSource {
+    fn random_seed(&self, user_key: &Pubkey) -> Result<[u8; 32], ProgramError>;
+}
+
+/// [`RandomnessSource`] over the SlotHashes sysvar, see the module level caveats.
+pub struct SlotHashRandomness<'a> {
+    pub clock: &'a Clock,
+    pub slot_hashes: &'a AccountInfo,
+}
+
+impl RandomnessSource for SlotHashRandomness<'_> {
+    #[inline(always)]
+    fn random_seed(&self, user_key: &Pubkey) -> Result<[u8; 32], ProgramError> {
+        pseudo_random_seed(self.clock, self.slot_hashes, user_key)
+    }
+}
+
+/// Hashes the most recent slot hash with the clock and `user_key`.
+///
+/// Not secure against leaders or simulating users, see the module docs.
+#[track_caller]
+pub fn pseudo_random_seed(
+    clock: &Clock,
+    slot_hashes: &AccountInfo,
+    user_key: &Pubkey,
+) -> Result<[u8; 32], ProgramError> {
+    if slot_hashes.key().ne(&SLOT_HASHES_SYSVAR_ID) {
+        return Err(trace(
+            "Account is not the SlotHashes sysvar",
+            ProgramError::InvalidArgument,
+        ));
+    }
+    let data = slot_hashes.try_borrow_data()?;
+    let recent = most_recent_slot_hash(&data)?;
+    Ok(hashv(&[
+        recent,
+        &clock.slot.to_le_bytes(),
+        &clock.unix_timestamp.to_le_bytes(),
+        user_key,
+    ]))
+}
+
+/// SlotHashes layout: `u64` count followed by `(slot: u64, hash: [u8; 32])`
+/// entries, most recent first.
+fn most_recent_slot_hash(data: &[u8]) -> Result<&[u8], ProgramError> {
+    match data.get(..8) {
+        Some(count) if count != [0; 8] => data.get(16..48).ok_or(ProgramError::InvalidAccountData),
+        _ => Err(trace(
+            "SlotHashes is empty",
+            ProgramError::InvalidAccountData,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_recent_slot_hash() {
+        let mut data = [0u8; 8 + 2 * 40];
+        data[..8].copy_from_slice(&2u64.to_le_bytes());
+        data[8..16].copy_from_slice(&10u64.to_le_bytes());
+        data[16..48].copy_from_slice(&[1; 32]);
+        data[48..56].copy_from_slice(&9u64.to_le_bytes());
+        data[56..88].copy_from_slice(&[2; 32]);
+
+        assert_eq!(most_recent_slot_hash(&data).unwrap(), &[1; 32]);
+        assert!(most_recent_slot_hash(&data[..40]).is_err());
+        assert!(most_recent_slot_hash(&[0; 48]).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Token-bucket rate limiting of outflows, e.g. bridge withdrawals or faucet
+//! claims, failing with [`ERROR_RATE_LIMITED`] once the bucket is drained.
+//!
+//! ```ignore
+//! let now = get_clock()?.unix_timestamp as u64;
+//! state.limiter.try_consume(amount, now)?;
+//! ```
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{trace, PodU64LE};
+
+pub const ERROR_RATE_LIMITED: u32 = 27;
+
+/// Token bucket allowing bursts of up to `capacity`, refilled continuously at
+/// `refill_rate` per second. Timestamps are unix seconds.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimiter {
+    /// Timestamp up to which `used` has been refilled.
+    pub window_start: PodU64LE,
+    /// Amount consumed and not refilled yet.
+    pub used: PodU64LE,
+    pub capacity: PodU64LE,
+    /// Amount refilled per second.
+    pub refill_rate: PodU64LE,
+}
+
+impl RateLimiter {
+    /// Full bucket as of `now`.
+    pub fn new(capacity: u64, refill_rate: u64, now: u64) -> Self {
+        Self {
+            window_start: PodU64LE::new(now),
+            used: PodU64LE::new(0),
+            capacity: PodU64LE::new(capacity),
+            refill_rate: PodU64LE::new(refill_rate),
+        }
+    }
+
+    /// Amount consumed and not refilled at `now`.
+    #[inline(always)]
+    fn used_at(&self, now: u64) -> u64 {
+        let elapsed = now.saturating_sub(self.window_start.get());
+        let refilled = elapsed.saturating_mul(self.refill_rate.get());
+        self.used.get().saturating_sub(refilled)
+    }
+
+    /// Amount that can be consumed at `now`.
+    #[inline(always)]
+    pub fn available(&self, now: u64) -> u64 {
+        self.capacity.get().saturating_sub(self.used_at(now))
+    }
+
+    /// Consumes `amount`, failing with [`ERROR_RATE_LIMITED`] if more than
+    /// [`Self::available`] at `now`. Timestamps older than the last update
+    /// don't refill anything.
+    #[track_caller]
+    pub fn try_consume(&mut self, amount: u64, now: u64) -> Result<(), ProgramError> {
+        if amount > self.available(now) {
+            return Err(trace(
+                "Rate limit exceeded",
+                ProgramError::Custom(ERROR_RATE_LIMITED),
+            ));
+        }
+        self.used.set(self.used_at(now) + amount);
+        self.window_start.set(now.max(self.window_start.get()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(1_000, 10, 100);
+        assert_eq!(limiter.available(100), 1_000);
+
+        limiter.try_consume(800, 100).unwrap();
+        assert_eq!(
+            limiter.try_consume(300, 100),
+            Err(ProgramError::Custom(ERROR_RATE_LIMITED))
+        );
+
+        // 10 seconds refill 100
+        assert_eq!(limiter.available(110), 300);
+        limiter.try_consume(300, 110).unwrap();
+        assert_eq!(limiter.available(110), 0);
+
+        // Stale timestamps don't refill
+        assert_eq!(limiter.available(50), 0);
+
+        // Refill caps at capacity
+        assert_eq!(limiter.available(10_000), 1_000);
+    }
+}
@@ -0,0 +1,211 @@
+//! Ratio type for exchange rates and percentages.
+
+use core::cmp::Ordering;
+
+use crate::{to_u64_checked, widen};
+
+/// Rounding direction of integer division.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+    /// Half rounds up.
+    Nearest,
+}
+
+/// `numerator / denominator`, with a denominator never zero.
+///
+/// Comparison and application widen to `u128`, so neither can overflow.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Ratio {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Ratio {
+    pub const ZERO: Self = Self {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Self = Self {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Returns `None` if `denominator` is zero.
+    #[inline(always)]
+    pub const fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        Some(Self {
+            numerator,
+            denominator,
+        })
+    }
+
+    /// `bps / 10_000`.
+    #[inline(always)]
+    pub const fn from_bps(bps: u64) -> Self {
+        Self {
+            numerator: bps,
+            denominator: 10_000,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn numerator(&self) -> u64 {
+        self.numerator
+    }
+
+    #[inline(always)]
+    pub const fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    /// Same ratio in lowest terms.
+    pub const fn reduce(&self) -> Self {
+        let gcd = gcd(self.numerator, self.denominator);
+        Self {
+            numerator: self.numerator / gcd,
+            denominator: self.denominator / gcd,
+        }
+    }
+
+    /// Inverse ratio, `None` if the numerator is zero.
+    #[inline(always)]
+    pub const fn invert(&self) -> Option<Self> {
+        Self::new(self.denominator, self.numerator)
+    }
+
+    /// `amount * numerator / denominator`, `None` if the result exceeds `u64`.
+    pub fn apply(&self, amount: u64, rounding: Rounding) -> Option<u64> {
+        let product = widen(amount) * widen(self.numerator);
+        let denominator = widen(self.denominator);
+        let quotient = product / denominator;
+        let remainder = product % denominator;
+        let round_up = match rounding {
+            Rounding::Down => false,
+            Rounding::Up => remainder > 0,
+            Rounding::Nearest => remainder * 2 >= denominator,
+        };
+        to_u64_checked(quotient + round_up as u128).ok()
+    }
+}
+
+impl PartialEq for Ratio {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ratio {}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    /// Compares by value through cross-multiplication, `1/2 == 2/4`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (widen(self.numerator) * widen(other.denominator))
+            .cmp(&(widen(other.numerator) * widen(self.denominator)))
+    }
+}
+
+// Off-chain, (de)serialize as `(numerator, denominator)`, rejecting a zero denominator
+#[cfg(feature = "client")]
+impl serde::Serialize for Ratio {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&(self.numerator, self.denominator), serializer)
+    }
+}
+
+#[cfg(feature = "client")]
+impl<'de> serde::Deserialize<'de> for Ratio {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (numerator, denominator) =
+            <(u64, u64) as serde::Deserialize>::deserialize(deserializer)?;
+        Self::new(numerator, denominator)
+            .ok_or_else(|| serde::de::Error::custom("ratio denominator is zero"))
+    }
+}
+
+#[cfg(feature = "client")]
+impl borsh::BorshSerialize for Ratio {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        writer.write_all(&self.numerator.to_le_bytes())?;
+        writer.write_all(&self.denominator.to_le_bytes())
+    }
+}
+
+#[cfg(feature = "client")]
+impl borsh::BorshDeserialize for Ratio {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let numerator = <u64 as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        let denominator = <u64 as borsh::BorshDeserialize>::deserialize_reader(reader)?;
+        Self::new(numerator, denominator).ok_or_else(|| {
+            borsh::io::Error::new(
+                borsh::io::ErrorKind::InvalidData,
+                "ratio denominator is zero",
+            )
+        })
+    }
+}
+
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    // gcd(0, 0) only happens for a zero denominator, which can't be constructed
+    if a == 0 {
+        1
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio() {
+        assert!(Ratio::new(1, 0).is_none());
+
+        let r = Ratio::new(6, 8).unwrap();
+        let reduced = r.reduce();
+        assert_eq!((reduced.numerator(), reduced.denominator()), (3, 4));
+        assert_eq!(r, reduced);
+        assert!(Ratio::new(2, 3).unwrap() < r);
+        assert!(Ratio::new(u64::MAX, 1).unwrap() > Ratio::new(u64::MAX - 1, 1).unwrap());
+        assert_eq!(Ratio::ZERO.invert(), None);
+
+        let third = Ratio::new(1, 3).unwrap();
+        assert_eq!(third.apply(10, Rounding::Down), Some(3));
+        assert_eq!(third.apply(10, Rounding::Up), Some(4));
+        assert_eq!(third.apply(10, Rounding::Nearest), Some(3));
+        assert_eq!(third.apply(11, Rounding::Nearest), Some(4));
+        assert_eq!(Ratio::from_bps(25).apply(10_000, Rounding::Down), Some(25));
+        assert_eq!(
+            Ratio::new(2, 1).unwrap().apply(u64::MAX, Rounding::Down),
+            None
+        );
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_ratio_borsh() {
+        let r = Ratio::new(3, 4).unwrap();
+        let bytes = borsh::to_vec(&r).unwrap();
+        assert_eq!(bytes, borsh::to_vec(&(3u64, 4u64)).unwrap());
+        let decoded = borsh::from_slice::<Ratio>(&bytes).unwrap();
+        assert_eq!((decoded.numerator(), decoded.denominator()), (3, 4));
+        assert!(borsh::from_slice::<Ratio>(&borsh::to_vec(&(3u64, 0u64)).unwrap()).is_err());
+    }
+}
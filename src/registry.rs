@@ -0,0 +1,124 @@
+//! Program id table for deployments spanning several programs and clusters.
+//!
+//! ```ignore
+//! pub static PROGRAMS: ProgramRegistry<2> = ProgramRegistry::new([
+//!     ProgramEntry::new("vault", VAULT_MAINNET, VAULT_DEVNET),
+//!     ProgramEntry::same("token", TOKEN_PROGRAM_ID),
+//! ]);
+//!
+//! Validation::default().is_program(PROGRAMS.id("token")).run(token_program)?;
+//! ```
+
+use pinocchio::pubkey::Pubkey;
+
+use crate::{Cluster, CLUSTER};
+
+/// Program id of a logical program on each cluster.
+#[derive(Clone, Copy, Debug)]
+pub struct ProgramEntry {
+    pub name: &'static str,
+    pub mainnet: Pubkey,
+    pub devnet: Pubkey,
+}
+
+impl ProgramEntry {
+    pub const fn new(name: &'static str, mainnet: Pubkey, devnet: Pubkey) -> Self {
+        Self {
+            name,
+            mainnet,
+            devnet,
+        }
+    }
+
+    /// Entry for programs deployed at the same address on all clusters.
+    pub const fn same(name: &'static str, id: Pubkey) -> Self {
+        Self::new(name, id, id)
+    }
+
+    /// Program id on the selected [`CLUSTER`], localnet uses the devnet id.
+    #[inline(always)]
+    pub const fn id(&self) -> &Pubkey {
+        match CLUSTER {
+            Cluster::Mainnet => &self.mainnet,
+            Cluster::Devnet | Cluster::Localnet => &self.devnet,
+        }
+    }
+}
+
+/// Const-friendly table of logical program names to program ids.
+pub struct ProgramRegistry<const N: usize> {
+    entries: [ProgramEntry; N],
+}
+
+impl<const N: usize> ProgramRegistry<N> {
+    pub const fn new(entries: [ProgramEntry; N]) -> Self {
+        Self { entries }
+    }
+
+    /// Program id of `name` on the selected cluster.
+    pub const fn get(&self, name: &str) -> Option<&Pubkey> {
+        let mut i = 0;
+        while i < N {
+            if str_eq(self.entries[i].name, name) {
+                return Some(self.entries[i].id());
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Same as `get`, but panics if `name` is not registered.
+    pub const fn id(&self, name: &str) -> &Pubkey {
+        match self.get(name) {
+            Some(id) => id,
+            None => panic!("program is not registered"),
+        }
+    }
+
+    /// Returns `true` if `program_id` is any registered program on the selected cluster.
+    pub fn contains(&self, program_id: &Pubkey) -> bool {
+        self.entries.iter().any(|e| e.id().eq(program_id))
+    }
+
+    pub fn entries(&self) -> &[ProgramEntry; N] {
+        &self.entries
+    }
+}
+
+pub(crate) const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PROGRAMS: ProgramRegistry<2> = ProgramRegistry::new([
+        ProgramEntry::new("vault", [1; 32], [2; 32]),
+        ProgramEntry::same("token", [3; 32]),
+    ]);
+
+    #[test]
+    fn test_registry() {
+        match CLUSTER {
+            Cluster::Mainnet => assert_eq!(PROGRAMS.id("vault"), &[1; 32]),
+            _ => assert_eq!(PROGRAMS.id("vault"), &[2; 32]),
+        }
+
+        assert_eq!(PROGRAMS.get("token"), Some(&[3; 32]));
+        assert_eq!(PROGRAMS.get("tok"), None);
+        assert!(PROGRAMS.contains(&[3; 32]));
+        assert!(!PROGRAMS.contains(&[4; 32]));
+    }
+}
@@ -0,0 +1,333 @@
+//! Derives the accounts of an instruction from its `instruction!` account list,
+//! so callers only supply the free inputs such as wallets and mints.
+//!
+//! Well-known accounts are resolved by name: `system_program`,
+//! `associated_token_program`, the `clock`, `rent` and `instructions` sysvars,
+//! and the `event_authority` and `program` accounts of `EmitEvent`. Other
+//! derivable accounts are declared with [`Resolution`] rules, which can read
+//! keys stored in other accounts through an [`AccountStore`], e.g. accounts
+//! fetched over RPC into a `HashMap`.
+//!
+//! Rules are written by hand rather than taken from `account!`'s `seeds = [...]`:
+//! those seeds name fields stored in the PDA itself, which can't be read before
+//! the account exists, while rules name the instruction's accounts and
+//! arguments the seeds come from. Keep the two in sync, e.g. with a test
+//! comparing the resolved key to the one `pda_seeds()` derives.
+//!
+//! ```ignore
+//! let instruction = AccountResolver::new::<Deposit>(&vault_program::ID)
+//!     .rule("vault", Resolution::Pda(&[SeedSource::Literal(b"vault"), SeedSource::Account("user")]))
+//!     .rule("user_ata", Resolution::Ata { wallet: "user", mint: "mint", token_program: "token_program" })
+//!     .rule("mint", Resolution::Pda(&[...]))
+//!     .account("user", user)
+//!     .account("token_program", TOKEN_PROGRAM_ID)
+//!     .store(&fetched)
+//!     .instruction(Deposit { amount }.to_bytes())?;
+//! ```
+
+use std::{
+    collections::HashMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use pinocchio::pubkey::Pubkey;
+use solana_instruction::{AccountMeta, Instruction};
+
+use crate::{
+    associated_token_address, find_program_address_offchain, AccountSpec, AccountStore,
+    InstructionAccounts, ASSOCIATED_TOKEN_PROGRAM_ID, CLOCK_SYSVAR_ID, EVENT_AUTHORITY_SEED,
+    INSTRUCTIONS_SYSVAR_ID, RENT_SYSVAR_ID, SYSTEM_PROGRAM_ID,
+};
+
+/// Accounts resolved by name unless a rule or key is given for them.
+pub const KNOWN_ADDRESSES: &[(&str, Pubkey)] = &[
+    ("system_program", SYSTEM_PROGRAM_ID),
+    ("associated_token_program", ASSOCIATED_TOKEN_PROGRAM_ID),
+    ("clock", CLOCK_SYSVAR_ID),
+    ("rent", RENT_SYSVAR_ID),
+    ("instructions", INSTRUCTIONS_SYSVAR_ID),
+];
+
+/// Bytes of a PDA seed.
+#[derive(Clone, Copy, Debug)]
+pub enum SeedSource {
+    Literal(&'static [u8]),
+    /// Key of another account of the instruction.
+    Account(&'static str),
+    /// Key stored at `offset` in the data of another account of the instruction.
+    AccountField {
+        account: &'static str,
+        offset: usize,
+    },
+    /// Bytes given with [`AccountResolver::arg`], e.g. an instruction argument.
+    Arg(&'static str),
+}
+
+/// How to derive an account.
+#[derive(Clone, Copy, Debug)]
+pub enum Resolution {
+    Address(Pubkey),
+    /// PDA of the instruction's program.
+    Pda(&'static [SeedSource]),
+    /// PDA of another program, named like an account of the instruction.
+    PdaOf {
+        program: &'static str,
+        seeds: &'static [SeedSource],
+    },
+    /// Associated token account, each field naming an account of the instruction.
+    Ata {
+        wallet: &'static str,
+        mint: &'static str,
+        token_program: &'static str,
+    },
+}
+
+/// Builds the account metas of an instruction from its [`InstructionAccounts`].
+pub struct AccountResolver<'a> {
+    program_id: Pubkey,
+    specs: &'static [AccountSpec],
+    keys: HashMap<&'static str, Pubkey>,
+    rules: HashMap<&'static str, Resolution>,
+    args: HashMap<&'static str, Vec<u8>>,
+    store: Option<&'a dyn AccountStore>,
+}
+
+impl<'a> AccountResolver<'a> {
+    pub fn new<T: InstructionAccounts>(program_id: &Pubkey) -> Self {
+        let mut rules: HashMap<_, _> = KNOWN_ADDRESSES
+            .iter()
+            .map(|(name, key)| (*name, Resolution::Address(*key)))
+            .collect();
+        rules.insert("program", Resolution::Address(*program_id));
+        rules.insert(
+            "event_authority",
+            Resolution::Pda(&[SeedSource::Literal(EVENT_AUTHORITY_SEED)]),
+        );
+        Self {
+            program_id: *program_id,
+            specs: T::ACCOUNTS,
+            keys: HashMap::new(),
+            rules,
+            args: HashMap::new(),
+            store: None,
+        }
+    }
+
+    /// Derives `name` with `resolution`, replacing any default.
+    pub fn rule(&mut self, name: &'static str, resolution: Resolution) -> &mut Self {
+        self.rules.insert(name, resolution);
+        self
+    }
+
+    /// Free input, or an override of a derivable account.
+    pub fn account(&mut self, name: &'static str, key: Pubkey) -> &mut Self {
+        self.keys.insert(name, key);
+        self
+    }
+
+    /// Seed bytes for [`SeedSource::Arg`].
+    pub fn arg(&mut self, name: &'static str, bytes: &[u8]) -> &mut Self {
+        self.args.insert(name, bytes.to_vec());
+        self
+    }
+
+    /// Account data read by [`SeedSource::AccountField`].
+    pub fn store(&mut self, store: &'a dyn AccountStore) -> &mut Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Resolves every account, in instruction order.
+    pub fn resolve(&self) -> Result<Vec<AccountMeta>, String> {
+        let mut keys = self.keys.clone();
+        // Each pass resolves at least one account, or none ever will
+        for _ in 0..self.specs.len() {
+            let mut progress = false;
+            for spec in self.specs {
+                if keys.contains_key(spec.name) {
+                    continue;
+                }
+                let Some(rule) = self.rules.get(spec.name) else {
+                    continue;
+                };
+                if let Some(key) = self.derive(rule, &keys)? {
+                    keys.insert(spec.name, key);
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        self.specs
+            .iter()
+            .map(|spec| {
+                let key = keys.get(spec.name).ok_or_else(|| {
+                    format!("account {} is not provided and can't be derived", spec.name)
+                })?;
+                Ok(AccountMeta {
+                    pubkey: (*key).into(),
+                    is_signer: spec.signer,
+                    is_writable: spec.writable,
+                })
+            })
+            .collect()
+    }
+
+    /// Instruction with the resolved accounts and `data`, e.g. from `to_bytes()`.
+    pub fn instruction(&self, data: Vec<u8>) -> Result<Instruction, String> {
+        Ok(Instruction {
+            program_id: self.program_id.into(),
+            accounts: self.resolve()?,
+            data,
+        })
+    }
+
+    /// Key of `rule`, `None` while an account it depends on is unresolved.
+    fn derive(
+        &self,
+        rule: &Resolution,
+        keys: &HashMap<&'static str, Pubkey>,
+    ) -> Result<Option<Pubkey>, String> {
+        let (program_id, seeds) = match rule {
+            Resolution::Address(key) => return Ok(Some(*key)),
+            Resolution::Ata {
+                wallet,
+                mint,
+                token_program,
+            } => {
+                return Ok(
+                    match (keys.get(wallet), keys.get(mint), keys.get(token_program)) {
+                        (Some(wallet), Some(mint), Some(token_program)) => Some(
+                            associated_token_address(wallet, mint, token_program)
+                                .ok_or_else(|| "invalid associated token address".to_string())?,
+                        ),
+                        _ => None,
+                    },
+                );
+            }
+            Resolution::Pda(seeds) => (&self.program_id, *seeds),
+            Resolution::PdaOf { program, seeds } => match keys.get(program) {
+                Some(program_id) => (program_id, *seeds),
+                None => return Ok(None),
+            },
+        };
+
+        let mut bytes: Vec<Vec<u8>> = Vec::with_capacity(seeds.len());
+        for seed in seeds {
+            let seed = match seed {
+                SeedSource::Literal(literal) => literal.to_vec(),
+                SeedSource::Account(name) => match keys.get(name) {
+                    Some(key) => key.to_vec(),
+                    None => return Ok(None),
+                },
+                SeedSource::AccountField { account, offset } => {
+                    let Some(key) = keys.get(account) else {
+                        return Ok(None);
+                    };
+                    let data = self
+                        .store
+                        .and_then(|store| store.account_data(key))
+                        .ok_or_else(|| format!("account {} data is not available", account))?;
+                    offset
+                        .checked_add(32)
+                        .and_then(|end| data.get(*offset..end))
+                        .ok_or_else(|| format!("account {} data is too short", account))?
+                        .to_vec()
+                }
+                SeedSource::Arg(name) => self
+                    .args
+                    .get(name)
+                    .ok_or_else(|| format!("seed argument {} is not provided", name))?
+                    .clone(),
+            };
+            bytes.push(seed);
+        }
+
+        let seeds: Vec<&[u8]> = bytes.iter().map(Vec::as_slice).collect();
+        find_program_address_offchain(&seeds, program_id)
+            .map(|(key, _)| Some(key))
+            .ok_or_else(|| "invalid PDA seeds".to_string())
+    }
+}
+
+/// Accounts fetched ahead of resolution, e.g. with `getMultipleAccounts`.
+impl AccountStore for HashMap<Pubkey, Vec<u8>> {
+    fn account_data(&self, key: &Pubkey) -> Option<&[u8]> {
+        self.get(key).map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Deposit;
+
+    impl InstructionAccounts for Deposit {
+        const ACCOUNTS: &'static [AccountSpec] = &[
+            AccountSpec::writable_signer("user"),
+            AccountSpec::readonly("config"),
+            AccountSpec::writable("vault"),
+            AccountSpec::readonly("system_program"),
+        ];
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (program_id, user, config, admin) = ([1; 32], [2; 32], [3; 32], [4; 32]);
+        let mut config_data = std::vec![0u8; 40];
+        config_data[8..40].copy_from_slice(&admin);
+        let store = HashMap::from([(config, config_data)]);
+
+        let mut resolver = AccountResolver::new::<Deposit>(&program_id);
+        resolver
+            .rule(
+                "vault",
+                Resolution::Pda(&[
+                    SeedSource::Literal(b"vault"),
+                    SeedSource::Account("user"),
+                    SeedSource::AccountField {
+                        account: "config",
+                        offset: 8,
+                    },
+                ]),
+            )
+            .account("user", user);
+        assert!(resolver.resolve().unwrap_err().contains("config"));
+
+        resolver.account("config", config);
+        assert!(resolver.resolve().unwrap_err().contains("not available"));
+
+        resolver.store(&store).rule(
+            "vault",
+            Resolution::Pda(&[SeedSource::AccountField {
+                account: "config",
+                offset: usize::MAX,
+            }]),
+        );
+        assert!(resolver.resolve().unwrap_err().contains("too short"));
+
+        resolver.rule(
+            "vault",
+            Resolution::Pda(&[
+                SeedSource::Literal(b"vault"),
+                SeedSource::Account("user"),
+                SeedSource::AccountField {
+                    account: "config",
+                    offset: 8,
+                },
+            ]),
+        );
+        let metas = resolver.resolve().unwrap();
+        let vault = find_program_address_offchain(&[b"vault", &user, &admin], &program_id)
+            .unwrap()
+            .0;
+        assert_eq!(metas[2].pubkey.to_bytes(), vault);
+        assert!(metas[2].is_writable && !metas[2].is_signer);
+        assert_eq!(metas[3].pubkey.to_bytes(), SYSTEM_PROGRAM_ID);
+    }
+}
@@ -0,0 +1,224 @@
+//! Staking reward accounting with a reward-per-share accumulator
+//! (MasterChef style): rewards streamed or distributed to a pool are shared
+//! pro rata between stakers, each settled when their stake changes.
+//!
+//! ```ignore
+//! let pending = pool.stake(&mut user, amount, clock.unix_timestamp as u64)?;
+//! // transfer `pending` rewards and `amount` staked tokens
+//! ```
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{mul_div, to_u64_checked, trace, widen, PodU128LE, PodU64LE, Rounding, WAD};
+
+/// Fixed-point scale of [`RewardPool::acc_reward_per_share`].
+pub const ACC_REWARD_PRECISION: u128 = WAD;
+
+/// Rewards streamed at `reward_per_second` to all stakers, pro rata.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewardPool {
+    /// Rewards per staked token since inception, [`ACC_REWARD_PRECISION`] scaled.
+    pub acc_reward_per_share: PodU128LE,
+    /// Unix timestamp up to which rewards are accounted.
+    pub last_update: PodU64LE,
+    pub reward_per_second: PodU64LE,
+    pub total_staked: PodU64LE,
+}
+
+/// Stake of one user in a [`RewardPool`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UserStake {
+    pub amount: PodU64LE,
+    /// `amount * acc_reward_per_share / ACC_REWARD_PRECISION` at the last
+    /// settlement, rounded up.
+    pub reward_debt: PodU128LE,
+}
+
+impl RewardPool {
+    pub const fn new(reward_per_second: u64, now: u64) -> Self {
+        Self {
+            acc_reward_per_share: PodU128LE::new(0),
+            last_update: PodU64LE::new(now),
+            reward_per_second: PodU64LE::new(reward_per_second),
+            total_staked: PodU64LE::new(0),
+        }
+    }
+
+    /// Accounts the rewards streamed up to `now`. Rewards of periods without
+    /// stakers are not distributed.
+    #[track_caller]
+    pub fn update_pool(&mut self, now: u64) -> Result<(), ProgramError> {
+        let elapsed = now.saturating_sub(self.last_update.get());
+        if elapsed == 0 {
+            return Ok(());
+        }
+        let rewards = self.reward_per_second.get() as u128 * elapsed as u128;
+        self.distribute(rewards)?;
+        self.last_update.set(now);
+        Ok(())
+    }
+
+    /// Distributes a lump sum of `rewards` to the current stakers.
+    #[track_caller]
+    pub fn distribute(&mut self, rewards: u128) -> Result<(), ProgramError> {
+        let total_staked = self.total_staked.get();
+        if total_staked == 0 {
+            return Ok(());
+        }
+        let increase = mul_div(
+            rewards,
+            ACC_REWARD_PRECISION,
+            total_staked as u128,
+            Rounding::Down,
+        )
+        .ok_or_else(overflow)?;
+        let acc = self
+            .acc_reward_per_share
+            .get()
+            .checked_add(increase)
+            .ok_or_else(overflow)?;
+        self.acc_reward_per_share.set(acc);
+        Ok(())
+    }
+
+    /// Rewards of `user` not claimed yet, as of the last pool update.
+    #[track_caller]
+    pub fn pending_rewards(&self, user: &UserStake) -> Result<u64, ProgramError> {
+        let accrued = self.accrued(user.amount.get(), Rounding::Down)?;
+        // Debts round up, so pending rewards stay covered by the distributed ones
+        to_u64_checked(accrued.saturating_sub(user.reward_debt.get()))
+    }
+
+    /// Updates the pool to `now`, then adds `amount` to the stake of `user`.
+    /// Returns the pending rewards settled, to be paid out by the caller.
+    #[track_caller]
+    pub fn stake(
+        &mut self,
+        user: &mut UserStake,
+        amount: u64,
+        now: u64,
+    ) -> Result<u64, ProgramError> {
+        self.update_pool(now)?;
+        let pending = self.pending_rewards(user)?;
+        let staked = user.amount.get().checked_add(amount).ok_or_else(overflow)?;
+        let total = self
+            .total_staked
+            .get()
+            .checked_add(amount)
+            .ok_or_else(overflow)?;
+        self.settle(user, staked)?;
+        self.total_staked.set(total);
+        Ok(pending)
+    }
+
+    /// Updates the pool to `now`, then removes `amount` from the stake of `user`.
+    /// Returns the pending rewards settled, to be paid out by the caller.
+    #[track_caller]
+    pub fn unstake(
+        &mut self,
+        user: &mut UserStake,
+        amount: u64,
+        now: u64,
+    ) -> Result<u64, ProgramError> {
+        self.update_pool(now)?;
+        let pending = self.pending_rewards(user)?;
+        let staked = user.amount.get().checked_sub(amount).ok_or_else(|| {
+            trace(
+                "Unstaking more than staked",
+                ProgramError::InsufficientFunds,
+            )
+        })?;
+        self.settle(user, staked)?;
+        self.total_staked
+            .set(self.total_staked.get().saturating_sub(amount));
+        Ok(pending)
+    }
+
+    /// Updates the pool to `now` and settles the pending rewards of `user`,
+    /// returning them to be paid out by the caller.
+    #[track_caller]
+    pub fn claim(&mut self, user: &mut UserStake, now: u64) -> Result<u64, ProgramError> {
+        self.unstake(user, 0, now)
+    }
+
+    /// `amount * acc_reward_per_share / ACC_REWARD_PRECISION`.
+    ///
+    /// The product goes through `U256`, as a dust first staker makes
+    /// `acc_reward_per_share` large enough to overflow `u128` for later stakes.
+    #[track_caller]
+    fn accrued(&self, amount: u64, rounding: Rounding) -> Result<u128, ProgramError> {
+        mul_div(
+            widen(amount),
+            self.acc_reward_per_share.get(),
+            ACC_REWARD_PRECISION,
+            rounding,
+        )
+        .ok_or_else(overflow)
+    }
+
+    #[track_caller]
+    fn settle(&self, user: &mut UserStake, amount: u64) -> Result<(), ProgramError> {
+        user.amount.set(amount);
+        user.reward_debt.set(self.accrued(amount, Rounding::Up)?);
+        Ok(())
+    }
+}
+
+#[track_caller]
+fn overflow() -> ProgramError {
+    trace("Reward math overflow", ProgramError::ArithmeticOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_pool() {
+        let mut pool = RewardPool::new(100, 0);
+        let (mut alice, mut bob) = (UserStake::default(), UserStake::default());
+
+        assert_eq!(pool.stake(&mut alice, 1_000, 0), Ok(0));
+        // Alice alone for 10s
+        assert_eq!(pool.stake(&mut bob, 3_000, 10), Ok(0));
+        assert_eq!(pool.pending_rewards(&alice), Ok(1_000));
+
+        // Alice 1/4, Bob 3/4 for 20s
+        pool.update_pool(30).unwrap();
+        assert_eq!(pool.pending_rewards(&alice), Ok(1_500));
+        assert_eq!(pool.pending_rewards(&bob), Ok(1_500));
+
+        assert_eq!(pool.claim(&mut alice, 30), Ok(1_500));
+        assert_eq!(pool.pending_rewards(&alice), Ok(0));
+        assert_eq!(pool.unstake(&mut bob, 3_000, 40), Ok(2_250));
+        assert_eq!(pool.total_staked.get(), 1_000);
+
+        // Alice alone again
+        assert_eq!(pool.claim(&mut alice, 50), Ok(250 + 1_000));
+        assert_eq!(
+            pool.unstake(&mut alice, 1_001, 50),
+            Err(ProgramError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn test_reward_pool_dust_staker() {
+        let mut pool = RewardPool::new(1_000_000_000_000, 0);
+        let (mut dust, mut whale) = (UserStake::default(), UserStake::default());
+
+        // 1 staked unit earns 1e12 rewards, `acc_reward_per_share` becomes 1e30
+        assert_eq!(pool.stake(&mut dust, 1, 0), Ok(0));
+        assert_eq!(pool.stake(&mut whale, 1_000_000_000_000_000, 1), Ok(0));
+        assert_eq!(pool.pending_rewards(&dust), Ok(1_000_000_000_000));
+
+        assert_eq!(pool.claim(&mut whale, 2), Ok(999_999_999_999));
+        assert_eq!(
+            pool.unstake(&mut whale, 1_000_000_000_000_000, 3),
+            Ok(999_999_999_999)
+        );
+        // Payouts stay within the 3e12 distributed
+        assert_eq!(pool.pending_rewards(&dust), Ok(1_000_000_000_001));
+    }
+}
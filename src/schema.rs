@@ -0,0 +1,86 @@
+//! Instruction data schema versioning, to coordinate breaking wire-format
+//! changes between a program and its clients.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::trace;
+
+pub const ERROR_CLIENT_TOO_OLD: u32 = 21;
+pub const ERROR_CLIENT_TOO_NEW: u32 = 22;
+
+/// Splits the leading schema version byte off `data`, failing with a clear
+/// error unless it lies within `min..=max`.
+#[track_caller]
+pub fn split_schema_version(data: &[u8], min: u8, max: u8) -> Result<(u8, &[u8]), ProgramError> {
+    let (&version, rest) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if version < min {
+        return Err(trace(
+            "Client too old, instruction schema version is no longer supported",
+            ProgramError::Custom(ERROR_CLIENT_TOO_OLD),
+        ));
+    }
+    if version > max {
+        return Err(trace(
+            "Client too new, instruction schema version is not supported yet",
+            ProgramError::Custom(ERROR_CLIENT_TOO_NEW),
+        ));
+    }
+    Ok((version, rest))
+}
+
+/// Declare the instruction schema versions the program accepts.
+///
+/// Instruction data then starts with a version byte, ahead of the discriminator:
+///
+/// ```ignore
+/// schema_version!(2..=3);
+///
+/// pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+///     let (version, data) = split_schema_version(data)?;
+///     let (ix, data) = parse_instruction::<MyInstruction>(&crate::ID, program_id, data)?;
+///     ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! schema_version {
+    ($min:literal ..= $max:literal) => {
+        /// Oldest instruction schema version still accepted.
+        pub const SCHEMA_VERSION_MIN: u8 = $min;
+        /// Current instruction schema version, written by up to date clients.
+        pub const SCHEMA_VERSION: u8 = $max;
+
+        /// Splits and checks the leading schema version byte of instruction data.
+        #[track_caller]
+        #[inline(always)]
+        pub fn split_schema_version(
+            data: &[u8],
+        ) -> Result<(u8, &[u8]), pinocchio::program_error::ProgramError> {
+            $crate::split_schema_version(data, SCHEMA_VERSION_MIN, SCHEMA_VERSION)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_schema_version() {
+        assert_eq!(split_schema_version(&[2, 9], 2, 3), Ok((2, &[9][..])));
+        assert_eq!(split_schema_version(&[3], 2, 3), Ok((3, &[][..])));
+        assert_eq!(
+            split_schema_version(&[1, 9], 2, 3),
+            Err(ProgramError::Custom(ERROR_CLIENT_TOO_OLD))
+        );
+        assert_eq!(
+            split_schema_version(&[4, 9], 2, 3),
+            Err(ProgramError::Custom(ERROR_CLIENT_TOO_NEW))
+        );
+        assert_eq!(
+            split_schema_version(&[], 2, 3),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Program-owned accounts used as working memory.
+//!
+//! A scratch account holds no typed state of its own. Its data starts with an
+//! 8 byte header, the number of bytes written so far, so data larger than an
+//! instruction can be accumulated over several transactions:
+//!
+//! ```ignore
+//! // One instruction per chunk, e.g. of a large merkle tree
+//! let mut scratch = Scratch::new(scratch_info, &crate::ID)?;
+//! scratch.write_chunk(args.offset.get() as usize, chunk)?;
+//!
+//! // Once all chunks are written
+//! let tree = scratch.read_all();
+//! ```
+//!
+//! Handlers can also carve disjoint buffers out of the data after the header,
+//! for computations too large for the stack or heap:
+//!
+//! ```ignore
+//! let mut arena = scratch.arena();
+//! let bitmap = arena.take(1024)?;
+//! let tree = arena.take_as::<MerkleTree>()?;
+//! ```
+
+use pinocchio::{
+    account_info::{AccountInfo, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{fast_memset, trace, Account};
+
+/// Length of the header in front of the scratch data: the number of bytes
+/// written by [`Scratch::write_chunk`], a little-endian `u64`.
+pub const SCRATCH_HEADER_LEN: usize = 8;
+
+/// Mutable borrow of a scratch account's data.
+pub struct Scratch<'a> {
+    data: RefMut<'a, [u8]>,
+}
+
+impl<'a> Scratch<'a> {
+    /// Borrows the data of a writable account owned by `program_id`.
+    pub fn new(ai: &'a AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError> {
+        if !ai.is_owned_by(program_id) {
+            return Err(trace(
+                "Scratch account has wrong owner",
+                ProgramError::InvalidAccountOwner,
+            ));
+        }
+        if !ai.is_writable() {
+            return Err(trace(
+                "Scratch account is not writable",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        if ai.data_len() < SCRATCH_HEADER_LEN {
+            return Err(trace(
+                "Scratch account has no room for its header",
+                ProgramError::AccountDataTooSmall,
+            ));
+        }
+        Ok(Self {
+            data: ai.try_borrow_mut_data()?,
+        })
+    }
+
+    /// Capacity for data after the header.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.data.len() - SCRATCH_HEADER_LEN
+    }
+
+    /// Number of bytes written so far.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        let mut len = [0u8; SCRATCH_HEADER_LEN];
+        len.copy_from_slice(&self.data[..SCRATCH_HEADER_LEN]);
+        u64::from_le_bytes(len) as usize
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes `bytes` at `offset` of the data, e.g. one chunk per transaction.
+    ///
+    /// Chunks may overwrite data written before, but must not leave a gap after
+    /// it, so that [`Self::read_all`] only returns written bytes.
+    pub fn write_chunk(&mut self, offset: usize, bytes: &[u8]) -> Result<(), ProgramError> {
+        let len = self.len();
+        if offset > len {
+            return Err(trace(
+                "Scratch chunk leaves a gap",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        let end = offset
+            .checked_add(bytes.len())
+            .filter(|&end| end <= self.capacity())
+            .ok_or_else(|| trace("Scratch space exhausted", ProgramError::AccountDataTooSmall))?;
+        self.data[SCRATCH_HEADER_LEN + offset..SCRATCH_HEADER_LEN + end].copy_from_slice(bytes);
+        if end > len {
+            self.data[..SCRATCH_HEADER_LEN].copy_from_slice(&(end as u64).to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// All bytes written so far.
+    #[inline(always)]
+    pub fn read_all(&self) -> &[u8] {
+        let len = self.len().min(self.capacity());
+        &self.data[SCRATCH_HEADER_LEN..SCRATCH_HEADER_LEN + len]
+    }
+
+    /// Zeroes the whole account data, header included.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        fast_memset(&mut self.data, 0);
+    }
+
+    /// Allocator handing out disjoint buffers from the start of the data after
+    /// the header.
+    #[inline(always)]
+    pub fn arena(&mut self) -> ScratchArena<'_> {
+        ScratchArena::new(&mut self.data[SCRATCH_HEADER_LEN..])
+    }
+}
+
+/// Bump allocator over a byte buffer.
+pub struct ScratchArena<'s> {
+    rest: &'s mut [u8],
+}
+
+impl<'s> ScratchArena<'s> {
+    #[inline(always)]
+    pub fn new(buf: &'s mut [u8]) -> Self {
+        Self { rest: buf }
+    }
+
+    /// Bytes still available.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.rest.len()
+    }
+
+    /// Takes the next `len` bytes.
+    pub fn take(&mut self, len: usize) -> Result<&'s mut [u8], ProgramError> {
+        if self.rest.len() < len {
+            return Err(trace(
+                "Scratch space exhausted",
+                ProgramError::AccountDataTooSmall,
+            ));
+        }
+        let (head, tail) = core::mem::take(&mut self.rest).split_at_mut(len);
+        self.rest = tail;
+        Ok(head)
+    }
+
+    /// Takes the next properly aligned `T`, as-is (not zeroed).
+    pub fn take_as<T: Account>(&mut self) -> Result<&'s mut T, ProgramError> {
+        let padding = self.rest.as_ptr().align_offset(core::mem::align_of::<T>());
+        self.take(padding)?;
+        let bytes = self.take(core::mem::size_of::<T>())?;
+        // SAFETY: length and alignment are ensured above, `Account` types are
+        // valid for any byte pattern
+        Ok(unsafe { &mut *(bytes.as_mut_ptr() as *mut T) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockAccount;
+
+    #[repr(C)]
+    struct Counter {
+        value: u64,
+    }
+
+    impl Account for Counter {}
+
+    #[test]
+    fn test_scratch_arena() {
+        let mut buf = [0u64; 4];
+        // SAFETY: u64 buffer reinterpreted as 32 bytes
+        let bytes = unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, 32) };
+        let mut arena = ScratchArena::new(bytes);
+
+        let a = arena.take(3).unwrap();
+        let counter = arena.take_as::<Counter>().unwrap();
+        a[0] = 1;
+        counter.value = 7;
+        assert_eq!(arena.remaining(), 32 - 16);
+        assert!(arena.take(17).is_err());
+        assert_eq!(buf[1], 7);
+    }
+
+    #[test]
+    fn test_scratch_chunks() {
+        let program_id = [9; 32];
+        let mut account = MockAccount::new([1; 32], program_id, 0, &[0; 16]).writable(true);
+
+        // Chunks written by separate instructions
+        {
+            let info = account.info();
+            let mut scratch = Scratch::new(&info, &program_id).unwrap();
+            assert!(scratch.is_empty());
+            assert_eq!(scratch.capacity(), 8);
+            scratch.write_chunk(0, &[1, 2, 3]).unwrap();
+        }
+        {
+            let info = account.info();
+            let mut scratch = Scratch::new(&info, &program_id).unwrap();
+            scratch.write_chunk(3, &[4, 5]).unwrap();
+            scratch.write_chunk(1, &[7]).unwrap();
+            assert_eq!(scratch.read_all(), &[1, 7, 3, 4, 5]);
+
+            assert_eq!(
+                scratch.write_chunk(6, &[6]),
+                Err(ProgramError::InvalidArgument)
+            );
+            assert_eq!(
+                scratch.write_chunk(5, &[0; 4]),
+                Err(ProgramError::AccountDataTooSmall)
+            );
+            assert_eq!(scratch.read_all(), &[1, 7, 3, 4, 5]);
+
+            scratch.clear();
+            assert!(scratch.read_all().is_empty());
+        }
+        assert_eq!(account.data(), &[0; 16]);
+
+        let mut small = MockAccount::new([2; 32], program_id, 0, &[0; 4]).writable(true);
+        assert!(Scratch::new(&small.info(), &program_id).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use pinocchio::pubkey::Pubkey;
+
+/// Byte encoding of a typed PDA seed, used by [`seeds!`](crate::seeds).
+pub trait SeedBytes {
+    type Bytes: AsRef<[u8]>;
+
+    fn seed_bytes(&self) -> Self::Bytes;
+}
+
+impl SeedBytes for Pubkey {
+    type Bytes = Pubkey;
+
+    #[inline(always)]
+    fn seed_bytes(&self) -> Self::Bytes {
+        *self
+    }
+}
+
+macro_rules! impl_seed_bytes_le {
+    ($($ty:ty),*) => {
+        $(
+            impl SeedBytes for $ty {
+                type Bytes = [u8; core::mem::size_of::<$ty>()];
+
+                #[inline(always)]
+                fn seed_bytes(&self) -> Self::Bytes {
+                    self.to_le_bytes()
+                }
+            }
+        )*
+    };
+}
+
+impl_seed_bytes_le!(u8, u16, u32, u64, u128, i64);
+
+#[cfg(test)]
+mod tests {
+    crate::seeds!(struct TestSeeds { b"vault", owner: Pubkey, index: u64, b"x" });
+
+    use pinocchio::pubkey::Pubkey;
+
+    #[test]
+    fn test_seeds() {
+        let seeds = TestSeeds::new([7; 32], 42);
+        assert_eq!(TestSeeds::LEN, 4);
+
+        let s = seeds.seeds();
+        assert_eq!(s[0], b"vault");
+        assert_eq!(s[1], &[7; 32]);
+        assert_eq!(s[2], &42u64.to_le_bytes());
+        assert_eq!(s[3], b"x");
+
+        let signer = seeds.signer(254);
+        assert_eq!(signer.bump(), 254);
+        assert_eq!(signer.signer_seeds().as_slice().len(), 5);
+    }
+}
@@ -0,0 +1,154 @@
+//! Deterministic selection and shuffling from a 32 byte seed, e.g. one from a
+//! [`RandomnessSource`](crate::RandomnessSource).
+//!
+//! # Bias
+//!
+//! Reducing a random `u64` with `x % n` favors the first `2^64 % n` values:
+//! each of them is hit `floor(2^64 / n) + 1` times out of `2^64` instead of
+//! `floor(2^64 / n)`. The bias is tiny for small `n`, but it is systematic and
+//! grows with `n`, and `x % n` over a `u8` or a hash byte is outright skewed.
+//!
+//! [`SeedStream::below`] removes it by rejection: draws in the incomplete
+//! last block of `2^64 % n` values are discarded, leaving every result with
+//! exactly the same number of preimages. Fewer than half of the draws are
+//! rejected for any `n`, so the expected cost is under two draws.
+//!
+//! Results are only as unpredictable as the seed, see the caveats in
+//! [`pseudo_random_seed`](crate::pseudo_random_seed).
+//!
+//! ```ignore
+//! let seed = randomness.random_seed(raffle.key())?;
+//! let winners = select_k::<3>(&seed, entrants, 3)?;
+//! ```
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{hashv, trace, StackVec};
+
+/// Stream of uniform `u64` expanded from a seed as `keccak(seed || counter)`,
+/// four words per hash.
+pub struct SeedStream {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    used: usize,
+}
+
+impl SeedStream {
+    pub const fn new(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            counter: 0,
+            block: [0; 32],
+            used: 32,
+        }
+    }
+
+    /// Next uniform `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        if self.used == 32 {
+            self.block = hashv(&[&self.seed, &self.counter.to_le_bytes()]);
+            self.counter += 1;
+            self.used = 0;
+        }
+        let mut word = [0; 8];
+        word.copy_from_slice(&self.block[self.used..self.used + 8]);
+        self.used += 8;
+        u64::from_le_bytes(word)
+    }
+
+    /// Uniform value in `0..bound`, without modulo bias. Panics if `bound` is zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // 2^64 % bound, the size of the incomplete last block
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return x % bound;
+            }
+        }
+    }
+}
+
+/// Fisher–Yates shuffle of `items`, every permutation being equally likely.
+pub fn shuffle<T>(seed: &[u8; 32], items: &mut [T]) {
+    let mut stream = SeedStream::new(*seed);
+    for i in (1..items.len()).rev() {
+        let j = stream.below(i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Selects `k` distinct indices of `0..n`, every subset being equally likely.
+///
+/// Uses Floyd's algorithm, which needs `k` draws and no `n`-sized buffer. The
+/// order of the result is not uniform, [`shuffle`] it if ranks matter.
+#[track_caller]
+pub fn select_k<const K: usize>(
+    seed: &[u8; 32],
+    n: u64,
+    k: usize,
+) -> Result<StackVec<u64, K>, ProgramError> {
+    if k > K || k as u64 > n {
+        return Err(trace(
+            "Selecting more items than available",
+            ProgramError::InvalidArgument,
+        ));
+    }
+    let mut stream = SeedStream::new(*seed);
+    let mut selected = StackVec::new();
+    for j in n - k as u64..n {
+        let t = stream.below(j + 1);
+        selected.push(if selected.contains(&t) { j } else { t });
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_below_is_unbiased() {
+        let mut stream = SeedStream::new([7; 32]);
+        let mut counts = [0u32; 3];
+        for _ in 0..3000 {
+            counts[stream.below(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|c| (850..1150).contains(c)));
+
+        // Same seed, same stream
+        assert_eq!(
+            SeedStream::new([1; 32]).next_u64(),
+            SeedStream::new([1; 32]).next_u64()
+        );
+    }
+
+    #[test]
+    fn test_select_k() {
+        for s in 0..50u8 {
+            let selected = select_k::<5>(&[s; 32], 10, 5).unwrap();
+            assert_eq!(selected.len(), 5);
+            for (i, a) in selected.iter().enumerate() {
+                assert!(*a < 10);
+                assert!(!selected[i + 1..].contains(a));
+            }
+        }
+        assert_eq!(select_k::<3>(&[0; 32], 3, 3).unwrap().len(), 3);
+        assert!(select_k::<3>(&[0; 32], 2, 3).is_err());
+        assert!(select_k::<2>(&[0; 32], 10, 3).is_err());
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items = [0, 1, 2, 3, 4, 5, 6, 7];
+        shuffle(&[3; 32], &mut items);
+        let mut sorted = items;
+        sorted.sort();
+        assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+        let mut again = [0, 1, 2, 3, 4, 5, 6, 7];
+        shuffle(&[3; 32], &mut again);
+        assert_eq!(items, again);
+    }
+}
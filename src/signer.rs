@@ -0,0 +1,80 @@
+use pinocchio::{
+    instruction::{Seed, Signer},
+    pubkey::{MAX_SEEDS, MAX_SEED_LEN},
+};
+
+use crate::StackVec;
+
+/// Seeds and bump of a program-derived address, reusable as a CPI signer.
+///
+/// ```ignore
+/// let vault = PdaSigner::new([b"vault", owner.key()], bump);
+/// vault.with_signer(|signers| transfer.invoke_signed(signers))?;
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PdaSigner<'a, const N: usize> {
+    seeds: [&'a [u8]; N],
+    bump: [u8; 1],
+}
+
+impl<'a, const N: usize> PdaSigner<'a, N> {
+    pub const fn new(seeds: [&'a [u8]; N], bump: u8) -> Self {
+        assert!(N < MAX_SEEDS, "too many seeds");
+        // A compile error when used in a const context
+        let mut i = 0;
+        while i < N {
+            assert!(seeds[i].len() <= MAX_SEED_LEN, "seed is too long");
+            i += 1;
+        }
+        Self {
+            seeds,
+            bump: [bump],
+        }
+    }
+
+    #[inline(always)]
+    pub const fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    /// Seeds without the bump.
+    #[inline(always)]
+    pub const fn seeds(&self) -> &[&'a [u8]; N] {
+        &self.seeds
+    }
+
+    /// Builds the signer seeds, bump included.
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> SignerSeeds<'_> {
+        let mut seeds = StackVec::new();
+        for seed in self.seeds {
+            seeds.push(Seed::from(seed));
+        }
+        seeds.push(Seed::from(self.bump.as_ref()));
+        SignerSeeds { seeds }
+    }
+
+    /// Calls `f` with a single-element signers slice for `invoke_signed`.
+    #[inline(always)]
+    pub fn with_signer<R>(&self, f: impl FnOnce(&[Signer]) -> R) -> R {
+        let seeds = self.signer_seeds();
+        f(&[seeds.signer()])
+    }
+}
+
+/// Signer seeds built by [`PdaSigner::signer_seeds`].
+pub struct SignerSeeds<'a> {
+    seeds: StackVec<Seed<'a>, MAX_SEEDS>,
+}
+
+impl<'a> SignerSeeds<'a> {
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[Seed<'a>] {
+        self.seeds.as_slice()
+    }
+
+    #[inline(always)]
+    pub fn signer(&self) -> Signer<'a, '_> {
+        Signer::from(self.as_slice())
+    }
+}
@@ -0,0 +1,302 @@
+//! Global config account pattern: one PDA per program at seeds `[b"config"]`.
+//!
+//! ```ignore
+//! account!(MyAccount, MyConfig, bump = bump);
+//! impl ConfigAdmin for MyConfig {
+//!     fn admin(&self) -> &Pubkey { &self.admin }
+//! }
+//!
+//! // `ADMIN` is fixed by the program, e.g. its upgrade authority
+//! Config::<MyConfig>::create(payer, config, admin, &ADMIN, &crate::ID, bump, |c| {
+//!     c.admin = *admin.key();
+//!     c.bump = bump;
+//! })?;
+//! let cfg = Config::<MyConfig>::load(config, &crate::ID)?;
+//! Config::<MyConfig>::update(config, admin, &crate::ID, |c| c.fee_bps = 30)?;
+//! ```
+
+use core::marker::PhantomData;
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+
+use crate::{
+    derive_pda, pubkey_eq, trace, AccountDeserialize, AsAccount, CreateProgramAccount,
+    Discriminator, PdaSigner, SavedBump, Validation,
+};
+
+/// Seed of the config PDA.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+const CONFIG_SEEDS: &[&[u8]] = &[CONFIG_SEED];
+
+/// Config types gated by an admin key.
+pub trait ConfigAdmin {
+    fn admin(&self) -> &Pubkey;
+}
+
+/// Typed access to the program's config singleton.
+pub struct Config<T>(PhantomData<T>);
+
+impl<T> Config<T>
+where
+    T: AccountDeserialize + Discriminator + SavedBump + ConfigAdmin,
+{
+    /// Derives the config PDA of `program_id`.
+    #[inline(always)]
+    pub fn pda(program_id: &Pubkey) -> (Pubkey, u8) {
+        find_program_address(CONFIG_SEEDS, program_id)
+    }
+
+    /// Creates the config account and initializes it with `init`, which must
+    /// store `bump` so that later loads can verify the PDA, and `authority` as
+    /// the admin.
+    ///
+    /// `authority` must sign and be `expected_authority`: the config PDA is
+    /// known in advance, so anyone could otherwise create it first and make
+    /// themselves admin.
+    pub fn create<F>(
+        payer: &AccountInfo,
+        config: &AccountInfo,
+        authority: &AccountInfo,
+        expected_authority: &Pubkey,
+        program_id: &Pubkey,
+        bump: u8,
+        init: F,
+    ) -> ProgramResult
+    where
+        F: FnOnce(&mut T),
+    {
+        Validation::default().is_signer(true).run(authority)?;
+        if !pubkey_eq(authority.key(), expected_authority) {
+            return Err(trace(
+                "Signer is not the config authority",
+                ProgramError::IncorrectAuthority,
+            ));
+        }
+        if !pubkey_eq(config.key(), &derive_pda(CONFIG_SEEDS, program_id, [bump])?) {
+            return Err(trace(
+                "Config is not the config PDA",
+                ProgramError::InvalidSeeds,
+            ));
+        }
+        Validation::default()
+            .is_writable(true)
+            .is_empty(true)
+            .run(config)?;
+
+        PdaSigner::new([CONFIG_SEED], bump).with_signer(|signers| {
+            CreateProgramAccount {
+                payer,
+                pda: config,
+                space: core::mem::size_of::<T>(),
+                owner: program_id,
+            }
+            .invoke_signed(signers)
+        })?;
+
+        let mut data = config.try_borrow_mut_data()?;
+        T::write_discriminator(&mut data);
+        let state = T::try_from_bytes_mut(&mut data)?;
+        init(&mut *state);
+
+        if state.bump() != bump {
+            return Err(trace(
+                "Config does not store its bump",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        if !pubkey_eq(state.admin(), authority.key()) {
+            return Err(trace(
+                "Config does not store its admin",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Loads the config, checking owner, type and PDA address.
+    pub fn load<'a>(
+        config: &'a AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Ref<'a, T>, ProgramError> {
+        Validation::default()
+            .has_seeds_with_saved_bump_of::<T>(CONFIG_SEEDS, program_id)
+            .run(config)?;
+        config.as_account::<T>(program_id)
+    }
+
+    /// Same as `load`, but returns a mutable reference.
+    pub fn load_mut<'a>(
+        config: &'a AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<RefMut<'a, T>, ProgramError> {
+        Validation::default()
+            .is_writable(true)
+            .has_seeds_with_saved_bump_of::<T>(CONFIG_SEEDS, program_id)
+            .run(config)?;
+        config.as_account_mut::<T>(program_id)
+    }
+
+    /// Applies `update` after checking that `admin` signed and is the config's admin.
+    pub fn update<F>(
+        config: &AccountInfo,
+        admin: &AccountInfo,
+        program_id: &Pubkey,
+        update: F,
+    ) -> ProgramResult
+    where
+        F: FnOnce(&mut T),
+    {
+        Validation::default().is_signer(true).run(admin)?;
+
+        let mut state = Self::load_mut(config, program_id)?;
+        if !pubkey_eq(state.admin(), admin.key()) {
+            return Err(trace(
+                "Signer is not the config admin",
+                ProgramError::IncorrectAuthority,
+            ));
+        }
+        update(&mut *state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{account, MockAccount};
+
+    #[repr(u8)]
+    enum TestAccount {
+        TestConfig = 1,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct TestConfig {
+        discriminator: u8,
+        bump: u8,
+        admin: Pubkey,
+        fee_bps: u8,
+    }
+
+    account!(TestAccount, TestConfig, bump = bump);
+
+    impl ConfigAdmin for TestConfig {
+        fn admin(&self) -> &Pubkey {
+            &self.admin
+        }
+    }
+
+    const BUMP: u8 = 255;
+
+    fn config_data(admin: &Pubkey) -> [u8; 35] {
+        let mut data = [0; 35];
+        data[0] = TestAccount::TestConfig as u8;
+        data[1] = BUMP;
+        data[2..34].copy_from_slice(admin);
+        data
+    }
+
+    #[test]
+    fn test_config() {
+        let program_id = [9; 32];
+        let (admin, other) = ([1; 32], [2; 32]);
+        let config_key = derive_pda(CONFIG_SEEDS, &program_id, [BUMP]).unwrap();
+
+        let mut config =
+            MockAccount::new(config_key, program_id, 100, &config_data(&admin)).writable(true);
+        let mut squatter =
+            MockAccount::new([5; 32], program_id, 100, &config_data(&other)).writable(true);
+        let mut read_only = MockAccount::new(config_key, program_id, 100, &config_data(&admin));
+        let mut admin_signer = MockAccount::new(admin, [0; 32], 100, &[]).signer(true);
+        let mut admin_non_signer = MockAccount::new(admin, [0; 32], 100, &[]);
+        let mut other_signer = MockAccount::new(other, [0; 32], 100, &[]).signer(true);
+
+        assert_eq!(
+            Config::<TestConfig>::load(&config.info(), &program_id)
+                .unwrap()
+                .admin,
+            admin
+        );
+        assert!(matches!(
+            Config::<TestConfig>::load(&squatter.info(), &program_id),
+            Err(ProgramError::InvalidSeeds)
+        ));
+        assert!(matches!(
+            Config::<TestConfig>::load_mut(&squatter.info(), &program_id),
+            Err(ProgramError::InvalidSeeds)
+        ));
+        assert!(matches!(
+            Config::<TestConfig>::load_mut(&read_only.info(), &program_id),
+            Err(ProgramError::InvalidAccountData)
+        ));
+
+        let update = |config: &mut MockAccount, admin: &mut MockAccount| {
+            Config::<TestConfig>::update(&config.info(), &admin.info(), &program_id, |c| {
+                c.fee_bps = 30
+            })
+        };
+        assert_eq!(
+            update(&mut config, &mut admin_non_signer),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            update(&mut config, &mut other_signer),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(
+            update(&mut squatter, &mut other_signer),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(config.data()[34], 0);
+        assert_eq!(update(&mut config, &mut admin_signer), Ok(()));
+        assert_eq!(config.data()[34], 30);
+    }
+
+    #[test]
+    fn test_config_create_checks_authority() {
+        let program_id = [9; 32];
+        let admin = [1; 32];
+        let config_key = derive_pda(CONFIG_SEEDS, &program_id, [BUMP]).unwrap();
+
+        let mut payer = MockAccount::new([3; 32], [0; 32], 1_000_000, &[])
+            .signer(true)
+            .writable(true);
+        let mut config = MockAccount::new(config_key, [0; 32], 0, &[]).writable(true);
+        let mut not_config = MockAccount::new([5; 32], [0; 32], 0, &[]).writable(true);
+        let mut admin_signer = MockAccount::new(admin, [0; 32], 100, &[]).signer(true);
+        let mut admin_non_signer = MockAccount::new(admin, [0; 32], 100, &[]);
+
+        let mut create =
+            |config: &mut MockAccount, authority: &mut MockAccount, expected: &Pubkey| {
+                Config::<TestConfig>::create(
+                    &payer.info(),
+                    &config.info(),
+                    &authority.info(),
+                    expected,
+                    &program_id,
+                    BUMP,
+                    |_| unreachable!(),
+                )
+            };
+        assert_eq!(
+            create(&mut config, &mut admin_non_signer, &admin),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        // Anyone else creating the config first is rejected
+        assert_eq!(
+            create(&mut config, &mut admin_signer, &[2; 32]),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(
+            create(&mut not_config, &mut admin_signer, &admin),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
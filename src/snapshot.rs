@@ -0,0 +1,154 @@
+//! Before/after state capture for invariant checks inside handlers.
+
+use core::ops::Range;
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{hashv, trace, HASH_LENGTH};
+
+pub const ERROR_INVARIANT_VIOLATED: u32 = 9;
+
+#[track_caller]
+#[inline(always)]
+fn violated(msg: &str) -> ProgramError {
+    trace(msg, ProgramError::Custom(ERROR_INVARIANT_VIOLATED))
+}
+
+/// Snapshot of `N` numeric fields (balances, counters, ...).
+///
+/// Capture at instruction start, then compare against the same fields
+/// read again at the end of the handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Snapshot<const N: usize> {
+    values: [u64; N],
+}
+
+impl<const N: usize> Snapshot<N> {
+    #[inline(always)]
+    pub const fn capture(values: [u64; N]) -> Self {
+        Self { values }
+    }
+
+    /// Captures the lamports of `accounts`.
+    #[inline(always)]
+    pub fn lamports(accounts: [&AccountInfo; N]) -> Self {
+        Self::capture(accounts.map(|ai| ai.lamports()))
+    }
+
+    /// Value of field `field` at capture time.
+    #[track_caller]
+    #[inline(always)]
+    pub fn get(&self, field: usize) -> Result<u64, ProgramError> {
+        match self.values.get(field) {
+            Some(value) => Ok(*value),
+            None => Err(trace(
+                "Snapshot field is out of range",
+                ProgramError::InvalidArgument,
+            )),
+        }
+    }
+
+    /// Fails if any field differs from its captured value.
+    #[track_caller]
+    pub fn assert_unchanged(&self, current: [u64; N]) -> ProgramResult {
+        if self.values != current {
+            return Err(violated("Snapshot fields changed"));
+        }
+        Ok(())
+    }
+
+    /// Fails if field `field` did not change by exactly `expected`.
+    #[track_caller]
+    pub fn assert_delta(&self, field: usize, current: u64, expected: i128) -> ProgramResult {
+        if current as i128 - self.get(field)? as i128 != expected {
+            return Err(violated("Snapshot field has unexpected delta"));
+        }
+        Ok(())
+    }
+
+    /// Fails if the sum of all fields changed, e.g. lamports moved between
+    /// the captured accounts but were neither minted nor burned.
+    #[track_caller]
+    pub fn assert_conserved(&self, current: [u64; N]) -> ProgramResult {
+        let sum = |v: &[u64; N]| v.iter().map(|&x| x as u128).sum::<u128>();
+        if sum(&self.values) != sum(&current) {
+            return Err(violated("Snapshot total is not conserved"));
+        }
+        Ok(())
+    }
+}
+
+/// Keccak hash of selected byte ranges of an account's data.
+pub struct DataSnapshot<'a> {
+    ranges: &'a [Range<usize>],
+    hash: [u8; HASH_LENGTH],
+}
+
+impl<'a> DataSnapshot<'a> {
+    /// Hashes `ranges` of the account data.
+    pub fn capture(ai: &AccountInfo, ranges: &'a [Range<usize>]) -> Result<Self, ProgramError> {
+        Ok(Self {
+            ranges,
+            hash: hash_ranges(&ai.try_borrow_data()?, ranges)?,
+        })
+    }
+
+    /// Fails if any captured byte range changed.
+    #[track_caller]
+    pub fn assert_unchanged(&self, ai: &AccountInfo) -> ProgramResult {
+        if hash_ranges(&ai.try_borrow_data()?, self.ranges)? != self.hash {
+            return Err(violated("Account data changed"));
+        }
+        Ok(())
+    }
+}
+
+fn hash_ranges(data: &[u8], ranges: &[Range<usize>]) -> Result<[u8; HASH_LENGTH], ProgramError> {
+    let mut hash = [0u8; HASH_LENGTH];
+    for range in ranges {
+        let bytes = data
+            .get(range.clone())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        // Chain ranges so that moving bytes between ranges changes the hash
+        hash = hashv(&[&hash, &(bytes.len() as u64).to_le_bytes(), bytes]);
+    }
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = Snapshot::capture([100, 50]);
+        assert!(snapshot.assert_unchanged([100, 50]).is_ok());
+        assert!(snapshot.assert_unchanged([100, 51]).is_err());
+        assert!(snapshot.assert_conserved([70, 80]).is_ok());
+        assert_eq!(
+            snapshot.assert_conserved([70, 81]),
+            Err(ProgramError::Custom(ERROR_INVARIANT_VIOLATED))
+        );
+        assert!(snapshot.assert_delta(0, 70, -30).is_ok());
+        assert!(snapshot.assert_delta(1, 80, 29).is_err());
+
+        assert_eq!(snapshot.get(1), Ok(50));
+        assert_eq!(snapshot.get(2), Err(ProgramError::InvalidArgument));
+        assert_eq!(
+            snapshot.assert_delta(2, 80, 30),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_hash_ranges() {
+        let data = [1u8, 2, 3, 4, 5, 6];
+        let a = hash_ranges(&data, &[0..2, 4..6]).unwrap();
+        let mut changed = data;
+        changed[3] = 9;
+        assert_eq!(a, hash_ranges(&changed, &[0..2, 4..6]).unwrap());
+        changed[5] = 9;
+        assert_ne!(a, hash_ranges(&changed, &[0..2, 4..6]).unwrap());
+        assert!(hash_ranges(&data, &[4..7]).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Fixed-capacity vector on the stack, for bounded lists in no_std.
+
+use core::{
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+/// Vector of up to `N` elements stored inline, e.g. `AccountMeta`s, seeds or
+/// signers of a CPI whose count is only known at runtime.
+///
+/// ```ignore
+/// let mut metas = StackVec::<AccountMeta, 8>::new();
+/// metas.push(AccountMeta::writable_signer(payer.key()));
+/// for ai in remaining {
+///     metas.try_push(AccountMeta::readonly(ai.key())).map_err(|_| ProgramError::InvalidArgument)?;
+/// }
+/// ```
+pub struct StackVec<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> StackVec<T, N> {
+    pub const CAPACITY: usize = N;
+
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            // SAFETY: an array of `MaybeUninit` needs no initialization
+            items: unsafe { MaybeUninit::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline(always)]
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Appends `item`, or gives it back if the vector is full.
+    #[inline(always)]
+    pub fn try_push(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+        self.items[self.len].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends `item`.
+    ///
+    /// # Panics
+    /// If the vector is full.
+    #[inline(always)]
+    pub fn push(&mut self, item: T) {
+        if self.try_push(item).is_err() {
+            panic!("StackVec is full");
+        }
+    }
+
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: elements below the old `len` are initialized, `len` no longer covers this one
+        Some(unsafe { self.items[self.len].assume_init_read() })
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { core::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    #[inline(always)]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` elements are initialized
+        unsafe { core::slice::from_raw_parts_mut(self.items.as_mut_ptr() as *mut T, self.len) }
+    }
+}
+
+impl<T, const N: usize> Default for StackVec<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Deref for StackVec<T, N> {
+    type Target = [T];
+
+    #[inline(always)]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for StackVec<T, N> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> Drop for StackVec<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: the first `len` elements are initialized and dropped once
+        unsafe { core::ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+
+    #[test]
+    fn test_stack_vec() {
+        let mut v = StackVec::<u8, 2>::new();
+        assert!(v.is_empty());
+        v.push(1);
+        assert_eq!(v.try_push(2), Ok(()));
+        assert_eq!(v.try_push(3), Err(3));
+        assert_eq!(v.as_slice(), &[1, 2]);
+        v[0] = 5;
+        assert_eq!(v.pop(), Some(2));
+        assert_eq!(&*v, &[5]);
+    }
+
+    #[test]
+    fn test_stack_vec_drops() {
+        let rc = Rc::new(());
+        {
+            let mut v = StackVec::<Rc<()>, 4>::new();
+            v.push(rc.clone());
+            v.push(rc.clone());
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+}
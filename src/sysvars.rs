@@ -0,0 +1,218 @@
+//! Clock and rent access for handlers.
+//!
+//! On-chain these are the sysvar syscalls. With the `library` feature the
+//! values are injected per thread instead, so instruction logic can be called as
+//! plain Rust functions in host tests:
+//!
+//! Handlers that take a [`SysvarSource`] can also be given fixed values directly,
+//! and share one [`CachedSysvars`] to load each sysvar once.
+//!
+//! ```ignore
+//! set_clock(Clock { slot: 1, unix_timestamp: 1_700_000_000, ..clock });
+//! set_rent(rent);
+//!
+//! process_deposit(&accounts, &data)?;
+//! ```
+
+use core::cell::Cell;
+
+use pinocchio::{
+    program_error::ProgramError,
+    sysvars::{clock::Clock, rent::Rent},
+};
+
+/// Current `Clock` sysvar.
+#[cfg(not(feature = "library"))]
+#[inline(always)]
+pub fn get_clock() -> Result<Clock, ProgramError> {
+    pinocchio::sysvars::Sysvar::get()
+}
+
+/// Current `Rent` sysvar.
+#[cfg(not(feature = "library"))]
+#[inline(always)]
+pub fn get_rent() -> Result<Rent, ProgramError> {
+    pinocchio::sysvars::Sysvar::get()
+}
+
+#[cfg(feature = "library")]
+mod injected {
+    use core::cell::Cell;
+
+    use pinocchio::sysvars::{clock::Clock, rent::Rent};
+
+    std::thread_local! {
+        pub(super) static CLOCK: Cell<Option<Clock>> = const { Cell::new(None) };
+        pub(super) static RENT: Cell<Option<Rent>> = const { Cell::new(None) };
+    }
+}
+
+/// Injected `Clock`, fails with `UnsupportedSysvar` until [`set_clock`] is called.
+#[cfg(feature = "library")]
+pub fn get_clock() -> Result<Clock, ProgramError> {
+    injected::CLOCK
+        .with(Cell::get)
+        .ok_or(ProgramError::UnsupportedSysvar)
+}
+
+/// Injected `Rent`, fails with `UnsupportedSysvar` until [`set_rent`] is called.
+#[cfg(feature = "library")]
+pub fn get_rent() -> Result<Rent, ProgramError> {
+    injected::RENT
+        .with(Cell::get)
+        .ok_or(ProgramError::UnsupportedSysvar)
+}
+
+/// Injects the `Clock` returned by [`get_clock`] on the current thread.
+#[cfg(feature = "library")]
+pub fn set_clock(clock: Clock) {
+    injected::CLOCK.with(|cell| cell.set(Some(clock)));
+}
+
+/// Injects the `Rent` returned by [`get_rent`] on the current thread.
+#[cfg(feature = "library")]
+pub fn set_rent(rent: Rent) {
+    injected::RENT.with(|cell| cell.set(Some(rent)));
+}
+
+/// Provider of the sysvars a handler reads.
+pub trait SysvarSource {
+    fn clock(&self) -> Result<Clock, ProgramError>;
+    fn rent(&self) -> Result<Rent, ProgramError>;
+}
+
+/// Reads sysvars through [`get_clock`] and [`get_rent`] on every call.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyscallSysvars;
+
+impl SysvarSource for SyscallSysvars {
+    #[inline(always)]
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        get_clock()
+    }
+
+    #[inline(always)]
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        get_rent()
+    }
+}
+
+/// Fixed sysvar values, e.g. for deterministic tests.
+#[derive(Clone, Copy)]
+pub struct FixedSysvars {
+    pub clock: Clock,
+    pub rent: Rent,
+}
+
+impl SysvarSource for FixedSysvars {
+    #[inline(always)]
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Ok(self.clock)
+    }
+
+    #[inline(always)]
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        Ok(self.rent)
+    }
+}
+
+/// Loads each sysvar from `S` on first use only, to pass through a handler
+/// instead of repeating the syscalls.
+pub struct CachedSysvars<S: SysvarSource = SyscallSysvars> {
+    source: S,
+    clock: Cell<Option<Clock>>,
+    rent: Cell<Option<Rent>>,
+}
+
+impl CachedSysvars {
+    pub const fn new() -> Self {
+        Self::with_source(SyscallSysvars)
+    }
+}
+
+impl Default for CachedSysvars {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: SysvarSource> CachedSysvars<S> {
+    pub const fn with_source(source: S) -> Self {
+        Self {
+            source,
+            clock: Cell::new(None),
+            rent: Cell::new(None),
+        }
+    }
+}
+
+impl<S: SysvarSource> SysvarSource for CachedSysvars<S> {
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        if let Some(clock) = self.clock.get() {
+            return Ok(clock);
+        }
+        let clock = self.source.clock()?;
+        self.clock.set(Some(clock));
+        Ok(clock)
+    }
+
+    fn rent(&self) -> Result<Rent, ProgramError> {
+        if let Some(rent) = self.rent.get() {
+            return Ok(rent);
+        }
+        let rent = self.source.rent()?;
+        self.rent.set(Some(rent));
+        Ok(rent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(slot: u64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp: 0,
+            epoch: 1,
+            leader_schedule_epoch: 2,
+            unix_timestamp: 1_700_000_000,
+        }
+    }
+
+    /// Returns a later slot on every call.
+    struct Ticking(Cell<u64>);
+
+    impl SysvarSource for Ticking {
+        fn clock(&self) -> Result<Clock, ProgramError> {
+            self.0.set(self.0.get() + 1);
+            Ok(clock(self.0.get()))
+        }
+
+        fn rent(&self) -> Result<Rent, ProgramError> {
+            Err(ProgramError::UnsupportedSysvar)
+        }
+    }
+
+    #[test]
+    fn test_cached_sysvars() {
+        let ticking = Ticking(Cell::new(0));
+        assert_eq!(ticking.clock().unwrap().slot, 1);
+        assert_eq!(ticking.clock().unwrap().slot, 2);
+
+        let cached = CachedSysvars::with_source(Ticking(Cell::new(0)));
+        assert_eq!(cached.clock().unwrap().slot, 1);
+        assert_eq!(cached.clock().unwrap().slot, 1);
+        assert_eq!(cached.rent().err(), Some(ProgramError::UnsupportedSysvar));
+    }
+
+    #[cfg(feature = "library")]
+    #[test]
+    fn test_injected_sysvars() {
+        assert_eq!(get_clock().err(), Some(ProgramError::UnsupportedSysvar));
+
+        set_clock(clock(7));
+        assert_eq!(get_clock().unwrap().slot, 7);
+        assert_eq!(get_rent().err(), Some(ProgramError::UnsupportedSysvar));
+    }
+}
@@ -0,0 +1,391 @@
+//! Host-side mock accounts, to unit test validation and instruction handlers
+//! without deploying.
+//!
+//! ```ignore
+//! let mut payer = MockAccount::new([1; 32], SYSTEM_PROGRAM_ID, 1_000_000_000, &[]).signer(true);
+//! let mut counter = MockAccount::new([2; 32], crate::ID, 1_000_000, &Counter::default().to_bytes())
+//!     .writable(true);
+//! let accounts = [payer.info(), counter.info()];
+//!
+//! process_instruction(&crate::ID, as_account_infos(&accounts), &data)?;
+//! assert_eq!(counter.data()[8..16], 1u64.to_le_bytes());
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{fmt::Debug, marker::PhantomData, ops::Deref};
+
+use pinocchio::{account_info::AccountInfo, pubkey::Pubkey};
+
+use crate::{
+    AccountDeserialize, AccountStore, PodU128LE, PodU16LE, PodU32LE, PodU64LE,
+    MAX_PERMITTED_DATA_INCREASE,
+};
+
+/// Runtime account header: borrow state, flags, resize delta, key, owner,
+/// lamports and data length, followed by the account data.
+const HEADER_LEN: usize = 88;
+
+const BORROW_STATE_OFFSET: usize = 0;
+const IS_SIGNER_OFFSET: usize = 1;
+const IS_WRITABLE_OFFSET: usize = 2;
+const EXECUTABLE_OFFSET: usize = 3;
+const KEY_OFFSET: usize = 8;
+const OWNER_OFFSET: usize = 40;
+const LAMPORTS_OFFSET: usize = 72;
+const DATA_LEN_OFFSET: usize = 80;
+
+/// Borrow state of an account that is not borrowed, same as the runtime's
+/// non-duplicate marker.
+const NOT_BORROWED: u8 = u8::MAX;
+
+/// Account laid out the way the runtime serializes it, handing out
+/// [`AccountInfo`]s that point into it.
+pub struct MockAccount {
+    // `u64` words keep the header 8-byte aligned.
+    buffer: Vec<u64>,
+}
+
+impl MockAccount {
+    /// Read-only, non-signer account holding a copy of `data`, with room to
+    /// grow by [`MAX_PERMITTED_DATA_INCREASE`] like on-chain accounts.
+    pub fn new(key: Pubkey, owner: Pubkey, lamports: u64, data: &[u8]) -> Self {
+        let len = HEADER_LEN + data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut account = Self {
+            buffer: vec![0u64; len.div_ceil(8)],
+        };
+        let bytes = account.bytes_mut();
+        bytes[BORROW_STATE_OFFSET] = NOT_BORROWED;
+        bytes[KEY_OFFSET..KEY_OFFSET + 32].copy_from_slice(&key);
+        bytes[OWNER_OFFSET..OWNER_OFFSET + 32].copy_from_slice(&owner);
+        bytes[LAMPORTS_OFFSET..LAMPORTS_OFFSET + 8].copy_from_slice(&lamports.to_le_bytes());
+        bytes[DATA_LEN_OFFSET..DATA_LEN_OFFSET + 8]
+            .copy_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes[HEADER_LEN..HEADER_LEN + data.len()].copy_from_slice(data);
+        account
+    }
+
+    pub fn signer(mut self, is_signer: bool) -> Self {
+        self.bytes_mut()[IS_SIGNER_OFFSET] = is_signer as u8;
+        self
+    }
+
+    pub fn writable(mut self, is_writable: bool) -> Self {
+        self.bytes_mut()[IS_WRITABLE_OFFSET] = is_writable as u8;
+        self
+    }
+
+    pub fn executable(mut self, executable: bool) -> Self {
+        self.bytes_mut()[EXECUTABLE_OFFSET] = executable as u8;
+        self
+    }
+
+    /// Returns an [`AccountInfo`] over this account, borrowing the mock for as
+    /// long as the info is in use.
+    ///
+    /// Changes made through it, e.g. by an instruction handler, are visible
+    /// through [`Self::lamports`] and [`Self::data`] once it is dropped.
+    pub fn info(&mut self) -> MockAccountInfo<'_> {
+        // SAFETY: `AccountInfo` is a single pointer to the runtime account header,
+        // which `buffer` holds with the runtime layout and alignment. The returned
+        // guard mutably borrows the mock, so the buffer outlives the info.
+        let info = unsafe {
+            core::mem::transmute::<*mut u8, AccountInfo>(self.buffer.as_mut_ptr() as *mut u8)
+        };
+        MockAccountInfo {
+            info,
+            _mock: PhantomData,
+        }
+    }
+
+    pub fn key(&self) -> &Pubkey {
+        self.bytes()[KEY_OFFSET..KEY_OFFSET + 32]
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn lamports(&self) -> u64 {
+        let bytes = self.bytes();
+        u64::from_le_bytes(
+            bytes[LAMPORTS_OFFSET..LAMPORTS_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Current account data, including changes of its length.
+    pub fn data(&self) -> &[u8] {
+        let bytes = self.bytes();
+        let len = u64::from_le_bytes(
+            bytes[DATA_LEN_OFFSET..DATA_LEN_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        );
+        &bytes[HEADER_LEN..HEADER_LEN + len as usize]
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: any `u64` buffer is a valid byte buffer
+        unsafe {
+            core::slice::from_raw_parts(self.buffer.as_ptr() as *const u8, self.buffer.len() * 8)
+        }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        // SAFETY: any `u64` buffer is a valid byte buffer
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.buffer.as_mut_ptr() as *mut u8,
+                self.buffer.len() * 8,
+            )
+        }
+    }
+}
+
+/// [`AccountInfo`] over a [`MockAccount`], valid while the mock is borrowed.
+#[repr(transparent)]
+pub struct MockAccountInfo<'a> {
+    info: AccountInfo,
+    _mock: PhantomData<&'a mut MockAccount>,
+}
+
+impl Deref for MockAccountInfo<'_> {
+    type Target = AccountInfo;
+
+    fn deref(&self) -> &AccountInfo {
+        &self.info
+    }
+}
+
+/// Returns the [`AccountInfo`]s of `infos`, e.g. to call an instruction processor.
+pub fn as_account_infos<'b>(infos: &'b [MockAccountInfo<'_>]) -> &'b [AccountInfo] {
+    // SAFETY: `MockAccountInfo` is a transparent wrapper around `AccountInfo`, and
+    // the returned slice can't outlive the guards, which borrow their mocks.
+    unsafe { core::slice::from_raw_parts(infos.as_ptr() as *const AccountInfo, infos.len()) }
+}
+
+/// Returns the [`AccountInfo`]s of `accounts`, in order, borrowing them for as
+/// long as the infos are in use.
+pub fn mock_account_infos(accounts: &mut [MockAccount]) -> Vec<MockAccountInfo<'_>> {
+    accounts.iter_mut().map(MockAccount::info).collect()
+}
+
+impl AccountStore for [MockAccount] {
+    fn account_data(&self, key: &Pubkey) -> Option<&[u8]> {
+        self.iter()
+            .find(|account| account.key() == key)
+            .map(MockAccount::data)
+    }
+}
+
+impl<const N: usize> AccountStore for [MockAccount; N] {
+    fn account_data(&self, key: &Pubkey) -> Option<&[u8]> {
+        self.as_slice().account_data(key)
+    }
+}
+
+/// Panics unless account `key` of `store` deserializes to `expected`.
+#[track_caller]
+pub fn assert_account_eq<T>(store: &(impl AccountStore + ?Sized), key: &Pubkey, expected: &T)
+where
+    T: AccountDeserialize + PartialEq + Debug + Copy,
+{
+    let data = store
+        .account_data(key)
+        .unwrap_or_else(|| panic!("account {:?} not found", key));
+    let actual = T::try_from_bytes_unaligned(data)
+        .unwrap_or_else(|e| panic!("account {:?} failed to deserialize: {:?}", key, e));
+    assert_eq!(&actual, expected, "account {:?} differs", key);
+}
+
+/// Plain value of an account field, unwrapping pod types, for [`assert_field!`](crate::assert_field).
+pub trait FieldValue {
+    type Value: PartialEq + Debug;
+
+    fn value(&self) -> Self::Value;
+}
+
+macro_rules! field_value_plain {
+    ($($ty:ty),*) => {
+        $(impl FieldValue for $ty {
+            type Value = $ty;
+
+            fn value(&self) -> $ty {
+                *self
+            }
+        })*
+    };
+}
+
+field_value_plain!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool);
+
+impl<const N: usize> FieldValue for [u8; N] {
+    type Value = [u8; N];
+
+    fn value(&self) -> [u8; N] {
+        *self
+    }
+}
+
+macro_rules! field_value_pod {
+    ($($pod:ty => $ty:ty),*) => {
+        $(impl FieldValue for $pod {
+            type Value = $ty;
+
+            fn value(&self) -> $ty {
+                self.get()
+            }
+        })*
+    };
+}
+
+field_value_pod!(PodU16LE => u16, PodU32LE => u32, PodU64LE => u64, PodU128LE => u128);
+
+/// Asserts that a field of a deserialized account equals `expected`, comparing
+/// pod fields by their plain value.
+///
+/// ```ignore
+/// let vault = Vault::try_from_bytes_unaligned(accounts[1].data())?;
+/// assert_field!(vault, balance, 100);
+/// ```
+#[macro_export]
+macro_rules! assert_field {
+    ($account:expr, $field:ident, $expected:expr) => {
+        assert_eq!(
+            $crate::FieldValue::value(&$account.$field),
+            $expected,
+            concat!("field `", stringify!($field), "` differs")
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use pinocchio::program_error::ProgramError;
+
+    use super::*;
+    use crate::{AsAccount, LamportsExt, Validation};
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Counter {
+        discriminator: [u8; 8],
+        count: u64,
+    }
+
+    crate::account!(Counter, discriminator = [7, 0, 0, 0, 0, 0, 0, 0]);
+
+    #[test]
+    fn test_mock_account() {
+        let program_id = [9u8; 32];
+        let mut data = [0u8; 16];
+        data[0] = 7;
+        data[8] = 3;
+        let mut accounts = [
+            MockAccount::new([1; 32], [0; 32], 100, &[]).signer(true),
+            MockAccount::new([2; 32], program_id, 50, &data).writable(true),
+        ];
+        let infos = mock_account_infos(&mut accounts);
+
+        assert_eq!(infos[0].key(), &[1; 32]);
+        assert!(Validation::default().is_signer(true).run(&infos[0]).is_ok());
+        assert_eq!(
+            Validation::default().is_signer(true).run(&infos[1]),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert!(Validation::default()
+            .is_writable(true)
+            .has_owner(&program_id)
+            .is_initialized(&program_id)
+            .run(&infos[1])
+            .is_ok());
+        assert_eq!(
+            Validation::default().has_owner(&program_id).run(&infos[0]),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+        assert!(Validation::default()
+            .has_discriminator_in_with_namespace(&program_id, &[5, 7], 0)
+            .run(&infos[1])
+            .is_ok());
+        assert_eq!(
+            Validation::default()
+                .has_discriminator_in_with_namespace(&program_id, &[5, 7], 1)
+                .run(&infos[1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        assert_eq!(
+            infos[1].as_account::<Counter>(&program_id).unwrap().count,
+            3
+        );
+        infos[1]
+            .as_account_mut::<Counter>(&program_id)
+            .unwrap()
+            .count = 4;
+        infos[0].transfer_lamports_to(&infos[1], 30).unwrap();
+        drop(infos);
+
+        assert_eq!(accounts[0].lamports(), 70);
+        assert_eq!(accounts[1].lamports(), 80);
+        assert_eq!(accounts[1].data()[8], 4);
+
+        let counter = Counter {
+            discriminator: [7, 0, 0, 0, 0, 0, 0, 0],
+            count: 4,
+        };
+        assert_account_eq(&accounts, &[2; 32], &counter);
+        crate::assert_field!(counter, count, 4);
+    }
+
+    #[cfg(feature = "library")]
+    #[test]
+    fn test_has_rent_epoch_exempt() {
+        use pinocchio::sysvars::rent::Rent;
+
+        let rent = Rent {
+            lamports_per_byte_year: 3_480,
+            exemption_threshold: 2.0,
+            burn_percent: 50,
+        };
+        let minimum = rent.minimum_balance(16);
+        crate::set_rent(rent);
+
+        let mut exempt = MockAccount::new([1; 32], [9; 32], minimum, &[0; 16]);
+        let mut paying = MockAccount::new([2; 32], [9; 32], minimum - 1, &[0; 16]);
+        let validation = Validation::default().has_rent_epoch_exempt();
+        assert!(validation.run(&exempt.info()).is_ok());
+        assert_eq!(
+            validation.run(&paying.info()),
+            Err(ProgramError::AccountNotRentExempt)
+        );
+
+        // Presets
+        let mut state = [0; 16];
+        state[0] = 1;
+        let mut exempt = MockAccount::new([1; 32], [9; 32], minimum, &state);
+        let mut paying = MockAccount::new([2; 32], [9; 32], minimum - 1, &state);
+        let validation = Validation::state_account(&[9; 32]);
+        assert!(validation.run(&exempt.info()).is_ok());
+        assert_eq!(
+            validation.run(&paying.info()),
+            Err(ProgramError::AccountNotRentExempt)
+        );
+
+        let token = [0; crate::TOKEN_ACCOUNT_LEN];
+        let minimum = rent.minimum_balance(token.len());
+        let owner = crate::TOKEN_PROGRAM_ID;
+        let mut exempt = MockAccount::new([1; 32], owner, minimum, &token);
+        let mut paying = MockAccount::new([2; 32], owner, minimum - 1, &token);
+        let mut not_token = MockAccount::new([3; 32], [9; 32], minimum, &token);
+        let validation = Validation::token_account();
+        assert!(validation.run(&exempt.info()).is_ok());
+        assert_eq!(
+            validation.run(&paying.info()),
+            Err(ProgramError::AccountNotRentExempt)
+        );
+        assert_eq!(
+            validation.run(&not_token.info()),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Price tick and size lot conversions for order books.
+//!
+//! Prices are quoted in whole ticks of `tick_size` and sizes in whole lots of
+//! `lot_size`, both in base units. User input off the grid is rejected with a
+//! traced error rather than silently rounded, unless rounding is asked for.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{trace, Rounding};
+
+pub const ERROR_PRICE_NOT_ON_TICK: u32 = 24;
+pub const ERROR_SIZE_NOT_ON_LOT: u32 = 25;
+
+/// Number of ticks of `price`, failing unless it is a multiple of `tick_size`.
+#[track_caller]
+pub fn price_to_tick(price: u64, tick_size: u64) -> Result<u64, ProgramError> {
+    check_step(tick_size)?;
+    if price % tick_size != 0 {
+        return Err(trace(
+            "Price is not a multiple of the tick size",
+            ProgramError::Custom(ERROR_PRICE_NOT_ON_TICK),
+        ));
+    }
+    Ok(price / tick_size)
+}
+
+/// Price of `tick`, failing on overflow.
+#[track_caller]
+pub fn tick_to_price(tick: u64, tick_size: u64) -> Result<u64, ProgramError> {
+    tick.checked_mul(tick_size)
+        .ok_or_else(|| trace("Tick price overflow", ProgramError::ArithmeticOverflow))
+}
+
+/// Number of lots of `size`, failing unless it is a non-zero multiple of `lot_size`.
+#[track_caller]
+pub fn size_to_lots(size: u64, lot_size: u64) -> Result<u64, ProgramError> {
+    check_step(lot_size)?;
+    if size == 0 || size % lot_size != 0 {
+        return Err(trace(
+            "Size is not a multiple of the lot size",
+            ProgramError::Custom(ERROR_SIZE_NOT_ON_LOT),
+        ));
+    }
+    Ok(size / lot_size)
+}
+
+/// Size of `lots`, failing on overflow.
+#[track_caller]
+pub fn lots_to_size(lots: u64, lot_size: u64) -> Result<u64, ProgramError> {
+    lots.checked_mul(lot_size)
+        .ok_or_else(|| trace("Lot size overflow", ProgramError::ArithmeticOverflow))
+}
+
+/// Nearest tick of `price` in the `rounding` direction, e.g. `Down` for bids
+/// and `Up` for asks.
+#[track_caller]
+pub fn round_price_to_tick(
+    price: u64,
+    tick_size: u64,
+    rounding: Rounding,
+) -> Result<u64, ProgramError> {
+    check_step(tick_size)?;
+    let (tick, remainder) = (price / tick_size, price % tick_size);
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => remainder > 0,
+        Rounding::Nearest => remainder >= tick_size - remainder,
+    };
+    Ok(tick + round_up as u64)
+}
+
+/// Whole lots of `size`, dropping the remainder, e.g. to fill as much of an
+/// order as the book allows.
+#[track_caller]
+pub fn size_to_lots_floor(size: u64, lot_size: u64) -> Result<u64, ProgramError> {
+    check_step(lot_size)?;
+    Ok(size / lot_size)
+}
+
+#[track_caller]
+#[inline(always)]
+fn check_step(step: u64) -> Result<(), ProgramError> {
+    if step == 0 {
+        return Err(trace(
+            "Tick or lot size is zero",
+            ProgramError::InvalidArgument,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks() {
+        assert_eq!(price_to_tick(1_500, 100), Ok(15));
+        assert_eq!(
+            price_to_tick(1_550, 100),
+            Err(ProgramError::Custom(ERROR_PRICE_NOT_ON_TICK))
+        );
+        assert_eq!(price_to_tick(1_500, 0), Err(ProgramError::InvalidArgument));
+        assert_eq!(tick_to_price(15, 100), Ok(1_500));
+        assert_eq!(
+            tick_to_price(u64::MAX, 2),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+
+        assert_eq!(round_price_to_tick(1_549, 100, Rounding::Down), Ok(15));
+        assert_eq!(round_price_to_tick(1_501, 100, Rounding::Up), Ok(16));
+        assert_eq!(round_price_to_tick(1_550, 100, Rounding::Nearest), Ok(16));
+        assert_eq!(round_price_to_tick(1_500, 100, Rounding::Up), Ok(15));
+    }
+
+    #[test]
+    fn test_lots() {
+        assert_eq!(size_to_lots(30, 10), Ok(3));
+        assert_eq!(
+            size_to_lots(35, 10),
+            Err(ProgramError::Custom(ERROR_SIZE_NOT_ON_LOT))
+        );
+        assert_eq!(
+            size_to_lots(0, 10),
+            Err(ProgramError::Custom(ERROR_SIZE_NOT_ON_LOT))
+        );
+        assert_eq!(size_to_lots_floor(35, 10), Ok(3));
+        assert_eq!(lots_to_size(3, 10), Ok(30));
+    }
+}
@@ -0,0 +1,235 @@
+//! Read-only views over SPL Token and Token-2022 accounts.
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{trace, TOKEN_PROGRAM_IDS};
+
+pub const ERROR_TOKEN_DELTA_MISMATCH: u32 = 14;
+pub const ERROR_INSUFFICIENT_TOKEN_BALANCE: u32 = 17;
+
+/// Length of the base token account layout, Token-2022 extensions follow it.
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Zero-copy view over the base layout of a token account.
+pub struct TokenAccountView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TokenAccountView<'a> {
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        if data.len() < TOKEN_ACCOUNT_LEN {
+            return Err(trace(
+                "Token account has wrong length",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        Ok(Self { data })
+    }
+
+    #[inline(always)]
+    fn pubkey_at(&self, offset: usize) -> &'a Pubkey {
+        // SAFETY: length is checked in `try_from_bytes`
+        self.data[offset..offset + 32].try_into().unwrap()
+    }
+
+    #[inline(always)]
+    fn u64_at(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.data[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// `COption` tag is a little-endian `u32`, `1` meaning `Some`.
+    #[inline(always)]
+    fn is_some_at(&self, offset: usize) -> bool {
+        self.data[offset] != 0
+    }
+
+    #[inline(always)]
+    pub fn mint(&self) -> &'a Pubkey {
+        self.pubkey_at(0)
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &'a Pubkey {
+        self.pubkey_at(32)
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        self.u64_at(64)
+    }
+
+    #[inline(always)]
+    pub fn delegate(&self) -> Option<&'a Pubkey> {
+        self.is_some_at(72).then(|| self.pubkey_at(76))
+    }
+
+    /// `0` uninitialized, `1` initialized, `2` frozen.
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.data[108]
+    }
+
+    #[inline(always)]
+    pub fn is_native(&self) -> Option<u64> {
+        self.is_some_at(109).then(|| self.u64_at(113))
+    }
+
+    #[inline(always)]
+    pub fn delegated_amount(&self) -> u64 {
+        self.u64_at(121)
+    }
+
+    #[inline(always)]
+    pub fn close_authority(&self) -> Option<&'a Pubkey> {
+        self.is_some_at(129).then(|| self.pubkey_at(133))
+    }
+
+    /// Fails unless the account holds at least `min_amount` of `mint`.
+    #[track_caller]
+    pub fn check_min_balance(&self, mint: &Pubkey, min_amount: u64) -> ProgramResult {
+        if self.mint().ne(mint) {
+            return Err(trace(
+                "Token account has wrong mint",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        if self.amount() < min_amount {
+            return Err(trace(
+                "Token balance is too low",
+                ProgramError::Custom(ERROR_INSUFFICIENT_TOKEN_BALANCE),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Reads the amount of a token account owned by SPL Token or Token-2022.
+#[inline(always)]
+pub fn token_amount(account: &AccountInfo) -> Result<u64, ProgramError> {
+    if !TOKEN_PROGRAM_IDS.iter().any(|id| account.is_owned_by(id)) {
+        return Err(trace(
+            "Token account has wrong owner",
+            ProgramError::InvalidAccountOwner,
+        ));
+    }
+    Ok(TokenAccountView::try_from_bytes(&account.try_borrow_data()?)?.amount())
+}
+
+/// Token-gated access: fails unless `token_account` is a token account of `mint`
+/// holding at least `min_amount`.
+///
+/// Only the token program owner is checked, callers gating on a specific wallet
+/// must also compare [`TokenAccountView::owner`].
+#[track_caller]
+pub fn require_min_token_balance(
+    token_account: &AccountInfo,
+    mint: &Pubkey,
+    min_amount: u64,
+) -> ProgramResult {
+    if !TOKEN_PROGRAM_IDS
+        .iter()
+        .any(|id| token_account.is_owned_by(id))
+    {
+        return Err(trace(
+            "Token account has wrong owner",
+            ProgramError::InvalidAccountOwner,
+        ));
+    }
+    TokenAccountView::try_from_bytes(&token_account.try_borrow_data()?)?
+        .check_min_balance(mint, min_amount)
+}
+
+/// Records a token account's amount at handler start to assert on its change at
+/// handler end, defending against fee-on-transfer and transfer-hook surprises.
+pub struct TokenDeltaGuard<'a> {
+    account: &'a AccountInfo,
+    start: u64,
+}
+
+impl<'a> TokenDeltaGuard<'a> {
+    pub fn new(account: &'a AccountInfo) -> Result<Self, ProgramError> {
+        Ok(Self {
+            account,
+            start: token_amount(account)?,
+        })
+    }
+
+    #[inline(always)]
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Change of the amount since `new`.
+    #[inline(always)]
+    pub fn delta(&self) -> Result<i128, ProgramError> {
+        Ok(token_amount(self.account)? as i128 - self.start as i128)
+    }
+
+    /// Fails unless the amount changed by exactly `expected`.
+    #[track_caller]
+    pub fn assert_delta(&self, expected: i128) -> ProgramResult {
+        self.assert_delta_between(expected, expected)
+    }
+
+    /// Fails unless the amount changed by a value within `min..=max`.
+    #[track_caller]
+    pub fn assert_delta_between(&self, min: i128, max: i128) -> ProgramResult {
+        let delta = self.delta()?;
+        if delta < min || delta > max {
+            return Err(trace(
+                "Token amount changed unexpectedly",
+                ProgramError::Custom(ERROR_TOKEN_DELTA_MISMATCH),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_account_view() {
+        let mut data = [0u8; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(&[1; 32]);
+        data[32..64].copy_from_slice(&[2; 32]);
+        data[64..72].copy_from_slice(&500u64.to_le_bytes());
+        data[72] = 1;
+        data[76..108].copy_from_slice(&[3; 32]);
+        data[108] = 1;
+        data[121..129].copy_from_slice(&20u64.to_le_bytes());
+
+        let view = TokenAccountView::try_from_bytes(&data).unwrap();
+        assert_eq!(view.mint(), &[1; 32]);
+        assert_eq!(view.owner(), &[2; 32]);
+        assert_eq!(view.amount(), 500);
+        assert_eq!(view.delegate(), Some(&[3; 32]));
+        assert_eq!(view.state(), 1);
+        assert_eq!(view.is_native(), None);
+        assert_eq!(view.delegated_amount(), 20);
+        assert_eq!(view.close_authority(), None);
+
+        assert!(TokenAccountView::try_from_bytes(&data[..164]).is_err());
+    }
+
+    #[test]
+    fn test_check_min_balance() {
+        let mut data = [0u8; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(&[1; 32]);
+        data[64..72].copy_from_slice(&500u64.to_le_bytes());
+
+        let view = TokenAccountView::try_from_bytes(&data).unwrap();
+        assert!(view.check_min_balance(&[1; 32], 500).is_ok());
+        assert_eq!(
+            view.check_min_balance(&[1; 32], 501),
+            Err(ProgramError::Custom(ERROR_INSUFFICIENT_TOKEN_BALANCE))
+        );
+        assert_eq!(
+            view.check_min_balance(&[2; 32], 1),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Instruction sequences commonly sent together with a program instruction.
+//!
+//! ```ignore
+//! let accounts = DepositAccounts { user: &user, vault: &vault, mint: &mint };
+//! let instructions = TransactionTemplate::new()
+//!     .compute_unit_limit(40_000)
+//!     .compute_unit_price(10_000)
+//!     .create_ata_idempotent(&user, &user, &mint, &TOKEN_PROGRAM_ID)?
+//!     .program_instruction(&vault_program::ID, &accounts.metas(), Deposit { amount }.to_bytes())
+//!     .build();
+//! ```
+
+use std::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use pinocchio::pubkey::Pubkey;
+use solana_instruction::{AccountMeta, Instruction};
+
+use crate::{
+    find_program_address_offchain, ASSOCIATED_TOKEN_PROGRAM_ID, COMPUTE_BUDGET_PROGRAM_ID,
+    SYSTEM_PROGRAM_ID,
+};
+
+/// Converts instruction accounts built on-chain style, e.g. by the `metas()` of
+/// an `instruction!` accounts struct, into a client instruction.
+pub fn to_instruction(
+    program_id: &Pubkey,
+    metas: &[pinocchio::instruction::AccountMeta],
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: (*program_id).into(),
+        accounts: metas
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: (*meta.pubkey).into(),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data,
+    }
+}
+
+/// Associated token account of `wallet` for `mint`, `None` if no bump yields
+/// an off-curve address.
+pub fn associated_token_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Option<Pubkey> {
+    find_program_address_offchain(&[wallet, token_program, mint], &ASSOCIATED_TOKEN_PROGRAM_ID)
+        .map(|(ata, _)| ata)
+}
+
+/// Max compute units of a transaction.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute units consumed by each compute budget instruction.
+pub const COMPUTE_BUDGET_INSTRUCTION_CU: u32 = 150;
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit`.
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    compute_budget_instruction(2, &units.to_le_bytes())
+}
+
+/// `ComputeBudgetInstruction::SetComputeUnitPrice`, in micro-lamports per compute unit.
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    compute_budget_instruction(3, &micro_lamports.to_le_bytes())
+}
+
+fn compute_budget_instruction(discriminator: u8, args: &[u8]) -> Instruction {
+    let mut data = vec![discriminator];
+    data.extend_from_slice(args);
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID.into(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Measured compute units per instruction, e.g. the consumption reported by
+/// [`cu_budget_test!`](crate::cu_budget_test) runs or the budgets they enforce.
+#[derive(Clone, Debug, Default)]
+pub struct CuRegistry {
+    entries: Vec<(String, u64)>,
+}
+
+impl CuRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `units` for `name`, keeping the highest measurement.
+    pub fn record(&mut self, name: &str, units: u64) -> &mut Self {
+        match self.entries.iter_mut().find(|(n, _)| n == name) {
+            Some((_, max)) => *max = (*max).max(units),
+            None => self.entries.push((name.to_string(), units)),
+        }
+        self
+    }
+
+    /// Parses `<name> <units>` lines, as recorded by bench runs with
+    /// `PINSTEEL_CU_REGISTRY` set. Repeated names keep the highest measurement.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut registry = Self::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, units) = line
+                .trim()
+                .rsplit_once(' ')
+                .ok_or_else(|| format!("invalid CU registry line `{}`", line))?;
+            let units = units
+                .parse()
+                .map_err(|_| format!("invalid compute units in `{}`", line))?;
+            registry.record(name.trim_end(), units);
+        }
+        Ok(registry)
+    }
+
+    /// Loads a registry file, see [`Self::parse`].
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, units)| *units)
+    }
+
+    /// Compute unit limit for a transaction of the `names` instructions plus
+    /// both compute budget instructions, with `margin_bps` of headroom and
+    /// capped at [`MAX_COMPUTE_UNIT_LIMIT`]. `None` if an instruction was never
+    /// measured.
+    pub fn estimate(&self, names: &[&str], margin_bps: u16) -> Option<u32> {
+        let mut total = 2 * COMPUTE_BUDGET_INSTRUCTION_CU as u64;
+        for name in names {
+            total = total.saturating_add(self.get(name)?);
+        }
+        let with_margin = total
+            .saturating_mul(10_000 + margin_bps as u64)
+            .div_ceil(10_000);
+        Some(with_margin.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
+    }
+}
+
+/// Builds the instructions of a transaction: compute budget first, then the
+/// other instructions in the order they were added.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionTemplate {
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+    instructions: Vec<Instruction>,
+}
+
+impl TransactionTemplate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests `units` compute units instead of the default 200k per instruction.
+    pub fn compute_unit_limit(&mut self, units: u32) -> &mut Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Sets the priority fee, in micro-lamports per compute unit.
+    pub fn compute_unit_price(&mut self, micro_lamports: u64) -> &mut Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// Creates the associated token account of `wallet` for `mint` unless it
+    /// exists. `None` if the address can't be derived, see
+    /// [`associated_token_address`].
+    pub fn create_ata_idempotent(
+        &mut self,
+        payer: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Option<&mut Self> {
+        let ata = associated_token_address(wallet, mint, token_program)?;
+        Some(self.instruction(Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID.into(),
+            accounts: vec![
+                AccountMeta::new((*payer).into(), true),
+                AccountMeta::new(ata.into(), false),
+                AccountMeta::new_readonly((*wallet).into(), false),
+                AccountMeta::new_readonly((*mint).into(), false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID.into(), false),
+                AccountMeta::new_readonly((*token_program).into(), false),
+            ],
+            // `CreateIdempotent`
+            data: vec![1],
+        }))
+    }
+
+    /// Instruction of a pinsteel program, see [`to_instruction`].
+    pub fn program_instruction(
+        &mut self,
+        program_id: &Pubkey,
+        metas: &[pinocchio::instruction::AccountMeta],
+        data: Vec<u8>,
+    ) -> &mut Self {
+        self.instruction(to_instruction(program_id, metas, data))
+    }
+
+    pub fn instruction(&mut self, instruction: Instruction) -> &mut Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn build(&self) -> Vec<Instruction> {
+        let mut out = Vec::with_capacity(self.instructions.len() + 2);
+        if let Some(units) = self.compute_unit_limit {
+            out.push(set_compute_unit_limit(units));
+        }
+        if let Some(micro_lamports) = self.compute_unit_price {
+            out.push(set_compute_unit_price(micro_lamports));
+        }
+        out.extend(self.instructions.iter().cloned());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TOKEN_PROGRAM_ID;
+
+    #[test]
+    fn test_transaction_template() {
+        let (user, mint, program_id) = ([1; 32], [2; 32], [3; 32]);
+        let metas = [pinocchio::instruction::AccountMeta::writable_signer(&user)];
+
+        let instructions = TransactionTemplate::new()
+            .program_instruction(&program_id, &metas, vec![7, 8])
+            .create_ata_idempotent(&user, &user, &mint, &TOKEN_PROGRAM_ID)
+            .unwrap()
+            .compute_unit_price(5)
+            .compute_unit_limit(40_000)
+            .build();
+
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].data, [2, 0x40, 0x9c, 0, 0]);
+        assert_eq!(instructions[1].data, [3, 5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(instructions[2].program_id.to_bytes(), program_id);
+        assert_eq!(instructions[2].data, [7, 8]);
+        assert!(instructions[2].accounts[0].is_signer);
+
+        let ata = associated_token_address(&user, &mint, &TOKEN_PROGRAM_ID).unwrap();
+        assert_eq!(instructions[3].accounts[1].pubkey.to_bytes(), ata);
+        assert_eq!(instructions[3].data, [1]);
+    }
+
+    #[test]
+    fn test_cu_registry() {
+        let mut registry = CuRegistry::new();
+        registry.record("deposit", 9_000).record("deposit", 9_700);
+        registry.record("withdraw", 12_000);
+        assert_eq!(registry.get("deposit"), Some(9_700));
+
+        // (9_700 + 12_000 + 300) * 1.1
+        assert_eq!(
+            registry.estimate(&["deposit", "withdraw"], 1_000),
+            Some(24_200)
+        );
+        assert_eq!(registry.estimate(&["swap"], 1_000), None);
+
+        let parsed = CuRegistry::parse("deposit 9000\nwithdraw 12000\n\ndeposit 9700\n").unwrap();
+        assert_eq!(parsed.get("deposit"), Some(9_700));
+        assert_eq!(parsed.get("withdraw"), Some(12_000));
+        assert!(CuRegistry::parse("deposit").is_err());
+        assert!(CuRegistry::parse("deposit many").is_err());
+
+        registry.record("huge", 2_000_000);
+        assert_eq!(
+            registry.estimate(&["huge"], 0),
+            Some(MAX_COMPUTE_UNIT_LIMIT)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Price smoothing for oracle consumers: a time-weighted average price
+//! accumulator read over windows between two observations, and an exponential
+//! moving average updated on every price.
+//!
+//! ```ignore
+//! market.twap.update(price, now);
+//! let twap = market.twap.read(&market.window_start, now);
+//! ```
+
+use crate::{to_u64_checked, widen, PodU128LE, PodU16LE, PodU64LE};
+
+/// Running sum of `price * seconds`.
+///
+/// The cumulative price wraps on overflow, like Uniswap's accumulators: only
+/// differences between two observations are meaningful, and those stay correct
+/// across a wrap as long as the window is shorter than a full wrap.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwapAccumulator {
+    pub cumulative_price: PodU128LE,
+    pub last_price: PodU64LE,
+    /// Unix timestamp of the last update.
+    pub last_timestamp: PodU64LE,
+}
+
+/// Snapshot of a [`TwapAccumulator`], the start of a TWAP window.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TwapObservation {
+    pub cumulative_price: PodU128LE,
+    pub timestamp: PodU64LE,
+}
+
+impl TwapAccumulator {
+    /// Cumulative price extrapolated to `now` with the last price.
+    #[inline(always)]
+    fn cumulative_at(&self, now: u64) -> u128 {
+        let elapsed = now.saturating_sub(self.last_timestamp.get());
+        self.cumulative_price
+            .get()
+            .wrapping_add(widen(self.last_price.get()) * widen(elapsed))
+    }
+
+    /// Accounts the previous price up to `now`, then records `price`.
+    pub fn update(&mut self, price: u64, now: u64) {
+        self.cumulative_price.set(self.cumulative_at(now));
+        self.last_price.set(price);
+        self.last_timestamp.set(now.max(self.last_timestamp.get()));
+    }
+
+    /// Current observation, to be stored as the start of a window.
+    pub fn observe(&self, now: u64) -> TwapObservation {
+        TwapObservation {
+            cumulative_price: PodU128LE::new(self.cumulative_at(now)),
+            timestamp: PodU64LE::new(now),
+        }
+    }
+
+    /// Average price over the window from `start` to `now`, `None` if the window is empty.
+    pub fn read(&self, start: &TwapObservation, now: u64) -> Option<u64> {
+        let window = now.checked_sub(start.timestamp.get()).filter(|w| *w > 0)?;
+        let delta = self
+            .cumulative_at(now)
+            .wrapping_sub(start.cumulative_price.get());
+        to_u64_checked(delta / widen(window)).ok()
+    }
+}
+
+/// Exponential moving average, `value += (price - value) * alpha` per update.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ema {
+    pub value: PodU64LE,
+    /// Smoothing factor in basis points, `10_000` tracks the latest price.
+    pub alpha_bps: PodU16LE,
+}
+
+impl Ema {
+    pub const fn new(initial: u64, alpha_bps: u16) -> Self {
+        Self {
+            value: PodU64LE::new(initial),
+            alpha_bps: PodU16LE::new(alpha_bps),
+        }
+    }
+
+    pub fn update(&mut self, price: u64) {
+        let value = self.value.get() as i128;
+        let alpha = self.alpha_bps.get().min(10_000) as i128;
+        let next = value + (price as i128 - value) * alpha / 10_000;
+        // Stays between the previous value and `price`
+        self.value.set(next as u64);
+    }
+
+    #[inline(always)]
+    pub fn read(&self) -> u64 {
+        self.value.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_twap() {
+        let mut twap = TwapAccumulator::default();
+        twap.update(100, 1_000);
+        let start = twap.observe(1_000);
+
+        twap.update(200, 1_010);
+        // 10s at 100, 30s at 200
+        assert_eq!(twap.read(&start, 1_040), Some(175));
+        assert_eq!(twap.read(&start, 1_000), None);
+
+        // Differences survive the cumulative price wrapping
+        let mut wrapping = TwapAccumulator {
+            cumulative_price: PodU128LE::new(u128::MAX - 50),
+            last_price: PodU64LE::new(10),
+            last_timestamp: PodU64LE::new(0),
+        };
+        let start = wrapping.observe(0);
+        wrapping.update(10, 20);
+        assert_eq!(wrapping.read(&start, 20), Some(10));
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut ema = Ema::new(100, 5_000);
+        ema.update(200);
+        assert_eq!(ema.read(), 150);
+        ema.update(50);
+        assert_eq!(ema.read(), 100);
+
+        let mut latest = Ema::new(100, 10_000);
+        latest.update(7);
+        assert_eq!(latest.read(), 7);
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(core::mem::size_of::<TwapAccumulator>(), 32);
+        assert_eq!(core::mem::align_of::<TwapAccumulator>(), 1);
+        assert_eq!(core::mem::size_of::<Ema>(), 10);
+    }
+}
@@ -1,6 +1,8 @@
 //! Helper functions for working with uint types
 
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{trace, Rounding};
 
 #[inline(always)]
 pub fn parse_u64(data: &[u8]) -> u64 {
@@ -17,10 +19,312 @@ pub fn parse_pubkey(data: &[u8]) -> Pubkey {
     data.try_into().expect("slice must be 32 bytes")
 }
 
+/// Narrows `value` to `u64`, failing with a traced `ArithmeticOverflow`
+/// instead of silently truncating.
+#[track_caller]
+#[inline(always)]
+pub fn to_u64_checked(value: u128) -> Result<u64, ProgramError> {
+    u64::try_from(value).map_err(|_| trace("Value exceeds u64", ProgramError::ArithmeticOverflow))
+}
+
+/// Widens `value` to `u128` for overflow-free intermediate math.
+#[inline(always)]
+pub const fn widen(value: u64) -> u128 {
+    value as u128
+}
+
+/// 256 bit unsigned integer, for intermediates of `u128` math that would
+/// otherwise overflow. Only what [`mul_div`] needs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct U256 {
+    // Field order makes the derived `Ord` compare numerically
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    pub const fn from_u128(value: u128) -> Self {
+        Self { hi: 0, lo: value }
+    }
+
+    /// Full product `a * b`, which can't overflow.
+    pub const fn full_mul(a: u128, b: u128) -> Self {
+        const MASK: u128 = u64::MAX as u128;
+        let (a1, a0) = (a >> 64, a & MASK);
+        let (b1, b0) = (b >> 64, b & MASK);
+        let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+
+        let mid = (p00 >> 64) + (p01 & MASK) + (p10 & MASK);
+        Self {
+            hi: p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64),
+            lo: (p00 & MASK) | (mid << 64),
+        }
+    }
+
+    /// Quotient and remainder of the division by `divisor`, `None` if it is zero.
+    pub const fn div_rem(self, divisor: u128) -> Option<(Self, u128)> {
+        if divisor == 0 {
+            return None;
+        }
+        if self.hi == 0 {
+            return Some((Self::from_u128(self.lo / divisor), self.lo % divisor));
+        }
+        // Binary long division, the remainder carries out of 128 bits at most once per step
+        let mut quotient = Self { hi: 0, lo: 0 };
+        let mut remainder = 0u128;
+        let mut i = 256;
+        while i > 0 {
+            i -= 1;
+            let bit = if i >= 128 {
+                (self.hi >> (i - 128)) & 1
+            } else {
+                (self.lo >> i) & 1
+            };
+            let carry = remainder >> 127;
+            remainder = (remainder << 1) | bit;
+            if carry == 1 || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                if i >= 128 {
+                    quotient.hi |= 1 << (i - 128);
+                } else {
+                    quotient.lo |= 1 << i;
+                }
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Returns `None` if the value exceeds `u128`.
+    pub const fn to_u128(self) -> Option<u128> {
+        if self.hi == 0 {
+            Some(self.lo)
+        } else {
+            None
+        }
+    }
+}
+
+// Off-chain, (de)serialize as 32 little-endian bytes
+#[cfg(feature = "client")]
+impl U256 {
+    fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&self.lo.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.hi.to_le_bytes());
+        bytes
+    }
+
+    fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        Self {
+            hi: u128::from_le_bytes(bytes[16..].try_into().unwrap()),
+            lo: u128::from_le_bytes(bytes[..16].try_into().unwrap()),
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl serde::Serialize for U256 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.to_le_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "client")]
+impl<'de> serde::Deserialize<'de> for U256 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <[u8; 32] as serde::Deserialize>::deserialize(deserializer).map(Self::from_le_bytes)
+    }
+}
+
+#[cfg(feature = "client")]
+impl borsh::BorshSerialize for U256 {
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+}
+
+#[cfg(feature = "client")]
+impl borsh::BorshDeserialize for U256 {
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self::from_le_bytes(bytes))
+    }
+}
+
+/// `a * b / denominator` without intermediate overflow, `None` if `denominator`
+/// is zero or the result exceeds `u128`.
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Option<u128> {
+    let (quotient, remainder) = U256::full_mul(a, b).div_rem(denominator)?;
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => remainder > 0,
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+    quotient.to_u128()?.checked_add(round_up as u128)
+}
+
+macro_rules! pod_le {
+    ($name:ident, $ty:ty) => {
+        #[doc = concat!("Little-endian `", stringify!($ty), "` with alignment 1, for use in account and instruction structs.")]
+        ///
+        /// `#[repr(C)]` structs built from these pods, bytes and pubkeys have
+        /// alignment 1 and no padding, so they can be embedded in other account
+        /// structs at any offset.
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct $name([u8; core::mem::size_of::<$ty>()]);
+
+        impl $name {
+            #[inline(always)]
+            pub const fn new(value: $ty) -> Self {
+                Self(value.to_le_bytes())
+            }
+
+            #[inline(always)]
+            pub const fn get(&self) -> $ty {
+                <$ty>::from_le_bytes(self.0)
+            }
+
+            #[inline(always)]
+            pub fn set(&mut self, value: $ty) {
+                self.0 = value.to_le_bytes();
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            #[inline(always)]
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<$ty> for $name {
+            #[inline(always)]
+            fn from(value: $ty) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for $ty {
+            #[inline(always)]
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        // Off-chain, (de)serialize as the plain integer
+        #[cfg(feature = "client")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&self.get(), serializer)
+            }
+        }
+
+        #[cfg(feature = "client")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <$ty as serde::Deserialize>::deserialize(deserializer).map(Self::new)
+            }
+        }
+
+        #[cfg(feature = "client")]
+        impl borsh::BorshSerialize for $name {
+            fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+                // Borsh integers are little-endian, same as the stored bytes
+                writer.write_all(&self.0)
+            }
+        }
+
+        #[cfg(feature = "client")]
+        impl borsh::BorshDeserialize for $name {
+            fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+                let mut bytes = [0u8; core::mem::size_of::<$ty>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(Self(bytes))
+            }
+        }
+    };
+}
+
+pod_le!(PodU16LE, u16);
+pod_le!(PodU32LE, u32);
+pod_le!(PodU64LE, u64);
+pod_le!(PodU128LE, u128);
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_pod_le() {
+        let mut pod = PodU64LE::new(420691337);
+        assert_eq!(pod.get(), 420691337);
+        assert_eq!(core::mem::align_of::<PodU64LE>(), 1);
+
+        pod.set(u64::MAX - 1);
+        assert_eq!(u64::from(pod), u64::MAX - 1);
+        assert_eq!(PodU128LE::from(7).get(), 7);
+        assert_eq!(PodU16LE::default().get(), 0);
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_pod_le_borsh() {
+        let pod = PodU32LE::new(0x01020304);
+        let bytes = borsh::to_vec(&pod).unwrap();
+        assert_eq!(bytes, borsh::to_vec(&0x01020304u32).unwrap());
+        assert_eq!(borsh::from_slice::<PodU32LE>(&bytes).unwrap(), pod);
+
+        let wide = U256::full_mul(u128::MAX, 3);
+        let bytes = borsh::to_vec(&wide).unwrap();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(bytes[..16], (u128::MAX - 2).to_le_bytes());
+        assert_eq!(bytes[16..], 2u128.to_le_bytes());
+        assert_eq!(borsh::from_slice::<U256>(&bytes).unwrap(), wide);
+    }
+
+    #[test]
+    fn test_to_u64_checked() {
+        assert_eq!(to_u64_checked(widen(u64::MAX)), Ok(u64::MAX));
+        assert_eq!(
+            to_u64_checked(widen(u64::MAX) + 1),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(
+            U256::full_mul(u128::MAX, u128::MAX),
+            U256 {
+                hi: u128::MAX - 1,
+                lo: 1
+            }
+        );
+        assert_eq!(
+            mul_div(u128::MAX, u128::MAX, u128::MAX, Rounding::Down),
+            Some(u128::MAX)
+        );
+        assert_eq!(
+            mul_div(1 << 100, 1 << 100, 1 << 120, Rounding::Down),
+            Some(1 << 80)
+        );
+        assert_eq!(mul_div(u128::MAX, 3, 2, Rounding::Down), None);
+        assert_eq!(mul_div(7, 1, 0, Rounding::Down), None);
+
+        // 10 * 10 / 3 = 33.3
+        assert_eq!(mul_div(10, 10, 3, Rounding::Down), Some(33));
+        assert_eq!(mul_div(10, 10, 3, Rounding::Up), Some(34));
+        assert_eq!(mul_div(10, 10, 3, Rounding::Nearest), Some(33));
+
+        // (2^128 - 1) * 2^64 / (2^66 + 1), checked against arbitrary precision
+        assert_eq!(
+            mul_div(u128::MAX, 1 << 64, (1 << 66) + 1, Rounding::Down),
+            Some(85070591730234615864690730353335205887)
+        );
+    }
+
     #[test]
     fn test_parse_u64() {
         let num = 420691337_u64;
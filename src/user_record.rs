@@ -0,0 +1,228 @@
+//! Per-user account pattern: one PDA per (user, resource) at seeds
+//! `[T::SEED, user, resource]`.
+//!
+//! ```ignore
+//! account!(MyAccount, Position, bump = bump);
+//! impl UserResource for Position {
+//!     const SEED: &'static [u8] = b"position";
+//! }
+//!
+//! UserRecord::<Position>::get_or_create(payer, position, user.key(), market.key(), &crate::ID, |p, bump| {
+//!     p.owner = *user.key();
+//!     p.bump = bump;
+//! })?;
+//! let mut p = UserRecord::<Position>::load_mut(position, user.key(), market.key(), &crate::ID)?;
+//! ```
+
+use core::marker::PhantomData;
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+};
+
+use crate::{
+    trace, AccountDeserialize, AsAccount, CreateProgramAccount, Discriminator, PdaSigner,
+    SavedBump, Validation,
+};
+
+/// Account types stored once per (user, resource) pair.
+pub trait UserResource {
+    /// First seed, distinguishing record types of the same program.
+    const SEED: &'static [u8];
+}
+
+/// Typed access to per-user records.
+pub struct UserRecord<T>(PhantomData<T>);
+
+impl<T> UserRecord<T>
+where
+    T: AccountDeserialize + Discriminator + SavedBump + UserResource,
+{
+    /// Seeds of the record of `user` for `resource`, without the bump.
+    #[inline(always)]
+    pub fn seeds<'a>(user: &'a Pubkey, resource: &'a Pubkey) -> [&'a [u8]; 3] {
+        [T::SEED, user, resource]
+    }
+
+    /// Derives the record PDA of `user` for `resource`.
+    #[inline(always)]
+    pub fn pda(user: &Pubkey, resource: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        find_program_address(&Self::seeds(user, resource), program_id)
+    }
+
+    /// Returns `true` if `record` was already created by `program_id`.
+    #[inline(always)]
+    pub fn exists(record: &AccountInfo, program_id: &Pubkey) -> bool {
+        record.is_owned_by(program_id) && !record.data_is_empty()
+    }
+
+    /// Creates the record and initializes it with `init` if it does not exist
+    /// yet, returning `true` if it was created. `init` is given the bump, which
+    /// it must store so that later loads can verify the PDA.
+    ///
+    /// Existing records are checked to be a `T` at the PDA of `user` and `resource`.
+    pub fn get_or_create<F>(
+        payer: &AccountInfo,
+        record: &AccountInfo,
+        user: &Pubkey,
+        resource: &Pubkey,
+        program_id: &Pubkey,
+        init: F,
+    ) -> Result<bool, ProgramError>
+    where
+        F: FnOnce(&mut T, u8),
+    {
+        let seeds = Self::seeds(user, resource);
+        if Self::exists(record, program_id) {
+            Validation::default()
+                .has_seeds_with_saved_bump_of::<T>(&seeds, program_id)
+                .run(record)?;
+            record.as_account::<T>(program_id)?;
+            return Ok(false);
+        }
+
+        let (pda, bump) = find_program_address(&seeds, program_id);
+        if record.key().ne(&pda) {
+            return Err(trace(
+                "User record is not the expected PDA",
+                ProgramError::InvalidSeeds,
+            ));
+        }
+        Validation::default().is_writable(true).run(record)?;
+
+        PdaSigner::new(seeds, bump).with_signer(|signers| {
+            CreateProgramAccount {
+                payer,
+                pda: record,
+                space: core::mem::size_of::<T>(),
+                owner: program_id,
+            }
+            .invoke_signed(signers)
+        })?;
+
+        let mut data = record.try_borrow_mut_data()?;
+        T::write_discriminator(&mut data);
+        let state = T::try_from_bytes_mut(&mut data)?;
+        init(&mut *state, bump);
+
+        if state.bump() != bump {
+            return Err(trace(
+                "User record does not store its bump",
+                ProgramError::InvalidAccountData,
+            ));
+        }
+        Ok(true)
+    }
+
+    /// Loads the record, checking owner, type and PDA address.
+    pub fn load<'a>(
+        record: &'a AccountInfo,
+        user: &Pubkey,
+        resource: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Ref<'a, T>, ProgramError> {
+        Validation::default()
+            .has_seeds_with_saved_bump_of::<T>(&Self::seeds(user, resource), program_id)
+            .run(record)?;
+        record.as_account::<T>(program_id)
+    }
+
+    /// Same as `load`, but returns a mutable reference.
+    pub fn load_mut<'a>(
+        record: &'a AccountInfo,
+        user: &Pubkey,
+        resource: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<RefMut<'a, T>, ProgramError> {
+        Validation::default()
+            .is_writable(true)
+            .has_seeds_with_saved_bump_of::<T>(&Self::seeds(user, resource), program_id)
+            .run(record)?;
+        record.as_account_mut::<T>(program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{account, MockAccount};
+
+    #[repr(u8)]
+    enum TestAccount {
+        Position = 1,
+        Order = 2,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Position {
+        discriminator: u8,
+        bump: u8,
+    }
+
+    account!(TestAccount, Position, bump = bump);
+
+    impl UserResource for Position {
+        const SEED: &'static [u8] = b"position";
+    }
+
+    fn record_data(discriminator: TestAccount) -> [u8; 2] {
+        [discriminator as u8, 255]
+    }
+
+    #[test]
+    fn test_user_record_rejects_before_deriving() {
+        let program_id = [9; 32];
+        let (user, market) = ([1; 32], [2; 32]);
+        let mut payer = MockAccount::new([3; 32], [0; 32], 1_000_000, &[])
+            .signer(true)
+            .writable(true);
+        let mut empty = MockAccount::new([4; 32], program_id, 0, &[]).writable(true);
+        let mut foreign =
+            MockAccount::new([5; 32], [7; 32], 100, &record_data(TestAccount::Position))
+                .writable(true);
+        let mut order =
+            MockAccount::new([6; 32], program_id, 100, &record_data(TestAccount::Order))
+                .writable(true);
+        let mut read_only = MockAccount::new(
+            [8; 32],
+            program_id,
+            100,
+            &record_data(TestAccount::Position),
+        );
+
+        assert!(!UserRecord::<Position>::exists(&empty.info(), &program_id));
+        assert!(!UserRecord::<Position>::exists(
+            &foreign.info(),
+            &program_id
+        ));
+        assert!(UserRecord::<Position>::exists(&order.info(), &program_id));
+
+        // An existing record of another type is not returned as created
+        assert_eq!(
+            UserRecord::<Position>::get_or_create(
+                &payer.info(),
+                &order.info(),
+                &user,
+                &market,
+                &program_id,
+                |_, _| unreachable!(),
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert!(matches!(
+            UserRecord::<Position>::load(&foreign.info(), &user, &market, &program_id),
+            Err(ProgramError::InvalidAccountOwner)
+        ));
+        assert!(matches!(
+            UserRecord::<Position>::load(&order.info(), &user, &market, &program_id),
+            Err(ProgramError::InvalidAccountData)
+        ));
+        assert!(matches!(
+            UserRecord::<Position>::load_mut(&read_only.info(), &user, &market, &program_id),
+            Err(ProgramError::InvalidAccountData)
+        ));
+    }
+}
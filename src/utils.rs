@@ -64,6 +64,35 @@ pub fn bytes_to_string<const N: usize>(bytes: &[u8; N]) -> Result<String, Progra
         .to_string())
 }
 
+/// Compares two 32 byte arrays as four `u64` words instead of byte by byte.
+#[inline(always)]
+pub fn memeq_32(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    // SAFETY: both arrays are 32 bytes, `read_unaligned` has no alignment requirement
+    let (a, b) = unsafe {
+        (
+            core::ptr::read_unaligned(a.as_ptr() as *const [u64; 4]),
+            core::ptr::read_unaligned(b.as_ptr() as *const [u64; 4]),
+        )
+    };
+    ((a[0] ^ b[0]) | (a[1] ^ b[1]) | (a[2] ^ b[2]) | (a[3] ^ b[3])) == 0
+}
+
+/// Compute-optimized pubkey equality, see [`memeq_32`].
+#[inline(always)]
+pub fn pubkey_eq(a: &Pubkey, b: &Pubkey) -> bool {
+    memeq_32(a, b)
+}
+
+/// Copies `src` into `dst` as four `u64` words.
+#[inline(always)]
+pub fn pubkey_copy(dst: &mut Pubkey, src: &Pubkey) {
+    // SAFETY: both arrays are 32 bytes, unaligned reads and writes are used
+    unsafe {
+        let words = core::ptr::read_unaligned(src.as_ptr() as *const [u64; 4]);
+        core::ptr::write_unaligned(dst.as_mut_ptr() as *mut [u64; 4], words);
+    }
+}
+
 pub const ERROR_STRING_TOO_LONG: u32 = 1;
 pub const ERROR_INVALID_UTF8: u32 = 2;
 
@@ -103,3 +132,19 @@ fn test_bytes_to_string() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), "hi");
 }
+
+#[test]
+fn test_pubkey_eq_and_copy() {
+    let a = [7u8; 32];
+    let mut b = [7u8; 32];
+    assert!(pubkey_eq(&a, &b));
+    b[31] = 8;
+    assert!(!pubkey_eq(&a, &b));
+    b[31] = 7;
+    b[0] = 0;
+    assert!(!memeq_32(&a, &b));
+
+    let mut c = [0u8; 32];
+    pubkey_copy(&mut c, &b);
+    assert_eq!(c, b);
+}
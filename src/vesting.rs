@@ -0,0 +1,151 @@
+//! Token vesting schedules with a cliff, unlocking linearly or in steps and
+//! tracking the amount released so far.
+//!
+//! ```ignore
+//! let amount = vesting.schedule.release(clock.unix_timestamp as u64)?;
+//! // transfer `amount` from the vault to the beneficiary
+//! ```
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{pod_enum, to_u64_checked, trace, widen, PodU64LE};
+
+pod_enum! {
+    /// How a [`VestingSchedule`] unlocks between its start and end.
+    pub enum VestingCurve(PodVestingCurve) {
+        /// Unlocks continuously.
+        Linear = 0,
+        /// Unlocks in equal steps, once per full `period`.
+        Step = 1,
+    }
+}
+
+/// Vesting of `total` tokens from `start` to `end`, nothing being claimable
+/// before `cliff`. Timestamps are unix seconds.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub start: PodU64LE,
+    pub cliff: PodU64LE,
+    pub end: PodU64LE,
+    /// Step length in seconds, only used by [`VestingCurve::Step`].
+    pub period: PodU64LE,
+    pub total: PodU64LE,
+    /// Amount claimed so far.
+    pub released: PodU64LE,
+    pub curve: PodVestingCurve,
+}
+
+impl VestingSchedule {
+    /// Fails with `InvalidArgument` unless `start <= cliff <= end`, `start < end`
+    /// and, for step vesting, `period > 0`.
+    #[track_caller]
+    pub fn new(
+        start: u64,
+        cliff: u64,
+        end: u64,
+        period: u64,
+        total: u64,
+        curve: VestingCurve,
+    ) -> Result<Self, ProgramError> {
+        if start >= end || cliff < start || cliff > end {
+            return Err(trace(
+                "Invalid vesting timestamps",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        if curve == VestingCurve::Step && period == 0 {
+            return Err(trace(
+                "Step vesting needs a period",
+                ProgramError::InvalidArgument,
+            ));
+        }
+        Ok(Self {
+            start: PodU64LE::new(start),
+            cliff: PodU64LE::new(cliff),
+            end: PodU64LE::new(end),
+            period: PodU64LE::new(period),
+            total: PodU64LE::new(total),
+            released: PodU64LE::new(0),
+            curve: PodVestingCurve::new(curve),
+        })
+    }
+
+    /// Total amount vested at `now`, released or not.
+    #[track_caller]
+    pub fn vested_at(&self, now: u64) -> Result<u64, ProgramError> {
+        let (start, end, total) = (self.start.get(), self.end.get(), self.total.get());
+        if now < self.cliff.get() || now <= start {
+            return Ok(0);
+        }
+        if now >= end {
+            return Ok(total);
+        }
+
+        let (elapsed, duration) = match self.curve.get()? {
+            VestingCurve::Linear => (now - start, end - start),
+            VestingCurve::Step => {
+                let period = self.period.get();
+                // The last step may be shorter, it completes at `end`
+                ((now - start) / period, (end - start).div_ceil(period))
+            }
+        };
+        to_u64_checked(widen(total) * widen(elapsed) / widen(duration))
+    }
+
+    /// Amount vested at `now` but not released yet.
+    #[track_caller]
+    pub fn claimable(&self, now: u64) -> Result<u64, ProgramError> {
+        Ok(self.vested_at(now)?.saturating_sub(self.released.get()))
+    }
+
+    /// Marks everything claimable at `now` as released and returns that amount.
+    #[track_caller]
+    pub fn release(&mut self, now: u64) -> Result<u64, ProgramError> {
+        let amount = self.claimable(now)?;
+        self.released.set(self.released.get() + amount);
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_vesting() {
+        let mut schedule =
+            VestingSchedule::new(1_000, 1_250, 2_000, 0, 1_000_000, VestingCurve::Linear).unwrap();
+
+        assert_eq!(schedule.vested_at(1_200), Ok(0));
+        assert_eq!(schedule.vested_at(1_250), Ok(250_000));
+        assert_eq!(schedule.vested_at(1_500), Ok(500_000));
+        assert_eq!(schedule.vested_at(5_000), Ok(1_000_000));
+
+        assert_eq!(schedule.release(1_500), Ok(500_000));
+        assert_eq!(schedule.claimable(1_500), Ok(0));
+        assert_eq!(schedule.release(1_750), Ok(250_000));
+        assert_eq!(schedule.release(2_000), Ok(250_000));
+        assert_eq!(schedule.released.get(), 1_000_000);
+    }
+
+    #[test]
+    fn test_step_vesting() {
+        // 4 steps of 250s, the last one cut short to 100s
+        let schedule = VestingSchedule::new(0, 0, 850, 250, 400, VestingCurve::Step).unwrap();
+
+        assert_eq!(schedule.vested_at(249), Ok(0));
+        assert_eq!(schedule.vested_at(250), Ok(100));
+        assert_eq!(schedule.vested_at(749), Ok(200));
+        assert_eq!(schedule.vested_at(750), Ok(300));
+        assert_eq!(schedule.vested_at(850), Ok(400));
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        assert!(VestingSchedule::new(10, 5, 20, 0, 1, VestingCurve::Linear).is_err());
+        assert!(VestingSchedule::new(10, 10, 10, 0, 1, VestingCurve::Linear).is_err());
+        assert!(VestingSchedule::new(0, 0, 10, 0, 1, VestingCurve::Step).is_err());
+        assert_eq!(core::mem::align_of::<VestingSchedule>(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+//! Zero-copy Wormhole VAA header parser.
+//!
+//! Only checks the structure of a VAA, guardian signatures must still be
+//! verified by the core bridge before trusting its contents.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::trace;
+
+/// Length of one guardian signature entry: guardian index + 65 byte signature.
+pub const VAA_SIGNATURE_LEN: usize = 66;
+
+/// Header length before the signatures.
+const HEADER_LEN: usize = 6;
+/// Body length before the payload.
+const BODY_LEN: usize = 51;
+
+/// Zero-copy view over a version 1 VAA. Multi-byte fields are big-endian.
+pub struct VaaView<'a> {
+    data: &'a [u8],
+    body: &'a [u8],
+}
+
+impl<'a> VaaView<'a> {
+    pub fn try_from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        if data.len() < HEADER_LEN || data[0] != 1 {
+            return Err(trace("Invalid VAA header", ProgramError::InvalidArgument));
+        }
+        let body_start = HEADER_LEN + data[5] as usize * VAA_SIGNATURE_LEN;
+        match data.get(body_start..) {
+            Some(body) if body.len() >= BODY_LEN => Ok(Self { data, body }),
+            _ => Err(trace("VAA is too short", ProgramError::InvalidArgument)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn version(&self) -> u8 {
+        self.data[0]
+    }
+
+    #[inline(always)]
+    pub fn guardian_set_index(&self) -> u32 {
+        u32::from_be_bytes(self.data[1..5].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn signatures_len(&self) -> u8 {
+        self.data[5]
+    }
+
+    /// Guardian signature entries, [`VAA_SIGNATURE_LEN`] bytes each.
+    #[inline(always)]
+    pub fn signatures(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.data[HEADER_LEN..HEADER_LEN + self.signatures_len() as usize * VAA_SIGNATURE_LEN]
+            .chunks_exact(VAA_SIGNATURE_LEN)
+    }
+
+    /// Signed body, hashed by the guardians.
+    #[inline(always)]
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    #[inline(always)]
+    pub fn timestamp(&self) -> u32 {
+        u32::from_be_bytes(self.body[0..4].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn nonce(&self) -> u32 {
+        u32::from_be_bytes(self.body[4..8].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn emitter_chain(&self) -> u16 {
+        u16::from_be_bytes(self.body[8..10].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn emitter_address(&self) -> &'a [u8; 32] {
+        self.body[10..42].try_into().unwrap()
+    }
+
+    #[inline(always)]
+    pub fn sequence(&self) -> u64 {
+        u64::from_be_bytes(self.body[42..50].try_into().unwrap())
+    }
+
+    #[inline(always)]
+    pub fn consistency_level(&self) -> u8 {
+        self.body[50]
+    }
+
+    #[inline(always)]
+    pub fn payload(&self) -> &'a [u8] {
+        &self.body[BODY_LEN..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_vaa_view() {
+        let mut data = Vec::new();
+        data.push(1);
+        data.extend_from_slice(&3u32.to_be_bytes());
+        data.push(2);
+        data.extend_from_slice(&[7; 2 * VAA_SIGNATURE_LEN]);
+        data.extend_from_slice(&100u32.to_be_bytes());
+        data.extend_from_slice(&5u32.to_be_bytes());
+        data.extend_from_slice(&2u16.to_be_bytes());
+        data.extend_from_slice(&[9; 32]);
+        data.extend_from_slice(&42u64.to_be_bytes());
+        data.push(1);
+        data.extend_from_slice(b"hello");
+
+        let vaa = VaaView::try_from_bytes(&data).unwrap();
+        assert_eq!(vaa.version(), 1);
+        assert_eq!(vaa.guardian_set_index(), 3);
+        assert_eq!(vaa.signatures().count(), 2);
+        assert_eq!(vaa.timestamp(), 100);
+        assert_eq!(vaa.nonce(), 5);
+        assert_eq!(vaa.emitter_chain(), 2);
+        assert_eq!(vaa.emitter_address(), &[9; 32]);
+        assert_eq!(vaa.sequence(), 42);
+        assert_eq!(vaa.consistency_level(), 1);
+        assert_eq!(vaa.payload(), b"hello");
+        assert_eq!(vaa.body().len(), BODY_LEN + 5);
+
+        assert!(VaaView::try_from_bytes(&data[..HEADER_LEN + 2 * VAA_SIGNATURE_LEN + 50]).is_err());
+        data[0] = 2;
+        assert!(VaaView::try_from_bytes(&data).is_err());
+    }
+}